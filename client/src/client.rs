@@ -0,0 +1,389 @@
+use std::time::Duration;
+
+use async_stream::try_stream;
+use futures::Stream;
+use reqwest::{
+    header::{ETAG, IF_MATCH},
+    Method, RequestBuilder, Response,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use url::Url;
+
+use crate::{
+    error::{Error, Result},
+    model::{DogModel, MerchantModel, MerchantRecordModel},
+    paging::Paged,
+    patch::MERGE_PATCH_CONTENT_TYPE,
+    response::{
+        DogData, DogList, Envelope, ErrorBody, MerchantData, MerchantList, MerchantRecordData, MerchantRecordList,
+        MerchantRecordStatusData, NearbyDogList, ZkpData,
+    },
+    retry::{retry_reason, Idempotence, RetryPolicy},
+    schema::{
+        CreateDogSchema, CreateMerchantRecordSchema, CreateMerchantSchema, DogFilterOptions, FilterOptions,
+        MerchantRecordFilterOptions, NearbyOptions, RecordStatusFilter, RenewMerchantRecordSchema,
+        UpdateDogSchema, UpdateMerchantSchema, ZkpSignInSchema, ZkpSignUpSchema,
+    },
+};
+
+const API_KEY_HEADER: &str = "x-api-key";
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+const NO_QUERY: &[(&str, &str)] = &[];
+
+/// A resource together with the ETag to send back as `If-Match` when changing it.
+#[derive(Debug)]
+pub struct Tagged<T> {
+    pub value: T,
+    pub etag: Option<String>,
+}
+
+/// Async client for the API. Cheap to clone; clones share one connection pool.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    api_key: Option<String>,
+    retry: RetryPolicy,
+}
+
+pub struct ClientBuilder {
+    base_url: String,
+    api_key: Option<String>,
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+    http: Option<reqwest::Client>,
+}
+
+impl ClientBuilder {
+    /// Merchant API key, sent as `X-Api-Key`. Needed to create and renew merchant records.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Per-attempt timeout. Ignored when an `http_client` is supplied.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Use an existing `reqwest::Client`, e.g. one shared with other code or with custom TLS.
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        let http = match self.http {
+            Some(http) => http,
+            None => {
+                let mut builder = reqwest::Client::builder();
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                builder.build()?
+            }
+        };
+        Ok(Client {
+            http,
+            base_url: Url::parse(&self.base_url)?,
+            api_key: self.api_key,
+            retry: self.retry,
+        })
+    }
+}
+
+impl Client {
+    pub fn new(base_url: &str) -> Result<Client> {
+        Client::builder(base_url).build()
+    }
+
+    pub fn builder(base_url: &str) -> ClientBuilder {
+        ClientBuilder {
+            base_url: base_url.to_string(),
+            api_key: None,
+            retry: RetryPolicy::default(),
+            timeout: None,
+            http: None,
+        }
+    }
+
+    // Dogs
+
+    pub async fn list_dogs(&self, opts: &DogFilterOptions) -> Result<DogList> {
+        self.get("/api/dogs", opts).await
+    }
+
+    /// Every dog matching `opts`, following `next` links page by page.
+    pub fn dogs<'a>(&'a self, opts: &'a DogFilterOptions) -> impl Stream<Item = Result<DogModel>> + 'a {
+        self.paginate::<DogList, _>("/api/dogs".to_string(), opts)
+    }
+
+    pub async fn nearby_dogs(&self, opts: &NearbyOptions) -> Result<NearbyDogList> {
+        self.get("/api/dogs/nearby", opts).await
+    }
+
+    pub async fn get_dog(&self, id: uuid::Uuid) -> Result<Tagged<DogModel>> {
+        let response = self.send(Method::GET, &format!("/api/dogs/{}", id), Idempotence::Safe, |r| r).await?;
+        tagged(response, |data: DogData| data.dog).await
+    }
+
+    /// Sent with a fresh `Idempotency-Key`, so the create is retried without risk of duplicates.
+    pub async fn create_dog(&self, dog: &CreateDogSchema) -> Result<Tagged<DogModel>> {
+        let key = uuid::Uuid::new_v4().to_string();
+        let response = self
+            .send(Method::POST, "/api/dogs/", Idempotence::Safe, |r| {
+                r.header(IDEMPOTENCY_KEY_HEADER, &key).json(dog)
+            })
+            .await?;
+        tagged(response, |data: DogData| data.dog).await
+    }
+
+    /// Applies a merge patch. `if_match` is the ETag the change is based on, or `*`.
+    pub async fn update_dog(&self, id: uuid::Uuid, patch: &UpdateDogSchema, if_match: &str) -> Result<Tagged<DogModel>> {
+        let response = self.merge_patch(&format!("/api/dogs/{}", id), patch, if_match).await?;
+        tagged(response, |data: DogData| data.dog).await
+    }
+
+    pub async fn delete_dog(&self, id: uuid::Uuid) -> Result<()> {
+        self.send(Method::DELETE, &format!("/api/dogs/{}", id), Idempotence::Unsafe, |r| r).await?;
+        Ok(())
+    }
+
+    pub async fn restore_dog(&self, id: uuid::Uuid) -> Result<Tagged<DogModel>> {
+        let response = self
+            .send(Method::POST, &format!("/api/dogs/{}/restore", id), Idempotence::Unsafe, |r| r)
+            .await?;
+        tagged(response, |data: DogData| data.dog).await
+    }
+
+    // Merchants
+
+    pub async fn list_merchants(&self, opts: &FilterOptions) -> Result<MerchantList> {
+        self.get("/api/merchants", opts).await
+    }
+
+    pub fn merchants<'a>(&'a self, opts: &'a FilterOptions) -> impl Stream<Item = Result<MerchantModel>> + 'a {
+        self.paginate::<MerchantList, _>("/api/merchants".to_string(), opts)
+    }
+
+    pub async fn get_merchant(&self, id: uuid::Uuid) -> Result<Tagged<MerchantModel>> {
+        let response = self.send(Method::GET, &format!("/api/merchants/{}", id), Idempotence::Safe, |r| r).await?;
+        tagged(response, |data: MerchantData| data.merchant).await
+    }
+
+    pub async fn create_merchant(&self, merchant: &CreateMerchantSchema) -> Result<Tagged<MerchantModel>> {
+        let response = self
+            .send(Method::POST, "/api/merchants/", Idempotence::Unsafe, |r| r.json(merchant))
+            .await?;
+        tagged(response, |data: MerchantData| data.merchant).await
+    }
+
+    pub async fn update_merchant(
+        &self,
+        id: uuid::Uuid,
+        patch: &UpdateMerchantSchema,
+        if_match: &str,
+    ) -> Result<Tagged<MerchantModel>> {
+        let response = self.merge_patch(&format!("/api/merchants/{}", id), patch, if_match).await?;
+        tagged(response, |data: MerchantData| data.merchant).await
+    }
+
+    pub async fn delete_merchant(&self, id: uuid::Uuid) -> Result<()> {
+        self.send(Method::DELETE, &format!("/api/merchants/{}", id), Idempotence::Unsafe, |r| r).await?;
+        Ok(())
+    }
+
+    pub async fn restore_merchant(&self, id: uuid::Uuid) -> Result<Tagged<MerchantModel>> {
+        let response = self
+            .send(Method::POST, &format!("/api/merchants/{}/restore", id), Idempotence::Unsafe, |r| r)
+            .await?;
+        tagged(response, |data: MerchantData| data.merchant).await
+    }
+
+    // Merchant records
+
+    pub async fn list_merchant_records(
+        &self,
+        merchant_id: uuid::Uuid,
+        opts: &MerchantRecordFilterOptions,
+    ) -> Result<MerchantRecordList> {
+        self.get(&format!("/api/merchantrecords/by_merchant/{}", merchant_id), opts).await
+    }
+
+    pub fn merchant_records<'a>(
+        &'a self,
+        merchant_id: uuid::Uuid,
+        opts: &'a MerchantRecordFilterOptions,
+    ) -> impl Stream<Item = Result<MerchantRecordModel>> + 'a {
+        self.paginate::<MerchantRecordList, _>(format!("/api/merchantrecords/by_merchant/{}", merchant_id), opts)
+    }
+
+    /// The record and whether it is still active. Expired records carry an explanatory `message`.
+    pub async fn get_merchant_record(
+        &self,
+        id: uuid::Uuid,
+        opts: &RecordStatusFilter,
+    ) -> Result<Envelope<MerchantRecordStatusData>> {
+        self.get(&format!("/api/merchantrecords/{}", id), opts).await
+    }
+
+    /// Needs an API key with the `records:write` scope.
+    pub async fn create_merchant_record(&self, record: &CreateMerchantRecordSchema) -> Result<MerchantRecordModel> {
+        let key = uuid::Uuid::new_v4().to_string();
+        let response = self
+            .send(Method::POST, "/api/merchantrecords/", Idempotence::Safe, |r| {
+                r.header(IDEMPOTENCY_KEY_HEADER, &key).json(record)
+            })
+            .await?;
+        let envelope: Envelope<MerchantRecordData> = decode(response).await?;
+        Ok(envelope.data.merchant_record)
+    }
+
+    /// Needs an API key with the `records:write` scope.
+    pub async fn renew_merchant_record(
+        &self,
+        id: uuid::Uuid,
+        renewal: &RenewMerchantRecordSchema,
+    ) -> Result<MerchantRecordModel> {
+        let response = self
+            .send(Method::POST, &format!("/api/merchantrecords/{}/renew", id), Idempotence::Unsafe, |r| {
+                r.json(renewal)
+            })
+            .await?;
+        let envelope: Envelope<MerchantRecordData> = decode(response).await?;
+        Ok(envelope.data.merchant_record)
+    }
+
+    pub async fn delete_merchant_record(&self, id: uuid::Uuid) -> Result<()> {
+        self.send(Method::DELETE, &format!("/api/merchantrecords/{}", id), Idempotence::Unsafe, |r| r)
+            .await?;
+        Ok(())
+    }
+
+    // ZKP
+
+    pub async fn zkp_sign_up(&self, sign_up: &ZkpSignUpSchema) -> Result<()> {
+        let key = uuid::Uuid::new_v4().to_string();
+        self.send(Method::POST, "/api/zkp/signup", Idempotence::Safe, |r| {
+            r.header(IDEMPOTENCY_KEY_HEADER, &key).json(sign_up)
+        })
+        .await?;
+        Ok(())
+    }
+
+    pub async fn zkp_sign_in(&self, sign_in: &ZkpSignInSchema) -> Result<ZkpData> {
+        let response = self
+            .send(Method::POST, "/api/zkp/signin/", Idempotence::Unsafe, |r| r.json(sign_in))
+            .await?;
+        let envelope: Envelope<ZkpData> = decode(response).await?;
+        Ok(envelope.data)
+    }
+
+    /// Items of every page of a list, starting from `path` with `query` and following each
+    /// page's `next` link, which carries the query on to later pages.
+    pub fn paginate<'a, P, Q>(&'a self, path: String, query: &'a Q) -> impl Stream<Item = Result<P::Item>> + 'a
+    where
+        P: Paged + 'a,
+        Q: Serialize + ?Sized + Sync,
+    {
+        try_stream! {
+            let mut page: P = self.get(&path, query).await?;
+            loop {
+                let (items, links) = page.into_parts();
+                for item in items {
+                    yield item;
+                }
+                let Some(next) = links.next else {
+                    break;
+                };
+                page = self.get(&next, NO_QUERY).await?;
+            }
+        }
+    }
+
+    async fn get<T, Q>(&self, path: &str, query: &Q) -> Result<T>
+    where
+        T: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        let response = self.send(Method::GET, path, Idempotence::Safe, |r| r.query(query)).await?;
+        decode(response).await
+    }
+
+    async fn merge_patch<B: Serialize>(&self, path: &str, patch: &B, if_match: &str) -> Result<Response> {
+        let body = serde_json::to_vec(patch)?;
+        self.send(Method::PATCH, path, Idempotence::Unsafe, |r| {
+            r.header(IF_MATCH, if_match)
+                .header(reqwest::header::CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE)
+                .body(body.clone())
+        })
+        .await
+    }
+
+    /// Sends the request built by `build`, retrying as the `RetryPolicy` allows, and turns
+    /// error statuses into `Error::Api`.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        idempotence: Idempotence,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Response> {
+        let url = self.base_url.join(path)?;
+        let mut attempt = 0;
+        loop {
+            let mut request = self.http.request(method.clone(), url.clone());
+            if let Some(api_key) = &self.api_key {
+                request = request.header(API_KEY_HEADER, api_key);
+            }
+            let outcome = build(request).send().await;
+
+            match retry_reason(&outcome, idempotence) {
+                Some(retry_after) if attempt < self.retry.max_retries => {
+                    tokio::time::sleep(self.retry.delay(attempt, retry_after)).await;
+                    attempt += 1;
+                }
+                _ => return check(outcome?).await,
+            }
+        }
+    }
+}
+
+async fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.bytes().await?;
+    Err(Error::Api {
+        status,
+        body: serde_json::from_slice::<ErrorBody>(&body).ok(),
+    })
+}
+
+async fn decode<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let body = response.bytes().await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+async fn tagged<D, T>(response: Response, unwrap: impl FnOnce(D) -> T) -> Result<Tagged<T>>
+where
+    D: DeserializeOwned,
+{
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let envelope: Envelope<D> = decode(response).await?;
+    Ok(Tagged {
+        value: unwrap(envelope.data),
+        etag,
+    })
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+
+use crate::response::ErrorBody;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent, or its response could not be read.
+    Transport(reqwest::Error),
+    /// The server answered with a 4xx or 5xx. `body` is `None` when the response was not the
+    /// usual `{"status", "message"}` envelope, e.g. from a proxy in front of the server.
+    Api { status: StatusCode, body: Option<ErrorBody> },
+    /// A success response did not have the shape of the expected type.
+    Decode(serde_json::Error),
+    /// The base URL, or a path joined onto it, is not a valid URL.
+    Url(url::ParseError),
+}
+
+impl Error {
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::Api { status, .. } => Some(*status),
+            Error::Transport(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// The server's `message` for an API error.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Error::Api { body: Some(body), .. } => Some(&body.message),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "request failed: {}", e),
+            Error::Api { status, body: Some(body) } => write!(f, "{}: {}", status, body.message),
+            Error::Api { status, body: None } => write!(f, "{}", status),
+            Error::Decode(e) => write!(f, "unexpected response body: {}", e),
+            Error::Url(e) => write!(f, "invalid URL: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(e) => Some(e),
+            Error::Decode(e) => Some(e),
+            Error::Url(e) => Some(e),
+            Error::Api { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Transport(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Decode(e)
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(e: url::ParseError) -> Self {
+        Error::Url(e)
+    }
+}
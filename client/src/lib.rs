@@ -0,0 +1,30 @@
+//! Async client for the dog registry API.
+//!
+//! Request and response types are the server's own, from the `dogregistry-types` crate both
+//! depend on, so a field renamed on the server is a compile error here rather than a decode
+//! failure at runtime. The client uses it without the `server` feature, so it does not pull
+//! in axum or sqlx.
+//!
+//! ```no_run
+//! # async fn run() -> dogregistry_client::Result<()> {
+//! use futures::TryStreamExt;
+//! use dogregistry_client::{schema::DogFilterOptions, Client};
+//!
+//! let client = Client::new("http://localhost:8000")?;
+//! let opts = DogFilterOptions { breed: Some("beagle".to_string()), ..Default::default() };
+//! let beagles: Vec<_> = client.dogs(&opts).try_collect().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub use dogregistry_types::{model, pagination, patch, response, schema};
+
+mod client;
+mod error;
+mod paging;
+mod retry;
+
+pub use client::{Client, ClientBuilder, Tagged};
+pub use error::{Error, Result};
+pub use paging::Paged;
+pub use retry::RetryPolicy;
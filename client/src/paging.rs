@@ -0,0 +1,55 @@
+use serde::de::DeserializeOwned;
+
+use crate::{
+    model::{AuditLogModel, DogModel, LostReportModel, MerchantModel, MerchantRecordModel},
+    pagination::Links,
+    response::{AuditLogList, DogList, LostReportList, MerchantList, MerchantRecordList},
+};
+
+/// A page of a keyset-paginated list, as returned by the list endpoints.
+pub trait Paged: DeserializeOwned {
+    type Item;
+
+    /// The page's items and its `next`/`prev` links.
+    fn into_parts(self) -> (Vec<Self::Item>, Links);
+}
+
+impl Paged for DogList {
+    type Item = DogModel;
+
+    fn into_parts(self) -> (Vec<DogModel>, Links) {
+        (self.dogs, self.links)
+    }
+}
+
+impl Paged for MerchantList {
+    type Item = MerchantModel;
+
+    fn into_parts(self) -> (Vec<MerchantModel>, Links) {
+        (self.merchants, self.links)
+    }
+}
+
+impl Paged for MerchantRecordList {
+    type Item = MerchantRecordModel;
+
+    fn into_parts(self) -> (Vec<MerchantRecordModel>, Links) {
+        (self.merchant_records, self.links)
+    }
+}
+
+impl Paged for LostReportList {
+    type Item = LostReportModel;
+
+    fn into_parts(self) -> (Vec<LostReportModel>, Links) {
+        (self.lost_reports, self.links)
+    }
+}
+
+impl Paged for AuditLogList {
+    type Item = AuditLogModel;
+
+    fn into_parts(self) -> (Vec<AuditLogModel>, Links) {
+        (self.audit_log, self.links)
+    }
+}
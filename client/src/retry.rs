@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use reqwest::{header::RETRY_AFTER, Response, StatusCode};
+
+/// How failed requests are retried, with exponential backoff between attempts.
+///
+/// A request the server cannot have acted on (connection refused, 429) is always retried.
+/// Timeouts and gateway errors are only retried for requests that are safe to repeat: reads,
+/// and creates sent with an `Idempotency-Key`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying.
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Wait before retry number `attempt` (from 0), honouring the server's `Retry-After`.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        retry_after
+            .unwrap_or_else(|| self.base_delay.saturating_mul(1 << attempt.min(16)))
+            .min(self.max_delay)
+    }
+}
+
+/// Whether a request may be sent again without risking doing its work twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Idempotence {
+    Safe,
+    Unsafe,
+}
+
+/// `Some(retry_after)` when `outcome` should be retried.
+pub(crate) fn retry_reason(
+    outcome: &Result<Response, reqwest::Error>,
+    idempotence: Idempotence,
+) -> Option<Option<Duration>> {
+    let safe = idempotence == Idempotence::Safe;
+    match outcome {
+        Ok(response) => match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => Some(retry_after(response)),
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT if safe => {
+                Some(retry_after(response))
+            }
+            _ => None,
+        },
+        Err(e) if e.is_connect() => Some(None),
+        Err(e) if e.is_timeout() && safe => Some(None),
+        Err(_) => None,
+    }
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
@@ -0,0 +1,135 @@
+//! Runs the client against a real server: the binary named by `DOGREGISTRY_SERVER_BIN`,
+//! started on a free port against the database in `DATABASE_URL`, which it migrates.
+//! Ignored by default; run with `cargo test -- --ignored` once both are set.
+
+use std::{process::Stdio, time::Duration};
+
+use dogregistry_client::{
+    patch::Patch,
+    schema::{CreateDogSchema, CreateMerchantSchema, DogFilterOptions, MerchantRecordFilterOptions, UpdateDogSchema},
+    Client, Error,
+};
+use futures::TryStreamExt;
+use tokio::process::{Child, Command};
+
+const OPERATOR_TOKEN: &str = "integration-test-operator-token-0123456789";
+
+/// The server process, killed when dropped.
+struct Server {
+    _process: Child,
+    base_url: String,
+}
+
+async fn spawn_server() -> Server {
+    let bin = std::env::var("DOGREGISTRY_SERVER_BIN").expect("DOGREGISTRY_SERVER_BIN must name the server binary");
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let process = Command::new(bin)
+        .args(["serve", "--bind", &format!("127.0.0.1:{port}"), "--chain-backend", "disabled"])
+        .env("APP_DATABASE__MIGRATE_ON_STARTUP", "true")
+        .env("APP_AUTH__OPERATOR_TOKEN", OPERATOR_TOKEN)
+        .env("APP_WEBHOOKS__ENABLED", "false")
+        // Tests run in parallel, so each server needs its own metrics port too.
+        .env("APP_SERVER__METRICS_BIND", "127.0.0.1:0")
+        .stdout(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start the server");
+
+    let base_url = format!("http://127.0.0.1:{port}");
+    let healthz = format!("{base_url}/healthz");
+    for _ in 0..100 {
+        if reqwest::get(&healthz).await.is_ok_and(|response| response.status().is_success()) {
+            return Server { _process: process, base_url };
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("the server did not come up on port {port}");
+}
+
+fn new_dog(name: &str) -> CreateDogSchema {
+    CreateDogSchema {
+        name: name.to_string(),
+        breed: "beagle".to_string(),
+        color: "tricolour".to_string(),
+        location: "Utrecht".to_string(),
+        latitude: Some(52.09),
+        longitude: Some(5.12),
+        prooflevel: 1,
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs DOGREGISTRY_SERVER_BIN and DATABASE_URL"]
+async fn dog_lifecycle() {
+    let server = spawn_server().await;
+    let client = Client::builder(&server.base_url).api_key(OPERATOR_TOKEN).build().unwrap();
+
+    let created = client.create_dog(&new_dog("Rex")).await.unwrap();
+    let id = created.value.id;
+    let fetched = client.get_dog(id).await.unwrap();
+    assert_eq!(fetched.value.name, "Rex");
+    let etag = fetched.etag.expect("dogs are served with an ETag");
+
+    let rename = UpdateDogSchema { name: Patch::Value("Max".to_string()), ..Default::default() };
+    let anonymous = Client::new(&server.base_url).unwrap();
+    let refused = anonymous.update_dog(id, &rename, &etag).await.unwrap_err();
+    assert_eq!(refused.status().map(|status| status.as_u16()), Some(401));
+    let updated = client.update_dog(id, &rename, &etag).await.unwrap();
+    assert_eq!(updated.value.name, "Max");
+    assert_eq!(updated.value.breed.as_deref(), Some("beagle"));
+
+    // The old ETag no longer matches.
+    let stale = client.update_dog(id, &rename, &etag).await.unwrap_err();
+    assert_eq!(stale.status().map(|status| status.as_u16()), Some(412));
+
+    client.delete_dog(id).await.unwrap();
+    let missing = client.get_dog(id).await.unwrap_err();
+    assert!(matches!(missing, Error::Api { .. }));
+    assert_eq!(missing.status().map(|status| status.as_u16()), Some(404));
+    assert!(missing.message().is_some());
+
+    let restored = client.restore_dog(id).await.unwrap();
+    assert_eq!(restored.value.name, "Max");
+}
+
+#[tokio::test]
+#[ignore = "needs DOGREGISTRY_SERVER_BIN and DATABASE_URL"]
+async fn follows_pages_to_the_end() {
+    let server = spawn_server().await;
+    let client = Client::new(&server.base_url).unwrap();
+
+    // A colour no other test uses, so the filter sees exactly these dogs.
+    let color = format!("paged-{}", uuid::Uuid::new_v4());
+    for n in 0..5 {
+        let dog = CreateDogSchema { color: color.clone(), ..new_dog(&format!("Pup {n}")) };
+        client.create_dog(&dog).await.unwrap();
+    }
+
+    let opts = DogFilterOptions { color: Some(color), limit: Some(2), ..Default::default() };
+    let first = client.list_dogs(&opts).await.unwrap();
+    assert_eq!(first.dogs.len(), 2);
+    let dogs: Vec<_> = client.dogs(&opts).try_collect().await.unwrap();
+    let mut names: Vec<String> = dogs.into_iter().map(|dog| dog.name).collect();
+    names.sort();
+    assert_eq!(names, ["Pup 0", "Pup 1", "Pup 2", "Pup 3", "Pup 4"]);
+}
+
+#[tokio::test]
+#[ignore = "needs DOGREGISTRY_SERVER_BIN and DATABASE_URL"]
+async fn merchant_records_need_a_key() {
+    let server = spawn_server().await;
+    let operator = Client::builder(&server.base_url).api_key(OPERATOR_TOKEN).build().unwrap();
+
+    let last_data_hash = uuid::Uuid::new_v4().simple().to_string();
+    let merchant = operator.create_merchant(&CreateMerchantSchema { last_data_hash }).await.unwrap().value;
+
+    let records = operator.list_merchant_records(merchant.id, &MerchantRecordFilterOptions::default()).await.unwrap();
+    assert_eq!(records.results, 0);
+
+    let anonymous = Client::new(&server.base_url).unwrap();
+    let refused = anonymous
+        .list_merchant_records(merchant.id, &MerchantRecordFilterOptions::default())
+        .await
+        .unwrap_err();
+    assert_eq!(refused.status().map(|status| status.as_u16()), Some(401));
+}
@@ -1,4 +1,6 @@
 -- Add down migration script here
-DROP TABLE IF EXISTS dogs;
+-- zkptable references dogs, so it has to go first.
+DROP TABLE IF EXISTS zkptable;
 
-DROP TABLE IF EXISTS zkptable;
\ No newline at end of file
+DROP TABLE IF EXISTS dogs;
+DROP FUNCTION IF EXISTS update_updated_on_dogs();
@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS merchant_api_keys (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    merchant_id UUID NOT NULL references merchants(id),
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL DEFAULT ARRAY['records:read', 'records:write'],
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP,
+    revoked_at TIMESTAMP,
+    rotated_from UUID references merchant_api_keys(id)
+);
+
+CREATE INDEX IF NOT EXISTS merchant_api_keys_merchant_id_idx ON merchant_api_keys (merchant_id);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE merchantsrecord
+    DROP COLUMN IF EXISTS signature,
+    DROP COLUMN IF EXISTS signing_key_id;
+
+DROP TABLE IF EXISTS merchant_signing_keys;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS merchant_signing_keys (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    merchant_id UUID NOT NULL references merchants(id),
+    public_key VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS merchant_signing_keys_merchant_id_idx ON merchant_signing_keys (merchant_id);
+
+ALTER TABLE merchantsrecord
+    ADD COLUMN IF NOT EXISTS signing_key_id UUID references merchant_signing_keys(id),
+    ADD COLUMN IF NOT EXISTS signature VARCHAR(128);
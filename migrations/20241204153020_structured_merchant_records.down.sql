@@ -0,0 +1,13 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS merchantsrecord_data_record_idx;
+DROP INDEX IF EXISTS merchantsrecord_merchant_id_record_type_idx;
+
+ALTER TABLE merchantsrecord
+    DROP COLUMN IF EXISTS data_hash,
+    DROP COLUMN IF EXISTS record_type,
+    ALTER COLUMN data_record TYPE TEXT USING data_record::text;
+
+DROP TABLE IF EXISTS merchant_record_types;
+
+-- The prev_data_hash foreign key is not restored: chained records reference hashes
+-- that are no longer any merchant's last_data_hash.
@@ -0,0 +1,22 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS merchant_record_types (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    merchant_id UUID NOT NULL references merchants(id),
+    record_type VARCHAR(100) NOT NULL,
+    json_schema JSONB NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (merchant_id, record_type)
+);
+
+-- Records now advance the merchant's hash chain, so last_data_hash changes after every insert
+-- and can no longer be the target of a foreign key.
+ALTER TABLE merchantsrecord DROP CONSTRAINT IF EXISTS merchantsrecord_prev_data_hash_fkey;
+
+ALTER TABLE merchantsrecord
+    ALTER COLUMN data_record TYPE JSONB USING to_jsonb(data_record),
+    ADD COLUMN IF NOT EXISTS record_type VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS data_hash VARCHAR(64) UNIQUE;
+
+CREATE INDEX IF NOT EXISTS merchantsrecord_merchant_id_record_type_idx ON merchantsrecord (merchant_id, record_type);
+CREATE INDEX IF NOT EXISTS merchantsrecord_data_record_idx ON merchantsrecord USING GIN (data_record jsonb_path_ops);
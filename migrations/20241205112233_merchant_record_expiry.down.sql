@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS merchantsrecord_archive;
+DROP TABLE IF EXISTS merchant_record_events;
+DROP INDEX IF EXISTS merchantsrecord_status_valid_until_idx;
+
+ALTER TABLE merchantsrecord
+    DROP COLUMN IF EXISTS expired_at,
+    DROP COLUMN IF EXISTS status;
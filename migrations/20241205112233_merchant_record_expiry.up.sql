@@ -0,0 +1,24 @@
+-- Add up migration script here
+
+ALTER TABLE merchantsrecord
+    ADD COLUMN IF NOT EXISTS status VARCHAR(16) NOT NULL DEFAULT 'active',
+    ADD COLUMN IF NOT EXISTS expired_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS merchantsrecord_status_valid_until_idx ON merchantsrecord (status, valid_until);
+
+CREATE TABLE IF NOT EXISTS merchant_record_events (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    merchant_record_id UUID NOT NULL,
+    merchant_id UUID,
+    event_type VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS merchant_record_events_merchant_id_idx ON merchant_record_events (merchant_id, created_at);
+
+CREATE TABLE IF NOT EXISTS merchantsrecord_archive (
+    LIKE merchantsrecord INCLUDING DEFAULTS,
+    archived_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id)
+);
@@ -0,0 +1,18 @@
+-- Add down migration script here
+ALTER TABLE merchantsrecord_archive
+    DROP COLUMN IF EXISTS supersedes,
+    DROP COLUMN IF EXISTS valid_from,
+    ALTER COLUMN archived_at TYPE TIMESTAMP USING archived_at AT TIME ZONE 'UTC',
+    ALTER COLUMN expired_at TYPE TIMESTAMP USING expired_at AT TIME ZONE 'UTC',
+    ALTER COLUMN valid_until TYPE TIMESTAMP USING valid_until AT TIME ZONE 'UTC',
+    ALTER COLUMN data_issued TYPE TIMESTAMP USING data_issued AT TIME ZONE 'UTC';
+
+DROP INDEX IF EXISTS merchantsrecord_supersedes_idx;
+
+ALTER TABLE merchantsrecord
+    DROP CONSTRAINT IF EXISTS merchantsrecord_validity_window_check,
+    DROP COLUMN IF EXISTS supersedes,
+    DROP COLUMN IF EXISTS valid_from,
+    ALTER COLUMN expired_at TYPE TIMESTAMP USING expired_at AT TIME ZONE 'UTC',
+    ALTER COLUMN valid_until TYPE TIMESTAMP USING valid_until AT TIME ZONE 'UTC',
+    ALTER COLUMN data_issued TYPE TIMESTAMP USING data_issued AT TIME ZONE 'UTC';
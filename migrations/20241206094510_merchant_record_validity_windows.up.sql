@@ -0,0 +1,30 @@
+-- Add up migration script here
+
+ALTER TABLE merchantsrecord
+    ALTER COLUMN data_issued TYPE TIMESTAMPTZ USING data_issued AT TIME ZONE 'UTC',
+    ALTER COLUMN data_issued SET DEFAULT CURRENT_TIMESTAMP,
+    ALTER COLUMN valid_until TYPE TIMESTAMPTZ USING valid_until AT TIME ZONE 'UTC',
+    ALTER COLUMN expired_at TYPE TIMESTAMPTZ USING expired_at AT TIME ZONE 'UTC',
+    ADD COLUMN IF NOT EXISTS valid_from TIMESTAMPTZ,
+    -- Not a foreign key: the superseded record may later be moved to merchantsrecord_archive.
+    ADD COLUMN IF NOT EXISTS supersedes UUID;
+
+UPDATE merchantsrecord SET valid_from = data_issued WHERE valid_from IS NULL;
+
+ALTER TABLE merchantsrecord
+    ALTER COLUMN valid_from SET NOT NULL,
+    ALTER COLUMN valid_from SET DEFAULT CURRENT_TIMESTAMP,
+    ADD CONSTRAINT merchantsrecord_validity_window_check CHECK (valid_until > valid_from) NOT VALID;
+
+-- A record can be renewed at most once; further renewals chain from the newest record.
+CREATE UNIQUE INDEX IF NOT EXISTS merchantsrecord_supersedes_idx ON merchantsrecord (supersedes);
+
+ALTER TABLE merchantsrecord_archive
+    ALTER COLUMN data_issued TYPE TIMESTAMPTZ USING data_issued AT TIME ZONE 'UTC',
+    ALTER COLUMN valid_until TYPE TIMESTAMPTZ USING valid_until AT TIME ZONE 'UTC',
+    ALTER COLUMN expired_at TYPE TIMESTAMPTZ USING expired_at AT TIME ZONE 'UTC',
+    ALTER COLUMN archived_at TYPE TIMESTAMPTZ USING archived_at AT TIME ZONE 'UTC',
+    ADD COLUMN IF NOT EXISTS valid_from TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS supersedes UUID;
+
+UPDATE merchantsrecord_archive SET valid_from = data_issued WHERE valid_from IS NULL;
@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS dogs_prooflevel_idx;
+DROP INDEX IF EXISTS dogs_location_idx;
+DROP INDEX IF EXISTS dogs_color_idx;
+DROP INDEX IF EXISTS dogs_breed_idx;
+DROP INDEX IF EXISTS dogs_search_vector_idx;
+
+ALTER TABLE dogs DROP COLUMN IF EXISTS search_vector;
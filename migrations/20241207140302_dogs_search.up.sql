@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+ALTER TABLE dogs
+    ADD COLUMN IF NOT EXISTS search_vector tsvector
+        GENERATED ALWAYS AS (
+            setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+            setweight(to_tsvector('simple', coalesce(breed, '')), 'B')
+        ) STORED;
+
+CREATE INDEX IF NOT EXISTS dogs_search_vector_idx ON dogs USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS dogs_breed_idx ON dogs (lower(breed));
+CREATE INDEX IF NOT EXISTS dogs_color_idx ON dogs (lower(color));
+CREATE INDEX IF NOT EXISTS dogs_location_idx ON dogs (lower(location));
+CREATE INDEX IF NOT EXISTS dogs_prooflevel_idx ON dogs (prooflevel);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS dogs_earth_location_idx;
+
+ALTER TABLE dogs
+    DROP CONSTRAINT IF EXISTS dogs_coordinates_check,
+    DROP COLUMN IF EXISTS longitude,
+    DROP COLUMN IF EXISTS latitude;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS dog_events;
+DROP TABLE IF EXISTS sightings;
+DROP TABLE IF EXISTS lost_reports;
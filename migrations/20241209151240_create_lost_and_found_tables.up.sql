@@ -0,0 +1,50 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS lost_reports (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    dog_id UUID NOT NULL references dogs(id),
+    status VARCHAR(16) NOT NULL DEFAULT 'lost',
+    last_seen_location VARCHAR(255),
+    latitude DOUBLE PRECISION,
+    longitude DOUBLE PRECISION,
+    description TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    resolved_at TIMESTAMP,
+    CONSTRAINT lost_reports_status_check CHECK (status IN ('lost', 'found', 'reunited', 'cancelled'))
+);
+
+-- At most one open report per dog.
+CREATE UNIQUE INDEX IF NOT EXISTS lost_reports_open_dog_idx ON lost_reports (dog_id)
+    WHERE status IN ('lost', 'found');
+
+CREATE TRIGGER update_lost_reports_updated_at
+    BEFORE UPDATE
+    ON lost_reports
+    FOR EACH ROW
+EXECUTE PROCEDURE update_updated_on_dogs();
+
+CREATE TABLE IF NOT EXISTS sightings (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    lost_report_id UUID NOT NULL references lost_reports(id),
+    status VARCHAR(16) NOT NULL DEFAULT 'reported',
+    location VARCHAR(255),
+    latitude DOUBLE PRECISION,
+    longitude DOUBLE PRECISION,
+    note TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    verified_at TIMESTAMP,
+    CONSTRAINT sightings_status_check CHECK (status IN ('reported', 'verified', 'rejected'))
+);
+
+CREATE INDEX IF NOT EXISTS sightings_lost_report_id_idx ON sightings (lost_report_id);
+
+CREATE TABLE IF NOT EXISTS dog_events (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    dog_id UUID NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS dog_events_dog_id_idx ON dog_events (dog_id, created_at);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lost_reports_created_at_id_idx;
+
+DROP INDEX IF EXISTS merchantsrecord_merchant_id_data_issued_id_idx;
+ALTER TABLE merchantsrecord ALTER COLUMN data_issued DROP NOT NULL;
+
+DROP INDEX IF EXISTS merchants_created_at_id_idx;
+ALTER TABLE merchants DROP COLUMN IF EXISTS created_at;
+
+DROP INDEX IF EXISTS dogs_created_at_id_idx;
+ALTER TABLE dogs ALTER COLUMN created_at DROP NOT NULL;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+-- Keyset pagination orders by (created_at, id), so created_at must always be set.
+UPDATE dogs SET created_at = COALESCE(updated_at, now()) WHERE created_at IS NULL;
+ALTER TABLE dogs ALTER COLUMN created_at SET NOT NULL;
+CREATE INDEX IF NOT EXISTS dogs_created_at_id_idx ON dogs (created_at, id);
+
+ALTER TABLE merchants ADD COLUMN IF NOT EXISTS created_at TIMESTAMP;
+UPDATE merchants SET created_at = COALESCE(last_updated, now()) WHERE created_at IS NULL;
+ALTER TABLE merchants
+    ALTER COLUMN created_at SET NOT NULL,
+    ALTER COLUMN created_at SET DEFAULT CURRENT_TIMESTAMP;
+CREATE INDEX IF NOT EXISTS merchants_created_at_id_idx ON merchants (created_at, id);
+
+-- data_issued is the creation time of a merchant record.
+UPDATE merchantsrecord SET data_issued = valid_from WHERE data_issued IS NULL;
+ALTER TABLE merchantsrecord ALTER COLUMN data_issued SET NOT NULL;
+CREATE INDEX IF NOT EXISTS merchantsrecord_merchant_id_data_issued_id_idx ON merchantsrecord (merchant_id, data_issued, id);
+
+CREATE INDEX IF NOT EXISTS lost_reports_created_at_id_idx ON lost_reports (created_at, id);
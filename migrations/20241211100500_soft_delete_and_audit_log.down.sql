@@ -0,0 +1,10 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS audit_log_append_only ON audit_log;
+DROP FUNCTION IF EXISTS reject_audit_log_changes();
+DROP TABLE IF EXISTS audit_log;
+
+DROP INDEX IF EXISTS merchants_live_idx;
+DROP INDEX IF EXISTS dogs_live_idx;
+
+ALTER TABLE merchants DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE dogs DROP COLUMN IF EXISTS deleted_at;
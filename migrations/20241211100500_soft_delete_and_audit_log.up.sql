@@ -0,0 +1,34 @@
+-- Add up migration script here
+
+ALTER TABLE dogs ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+ALTER TABLE merchants ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS dogs_live_idx ON dogs (created_at, id) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS merchants_live_idx ON merchants (created_at, id) WHERE deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    actor VARCHAR(255) NOT NULL,
+    entity_type VARCHAR(64) NOT NULL,
+    entity_id UUID NOT NULL,
+    action VARCHAR(16) NOT NULL,
+    before JSONB,
+    after JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_entity_idx ON audit_log (entity_type, entity_id, created_at);
+
+-- The audit log is append-only.
+CREATE OR REPLACE FUNCTION reject_audit_log_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE
+    ON audit_log
+    FOR EACH ROW
+EXECUTE PROCEDURE reject_audit_log_changes();
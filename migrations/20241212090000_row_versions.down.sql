@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS bump_merchants_version ON merchants;
+DROP TRIGGER IF EXISTS bump_dogs_version ON dogs;
+DROP FUNCTION IF EXISTS bump_row_version();
+
+ALTER TABLE merchants DROP COLUMN IF EXISTS version;
+ALTER TABLE dogs DROP COLUMN IF EXISTS version;
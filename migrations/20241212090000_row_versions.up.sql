@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- Every write bumps `version`; it backs the ETag / If-Match checks on PATCH.
+ALTER TABLE dogs ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE merchants ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION bump_row_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version = OLD.version + 1;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER bump_dogs_version
+    BEFORE UPDATE
+    ON dogs
+    FOR EACH ROW
+EXECUTE PROCEDURE bump_row_version();
+
+CREATE TRIGGER bump_merchants_version
+    BEFORE UPDATE
+    ON merchants
+    FOR EACH ROW
+EXECUTE PROCEDURE bump_row_version();
@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+-- One row per Idempotency-Key. `response_status` stays NULL while the first request is running.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    idempotency_key VARCHAR(255) PRIMARY KEY NOT NULL,
+    request_hash VARCHAR(64) NOT NULL,
+    response_status SMALLINT,
+    response_headers JSONB,
+    response_body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_created_at_idx ON idempotency_keys (created_at);
@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- Token buckets shared by every replica when rate_limit.backend = "postgres".
+-- `key` is "<route class>:<dimension>:<value>"; API keys are stored hashed.
+CREATE TABLE IF NOT EXISTS rate_limit_buckets (
+    key VARCHAR(255) PRIMARY KEY NOT NULL,
+    tokens DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS rate_limit_buckets_updated_at_idx ON rate_limit_buckets (updated_at);
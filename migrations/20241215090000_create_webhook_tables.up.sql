@@ -0,0 +1,47 @@
+-- Add up migration script here
+
+-- Domain events written in the same transaction as the change they describe, then fanned out
+-- to webhook subscriptions. `merchant_id` scopes an event to one merchant; NULL goes to all.
+CREATE TABLE IF NOT EXISTS outbox_events (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    event_type VARCHAR(64) NOT NULL,
+    merchant_id UUID,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    dispatched_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS outbox_events_undispatched_idx ON outbox_events (created_at) WHERE dispatched_at IS NULL;
+
+-- An empty `event_types` subscribes to every event.
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    merchant_id UUID NOT NULL references merchants(id),
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(128) NOT NULL,
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    disabled_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS webhook_subscriptions_merchant_id_idx ON webhook_subscriptions (merchant_id);
+
+-- One row per event per subscription. `status` is pending, delivered or dead; dead rows are
+-- the dead-letter queue and stay until replayed.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    subscription_id UUID NOT NULL references webhook_subscriptions(id),
+    event_id UUID NOT NULL references outbox_events(id),
+    event_type VARCHAR(64) NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_status_code SMALLINT,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at TIMESTAMPTZ,
+    UNIQUE (subscription_id, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_due_idx ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS webhook_deliveries_subscription_id_idx ON webhook_deliveries (subscription_id, created_at);
@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS dogs_notify_changed ON dogs;
+DROP TRIGGER IF EXISTS merchants_notify_chain_head ON merchants;
+DROP TRIGGER IF EXISTS merchantsrecord_notify_created ON merchantsrecord;
+DROP FUNCTION IF EXISTS notify_dog_changed;
+DROP FUNCTION IF EXISTS notify_merchant_chain_head;
+DROP FUNCTION IF EXISTS notify_merchant_record_created;
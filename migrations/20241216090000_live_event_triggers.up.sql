@@ -0,0 +1,79 @@
+-- Add up migration script here
+
+-- Changes are announced on the `live_events` channel for the event stream. NOTIFY is only
+-- delivered on commit, and payloads must stay under 8000 bytes, so they carry ids and a few
+-- fields rather than whole rows.
+
+CREATE OR REPLACE FUNCTION notify_merchant_record_created()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('live_events', jsonb_build_object(
+        'type', 'merchant_record.created',
+        'topic', 'merchant_records',
+        'merchant_id', NEW.merchant_id,
+        'data', jsonb_build_object(
+            'id', NEW.id,
+            'merchant_id', NEW.merchant_id,
+            'record_type', NEW.record_type,
+            'data_hash', NEW.data_hash,
+            'valid_from', NEW.valid_from,
+            'valid_until', NEW.valid_until,
+            'supersedes', NEW.supersedes
+        )
+    )::text);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER merchantsrecord_notify_created
+    AFTER INSERT
+    ON merchantsrecord
+    FOR EACH ROW
+EXECUTE PROCEDURE notify_merchant_record_created();
+
+CREATE OR REPLACE FUNCTION notify_merchant_chain_head()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('live_events', jsonb_build_object(
+        'type', 'merchant.chain_head_changed',
+        'topic', 'chain_head',
+        'merchant_id', NEW.id,
+        'data', jsonb_build_object(
+            'merchant_id', NEW.id,
+            'last_data_hash', NEW.last_data_hash,
+            'previous_data_hash', OLD.last_data_hash
+        )
+    )::text);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER merchants_notify_chain_head
+    AFTER UPDATE OF last_data_hash
+    ON merchants
+    FOR EACH ROW
+    WHEN (OLD.last_data_hash IS DISTINCT FROM NEW.last_data_hash)
+EXECUTE PROCEDURE notify_merchant_chain_head();
+
+CREATE OR REPLACE FUNCTION notify_dog_changed()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('live_events', jsonb_build_object(
+        'type', CASE TG_OP WHEN 'INSERT' THEN 'dog.created' ELSE 'dog.updated' END,
+        'topic', 'dogs',
+        'dog_id', NEW.id,
+        'data', jsonb_build_object(
+            'id', NEW.id,
+            'version', NEW.version,
+            'deleted', NEW.deleted_at IS NOT NULL
+        )
+    )::text);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER dogs_notify_changed
+    AFTER INSERT OR UPDATE
+    ON dogs
+    FOR EACH ROW
+EXECUTE PROCEDURE notify_dog_changed();
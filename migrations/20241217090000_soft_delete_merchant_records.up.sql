@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+ALTER TABLE merchantsrecord ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE merchantsrecord_archive ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+ALTER TABLE merchantsrecord_archive DROP COLUMN IF EXISTS format_version;
+ALTER TABLE merchantsrecord DROP COLUMN IF EXISTS format_version;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- Which version of the signed byte layout (see `signing.rs`) a record was signed under. Rows
+-- written before versions were recorded are v1; new rows always name their version.
+ALTER TABLE merchantsrecord ADD COLUMN IF NOT EXISTS format_version SMALLINT NOT NULL DEFAULT 1;
+ALTER TABLE merchantsrecord ALTER COLUMN format_version DROP DEFAULT;
+ALTER TABLE merchantsrecord_archive ADD COLUMN IF NOT EXISTS format_version SMALLINT NOT NULL DEFAULT 1;
+ALTER TABLE merchantsrecord_archive ALTER COLUMN format_version DROP DEFAULT;
@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+DELETE FROM idempotency_keys;
+
+ALTER TABLE idempotency_keys
+    DROP CONSTRAINT idempotency_keys_pkey,
+    DROP COLUMN caller,
+    ADD PRIMARY KEY (idempotency_key);
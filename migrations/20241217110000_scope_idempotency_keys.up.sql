@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+-- Keys are scoped to the caller that sent them: the hash of its API key, or '' without one.
+-- Keys already stored predate scoping and are dropped rather than guessed at.
+DELETE FROM idempotency_keys;
+
+ALTER TABLE idempotency_keys
+    ADD COLUMN caller VARCHAR(64) NOT NULL DEFAULT '',
+    DROP CONSTRAINT idempotency_keys_pkey,
+    ADD PRIMARY KEY (caller, idempotency_key);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE lost_reports DROP COLUMN owner_merchant_id;
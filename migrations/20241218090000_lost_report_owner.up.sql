@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- The merchant whose key opened the report, or NULL when the operator did. Only the owner or
+-- the operator may move a report along, and its match notifications go to the owner alone.
+ALTER TABLE lost_reports ADD COLUMN owner_merchant_id UUID references merchants(id);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS outbox_events_undispatched_idx;
+DELETE FROM outbox_events WHERE held;
+ALTER TABLE outbox_events DROP COLUMN held;
+CREATE INDEX IF NOT EXISTS outbox_events_undispatched_idx ON outbox_events (created_at) WHERE dispatched_at IS NULL;
+
+ALTER TABLE zkptable
+    DROP CONSTRAINT zkptable_dog_id_key,
+    DROP COLUMN enrolled_event_id,
+    DROP COLUMN submission_status;
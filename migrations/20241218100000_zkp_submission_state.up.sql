@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+-- One commitment per dog. Retried sign-ups left duplicates; keep one of each.
+DELETE FROM zkptable a USING zkptable b WHERE a.dog_id = b.dog_id AND a.id < b.id;
+
+-- A commitment is `pending` until the chain has it, then `submitted`. Existing rows were only
+-- ever written around a successful submit. `enrolled_event_id` is the held `dog.enrolled`
+-- event released once it is submitted.
+ALTER TABLE zkptable
+    ADD COLUMN submission_status VARCHAR(16) NOT NULL DEFAULT 'submitted',
+    ADD COLUMN enrolled_event_id UUID references outbox_events(id) ON DELETE SET NULL,
+    ADD CONSTRAINT zkptable_dog_id_key UNIQUE (dog_id);
+ALTER TABLE zkptable ALTER COLUMN submission_status SET DEFAULT 'pending';
+
+-- Held events are not fanned out until released.
+ALTER TABLE outbox_events ADD COLUMN held BOOLEAN NOT NULL DEFAULT false;
+DROP INDEX IF EXISTS outbox_events_undispatched_idx;
+CREATE INDEX IF NOT EXISTS outbox_events_undispatched_idx ON outbox_events (created_at) WHERE dispatched_at IS NULL AND NOT held;
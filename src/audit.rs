@@ -0,0 +1,51 @@
+use serde::Serialize;
+use sqlx::PgConnection;
+
+pub const ACTION_CREATE: &str = "create";
+pub const ACTION_UPDATE: &str = "update";
+pub const ACTION_DELETE: &str = "delete";
+pub const ACTION_RESTORE: &str = "restore";
+
+/// One row of the append-only `audit_log`.
+pub struct AuditEntry<'a> {
+    pub actor: &'a str,
+    pub entity_type: &'a str,
+    pub entity_id: uuid::Uuid,
+    pub action: &'a str,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+/// A row returned by an UPDATE together with the row as it was before, for `AuditEntry::before`.
+#[derive(Debug, sqlx::FromRow)]
+pub struct Audited<T> {
+    #[sqlx(flatten)]
+    pub row: T,
+    pub before: Option<serde_json::Value>,
+}
+
+pub fn snapshot<T: Serialize>(value: &T) -> Option<serde_json::Value> {
+    serde_json::to_value(value).ok()
+}
+
+/// Writes `entry` on `conn`, which should be the transaction making the change being audited.
+#[tracing::instrument(
+    name = "db.record_audit",
+    skip_all,
+    fields(entity_type = entry.entity_type, entity_id = %entry.entity_id, action = entry.action),
+    err
+)]
+pub async fn record_audit(conn: &mut PgConnection, entry: AuditEntry<'_>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO audit_log (actor, entity_type, entity_id, action, before, after) VALUES ($1, $2, $3, $4, $5, $6)"#
+    )
+    .bind(entry.actor)
+    .bind(entry.entity_type)
+    .bind(entry.entity_id)
+    .bind(entry.action)
+    .bind(entry.before)
+    .bind(entry.after)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
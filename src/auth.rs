@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap, StatusCode},
+    Json,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{encoding::to_hex, model::MerchantApiKeyModel, schema::SCOPE_ADMIN, AppState};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+pub const API_KEY_PREFIX: &str = "mk_";
+
+/// Who authenticated a request: an operator with `auth.operator_token`, or a merchant with
+/// one of its API keys.
+#[derive(Debug, Clone)]
+pub enum Principal {
+    Operator,
+    Merchant(MerchantAuth),
+}
+
+impl Principal {
+    /// Rejects the request unless the principal may act for `merchant_id` with `scope`.
+    /// Operators may act for any merchant.
+    pub fn authorize(
+        &self,
+        merchant_id: uuid::Uuid,
+        scope: &str,
+    ) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+        match self {
+            Principal::Operator => Ok(()),
+            Principal::Merchant(auth) => auth.authorize(merchant_id, scope),
+        }
+    }
+
+    /// Rejects the request unless the principal is the operator or holds a key with the
+    /// admin scope. For changes to rows that no single merchant owns, such as dogs.
+    pub fn authorize_admin(&self) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+        match self {
+            Principal::Merchant(auth) if !auth.has_scope(SCOPE_ADMIN) => {
+                let error_response = json!({
+                    "status": "fail",
+                    "message": format!("API key is missing the '{}' scope", SCOPE_ADMIN)
+                });
+                Err((StatusCode::FORBIDDEN, Json(error_response)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A merchant authenticated by one of its API keys.
+#[derive(Debug, Clone)]
+pub struct MerchantAuth {
+    pub key_id: uuid::Uuid,
+    pub merchant_id: uuid::Uuid,
+    pub scopes: Vec<String>,
+}
+
+impl MerchantAuth {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    /// Rejects the request unless the key belongs to `merchant_id` and carries `scope`.
+    pub fn authorize(
+        &self,
+        merchant_id: uuid::Uuid,
+        scope: &str,
+    ) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+        if self.merchant_id != merchant_id {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("API key is not valid for merchant {}", merchant_id)
+            });
+            return Err((StatusCode::FORBIDDEN, Json(error_response)));
+        }
+        if !self.has_scope(scope) {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("API key is missing the '{}' scope", scope)
+            });
+            return Err((StatusCode::FORBIDDEN, Json(error_response)));
+        }
+        Ok(())
+    }
+}
+
+/// Generates a new plaintext API key. Only its hash is ever stored.
+pub fn generate_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", API_KEY_PREFIX, to_hex(&bytes))
+}
+
+pub fn hash_api_key(key: &str) -> String {
+    to_hex(&Sha256::digest(key.as_bytes()))
+}
+
+/// The short, non-secret part of a key shown back to merchants in listings.
+pub fn api_key_prefix(key: &str) -> String {
+    key.chars().take(API_KEY_PREFIX.len() + 8).collect()
+}
+
+/// The key from `X-Api-Key`, or else from `Authorization: Bearer`.
+pub fn extract_api_key(headers: &HeaderMap) -> Option<String> {
+    if let Some(value) = headers.get(API_KEY_HEADER) {
+        return value.to_str().ok().map(|v| v.trim().to_string());
+    }
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.trim().to_string())
+}
+
+fn presented_key(headers: &HeaderMap) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    match extract_api_key(headers) {
+        Some(key) if !key.is_empty() => Ok(key),
+        _ => {
+            let error_response = json!({
+                "status": "fail",
+                "message": "Missing API key",
+            });
+            Err((StatusCode::UNAUTHORIZED, Json(error_response)))
+        }
+    }
+}
+
+/// Compares hashes rather than the tokens themselves, so the time taken says nothing about
+/// how much of the token matched.
+fn is_operator_token(state: &AppState, key: &str) -> bool {
+    state
+        .config
+        .auth
+        .operator_token
+        .as_deref()
+        .is_some_and(|token| hash_api_key(token) == hash_api_key(key))
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for Principal {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(principal) = parts.extensions.get::<Principal>() {
+            return Ok(principal.clone());
+        }
+        let key = presented_key(&parts.headers)?;
+        authenticate(state, &key).await
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for MerchantAuth {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(Principal::Merchant(auth)) = parts.extensions.get::<Principal>() {
+            return Ok(auth.clone());
+        }
+        let key = presented_key(&parts.headers)?;
+        authenticate_merchant(state, &key).await
+    }
+}
+
+/// Authenticates `key` as the operator token or a merchant API key. Middleware that needs the
+/// caller before the handler runs stores the result in the request's extensions, where the
+/// `Principal` and `MerchantAuth` extractors pick it up instead of authenticating again.
+pub async fn authenticate(state: &AppState, key: &str) -> Result<Principal, (StatusCode, Json<serde_json::Value>)> {
+    if is_operator_token(state, key) {
+        return Ok(Principal::Operator);
+    }
+    authenticate_merchant(state, key).await.map(Principal::Merchant)
+}
+
+/// Keys of a soft-deleted merchant are refused along with revoked ones, and work again if
+/// the merchant is restored.
+async fn authenticate_merchant(state: &AppState, key: &str) -> Result<MerchantAuth, (StatusCode, Json<serde_json::Value>)> {
+    let query_result = sqlx::query_as::<_, MerchantApiKeyModel>(
+        r#"UPDATE merchant_api_keys SET last_used_at = now()
+        FROM merchants
+        WHERE merchant_api_keys.key_hash = $1 AND merchant_api_keys.revoked_at IS NULL
+            AND merchants.id = merchant_api_keys.merchant_id AND merchants.deleted_at IS NULL
+        RETURNING merchant_api_keys.*"#
+    )
+    .bind(hash_api_key(key))
+    .fetch_optional(&state.db)
+    .await;
+
+    match query_result {
+        Ok(Some(api_key)) => Ok(MerchantAuth {
+            key_id: api_key.id,
+            merchant_id: api_key.merchant_id,
+            scopes: api_key.scopes,
+        }),
+        Ok(None) => {
+            let error_response = json!({
+                "status": "fail",
+                "message": "Invalid or revoked API key",
+            });
+            Err((StatusCode::UNAUTHORIZED, Json(error_response)))
+        }
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error","message": format!("{:?}", e)})),
+        )),
+    }
+}
+
+/// Who is making a change, for the audit log: the operator or merchant API key that
+/// authenticated the request, or `anonymous` when it carried no credentials.
+#[derive(Debug, Clone)]
+pub struct Actor(pub String);
+
+impl Actor {
+    pub fn merchant(auth: &MerchantAuth) -> Actor {
+        Actor(format!("merchant:{}/key:{}", auth.merchant_id, auth.key_id))
+    }
+
+    pub fn principal(principal: &Principal) -> Actor {
+        match principal {
+            Principal::Operator => Actor("operator".to_string()),
+            Principal::Merchant(auth) => Actor::merchant(auth),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A request that presents a key which fails to authenticate is refused rather than
+/// recorded as `anonymous`.
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for Actor {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        if extract_api_key(&parts.headers).is_none_or(|key| key.is_empty()) {
+            return Ok(Actor("anonymous".to_string()));
+        }
+        let principal = Principal::from_request_parts(parts, state).await?;
+        Ok(Actor::principal(&principal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{Pool, Postgres};
+
+    use super::*;
+    use crate::{config::Config, migrate::MIGRATOR};
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn keys_of_deleted_merchants_are_refused(db: Pool<Postgres>) {
+        let merchant_id = sqlx::query_scalar::<_, uuid::Uuid>(r#"INSERT INTO merchants (last_data_hash) VALUES ('genesis') RETURNING id"#)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let key = generate_api_key();
+        sqlx::query(r#"INSERT INTO merchant_api_keys (merchant_id, key_prefix, key_hash, scopes) VALUES ($1, $2, $3, $4)"#)
+            .bind(merchant_id)
+            .bind(api_key_prefix(&key))
+            .bind(hash_api_key(&key))
+            .bind(vec![SCOPE_ADMIN.to_string()])
+            .execute(&db)
+            .await
+            .unwrap();
+        let state = AppState::for_tests(db.clone(), Config::default());
+        assert!(authenticate(&state, &key).await.is_ok());
+
+        let set_deleted = |deleted: bool| {
+            sqlx::query(r#"UPDATE merchants SET deleted_at = CASE WHEN $2 THEN now() END WHERE id = $1"#)
+                .bind(merchant_id)
+                .bind(deleted)
+                .execute(&db)
+        };
+        set_deleted(true).await.unwrap();
+        let refused = authenticate(&state, &key).await.unwrap_err();
+        assert_eq!(refused.0, StatusCode::UNAUTHORIZED);
+
+        set_deleted(false).await.unwrap();
+        assert!(authenticate(&state, &key).await.is_ok());
+    }
+}
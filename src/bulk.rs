@@ -0,0 +1,235 @@
+use std::{
+    io::Write,
+    pin::pin,
+    sync::{Arc, Mutex},
+};
+
+use arrow_json::reader::{Decoder, ReaderBuilder};
+use arrow_schema::Schema;
+use axum::{
+    body::{Body, Bytes},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    BoxError, Json,
+};
+use futures::{Stream, StreamExt, TryStreamExt};
+use parquet::arrow::ArrowWriter;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use tokio::io::AsyncBufReadExt;
+use tokio_util::io::StreamReader;
+
+use crate::schema::ExportFormat;
+
+/// Rows encoded per chunk of an export, and per decoded Parquet row group.
+const EXPORT_BATCH_ROWS: usize = 1000;
+
+/// Streams `rows` to the client as a `format` download. Rows are encoded in chunks of
+/// `EXPORT_BATCH_ROWS`, so memory use does not grow with the size of the export.
+/// `schema` describes the columns of `T` and is only used for Parquet.
+pub fn export_response<T, S>(rows: S, format: ExportFormat, schema: Arc<Schema>, filename: &str) -> Response
+where
+    T: Serialize + Send + 'static,
+    S: Stream<Item = Result<T, sqlx::Error>> + Send + 'static,
+{
+    let body = async_stream::try_stream! {
+        let mut encoder = ExportEncoder::new(format, schema)?;
+        let mut rows = pin!(rows);
+        let mut batch = Vec::with_capacity(EXPORT_BATCH_ROWS);
+        while let Some(row) = rows.next().await {
+            batch.push(row?);
+            if batch.len() == EXPORT_BATCH_ROWS {
+                yield encoder.encode(&batch)?;
+                batch.clear();
+            }
+        }
+        yield encoder.encode(&batch)?;
+        yield encoder.finish()?;
+    };
+    let body: std::pin::Pin<Box<dyn Stream<Item = Result<Bytes, BoxError>> + Send>> = Box::pin(body);
+
+    (
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.{}\"", filename, format.extension()),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+enum ExportEncoder {
+    Csv { wrote_header: bool },
+    Ndjson,
+    Parquet {
+        decoder: Box<Decoder>,
+        writer: Option<Box<ArrowWriter<SharedBuffer>>>,
+        buffer: SharedBuffer,
+    },
+}
+
+impl ExportEncoder {
+    fn new(format: ExportFormat, schema: Arc<Schema>) -> Result<Self, BoxError> {
+        Ok(match format {
+            ExportFormat::Csv => ExportEncoder::Csv { wrote_header: false },
+            ExportFormat::Ndjson => ExportEncoder::Ndjson,
+            ExportFormat::Parquet => {
+                let buffer = SharedBuffer::default();
+                ExportEncoder::Parquet {
+                    decoder: Box::new(ReaderBuilder::new(schema.clone()).build_decoder()?),
+                    writer: Some(Box::new(ArrowWriter::try_new(buffer.clone(), schema, None)?)),
+                    buffer,
+                }
+            }
+        })
+    }
+
+    fn encode<T: Serialize>(&mut self, rows: &[T]) -> Result<Bytes, BoxError> {
+        if rows.is_empty() {
+            return Ok(Bytes::new());
+        }
+        match self {
+            ExportEncoder::Csv { wrote_header } => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(!*wrote_header)
+                    .from_writer(Vec::new());
+                for row in rows {
+                    writer.serialize(row)?;
+                }
+                *wrote_header = true;
+                Ok(Bytes::from(writer.into_inner().map_err(|e| e.into_error())?))
+            }
+            ExportEncoder::Ndjson => {
+                let mut out = Vec::new();
+                for row in rows {
+                    serde_json::to_writer(&mut out, row)?;
+                    out.push(b'\n');
+                }
+                Ok(Bytes::from(out))
+            }
+            ExportEncoder::Parquet { decoder, writer, buffer } => {
+                decoder.serialize(rows)?;
+                if let (Some(batch), Some(writer)) = (decoder.flush()?, writer.as_mut()) {
+                    writer.write(&batch)?;
+                    // Close the row group so its bytes can be sent now.
+                    writer.flush()?;
+                }
+                Ok(buffer.take())
+            }
+        }
+    }
+
+    /// Trailing bytes once every row has been encoded, e.g. the Parquet footer.
+    fn finish(&mut self) -> Result<Bytes, BoxError> {
+        match self {
+            ExportEncoder::Parquet { writer, buffer, .. } => {
+                if let Some(writer) = writer.take() {
+                    writer.close()?;
+                }
+                Ok(buffer.take())
+            }
+            _ => Ok(Bytes::new()),
+        }
+    }
+}
+
+/// A `Write` target the Parquet writer appends to while we drain finished chunks from it.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Bytes {
+        Bytes::from(std::mem::take(&mut *self.0.lock().unwrap()))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ImportFormat {
+    /// Picks the format from the request's `Content-Type`.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, (StatusCode, Json<serde_json::Value>)> {
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        match content_type.as_str() {
+            "text/csv" => Ok(ImportFormat::Csv),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => Ok(ImportFormat::Ndjson),
+            _ => {
+                let error_response = json!({
+                    "status": "fail",
+                    "message": "Content-Type must be text/csv or application/x-ndjson",
+                });
+                Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, Json(error_response)))
+            }
+        }
+    }
+}
+
+/// One parsed input row: its 1-based data row number and the row or why it could not be read.
+pub type ImportRow<T> = (usize, Result<T, String>);
+
+/// Parses `body` row by row as it arrives. Malformed rows are yielded as errors so the caller
+/// can report them and carry on; an error reading the body itself ends the stream.
+pub fn import_rows<T>(body: Body, format: ImportFormat) -> impl Stream<Item = Result<ImportRow<T>, BoxError>> + Send
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let reader = StreamReader::new(
+        body.into_data_stream()
+            .map_err(std::io::Error::other),
+    );
+    async_stream::try_stream! {
+        match format {
+            ImportFormat::Ndjson => {
+                let mut lines = reader.lines();
+                let mut row = 0;
+                while let Some(line) = lines.next_line().await? {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    row += 1;
+                    yield (row, serde_json::from_str::<T>(&line).map_err(|e| e.to_string()));
+                }
+            }
+            ImportFormat::Csv => {
+                let mut reader = csv_async::AsyncReaderBuilder::new()
+                    .trim(csv_async::Trim::All)
+                    .create_deserializer(reader);
+                let mut records = reader.deserialize::<T>();
+                let mut row = 0;
+                while let Some(record) = records.next().await {
+                    row += 1;
+                    match record {
+                        Ok(record) => yield (row, Ok(record)),
+                        Err(e) if matches!(e.kind(), csv_async::ErrorKind::Io(_)) => Err(e)?,
+                        Err(e) => yield (row, Err(e.to_string())),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::encoding::to_hex;
+
+/// Serializes `value` as RFC 8785 (JCS) canonical JSON: object keys sorted by UTF-16 code
+/// unit, numbers formatted as ECMAScript does and no insignificant whitespace, so equal JSON
+/// documents always produce the same bytes regardless of how the client wrote them.
+pub fn to_canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        Value::Number(number) => out.push_str(&canonical_number(number.as_f64().unwrap_or_default())),
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Largest integer an IEEE 754 double holds exactly, and so the largest I-JSON (RFC 7493)
+/// allows: beyond it canonical JSON, which formats every number as a double, would round.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// JSON Pointers to every integer in `value` outside `±MAX_SAFE_INTEGER`. Such payloads are
+/// refused before signing, as their canonical form would not say what the client sent.
+pub fn unsafe_integer_paths(value: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    collect_unsafe_integers(value, &mut String::new(), &mut paths);
+    paths
+}
+
+fn collect_unsafe_integers(value: &Value, path: &mut String, paths: &mut Vec<String>) {
+    let mut descend = |segment: &str, item: &Value, path: &mut String| {
+        let len = path.len();
+        path.push('/');
+        path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+        collect_unsafe_integers(item, path, paths);
+        path.truncate(len);
+    };
+    match value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                descend(&i.to_string(), item, path);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                descend(key, item, path);
+            }
+        }
+        Value::Number(number) => {
+            let safe = match (number.as_u64(), number.as_i64()) {
+                (Some(n), _) => n <= MAX_SAFE_INTEGER,
+                (None, Some(n)) => n.unsigned_abs() <= MAX_SAFE_INTEGER,
+                (None, None) => true,
+            };
+            if !safe {
+                paths.push(path.clone());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `number` as ECMAScript's `Number.prototype.toString` writes it: the shortest digits that
+/// round-trip, in plain notation for exponents from -7 to 20 and scientific beyond.
+fn canonical_number(number: f64) -> String {
+    if number == 0.0 {
+        return "0".to_string();
+    }
+    // `{:e}` gives the shortest round-tripping digits, e.g. `-1.2345e-7`.
+    let scientific = format!("{:e}", number.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap_or_default() + 1;
+
+    let formatted = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let sign = if n > 0 { "+" } else { "-" };
+        let fraction = if k > 1 { format!(".{}", &digits[1..]) } else { String::new() };
+        format!("{}{}e{}{}", &digits[..1], fraction, sign, (n - 1).abs())
+    };
+    if number < 0.0 {
+        format!("-{}", formatted)
+    } else {
+        formatted
+    }
+}
+
+/// Hash of a chained merchant record: SHA-256 over the previous chain head, a NUL separator,
+/// the record type, another NUL, and the canonical JSON of the payload, hex encoded.
+pub fn merchant_record_hash(prev_data_hash: &str, record_type: &str, canonical_data: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_data_hash.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(record_type.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(canonical_data.as_bytes());
+    to_hex(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn formats_numbers_as_ecmascript_does() {
+        for (number, expected) in [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1e2, "100"),
+            (-1.5, "-1.5"),
+            (333333333.3333333, "333333333.3333333"),
+            (1e21, "1e+21"),
+            (1e-7, "1e-7"),
+            (1.2345e-7, "1.2345e-7"),
+            (0.000001, "0.000001"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+            (9007199254740992.0, "9007199254740992"),
+            (295147905179352830000.0, "295147905179352830000"),
+        ] {
+            assert_eq!(canonical_number(number), expected, "{:e}", number);
+        }
+    }
+
+    #[test]
+    fn finds_integers_a_double_cannot_hold() {
+        let value = json!({
+            "safe": [9007199254740991_u64, -9007199254740991_i64, 1.5e300],
+            "big": 9007199254740992_u64,
+            "nested": {"a/b": [-9007199254740993_i64]},
+        });
+        let mut paths = unsafe_integer_paths(&value);
+        paths.sort();
+        assert_eq!(paths, vec!["/big", "/nested/a~1b/0"]);
+    }
+
+    #[test]
+    fn sorts_keys_by_utf16_code_unit() {
+        let value = json!({"\u{e000}": 1, "\u{1f600}": 2, "b": [1e2, "\u{1f}"], "a": null});
+        assert_eq!(to_canonical_json(&value), "{\"a\":null,\"b\":[100,\"\\u001f\"],\"\u{1f600}\":2,\"\u{e000}\":1}");
+    }
+}
@@ -0,0 +1,57 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::config::ChainBackend;
+
+/// Command-line flags. Each override wins over the config file and the environment.
+#[derive(Parser, Debug, Default)]
+#[command(version, about = "Dog registry API server")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// TOML config file. Defaults to `config.toml` when present.
+    #[arg(long, short, global = true, env = "APP_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Address to listen on, e.g. `0.0.0.0:8000`.
+    #[arg(long, global = true)]
+    pub bind: Option<SocketAddr>,
+    #[arg(long, global = true)]
+    pub database_url: Option<String>,
+    #[arg(long, global = true)]
+    pub max_connections: Option<u32>,
+    /// Allowed CORS origin; repeat for several. Replaces the configured list.
+    #[arg(long = "cors-origin", global = true)]
+    pub cors_origins: Vec<String>,
+    #[arg(long, global = true)]
+    pub prover_threads: Option<usize>,
+    #[arg(long, global = true)]
+    pub params_path: Option<PathBuf>,
+    #[arg(long, value_enum, global = true)]
+    pub chain_backend: Option<ChainBackend>,
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum Command {
+    /// Run the API server. The default when no command is given.
+    Serve,
+    /// Inspect or change the database schema with the migrations built into this binary.
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateCommand,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum MigrateCommand {
+    /// Apply every pending migration.
+    Up,
+    /// Revert applied migrations newer than `--to`, newest first.
+    Down {
+        /// Version to go back to; it stays applied. `0` reverts everything.
+        #[arg(long)]
+        to: i64,
+    },
+    /// List each migration and whether it is applied.
+    Status,
+}
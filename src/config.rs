@@ -0,0 +1,528 @@
+use std::{
+    borrow::Cow,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use figment::{
+    providers::{Env, Format, Serialized, Toml},
+    Figment,
+};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::{cli::Cli, expiry::ExpiryConfig};
+
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+/// Prefix of environment overrides; `__` separates sections, e.g. `APP_SERVER__BIND`.
+pub const ENV_PREFIX: &str = "APP_";
+const REDACTED: &str = "********";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Validate)]
+pub struct Config {
+    #[validate(nested)]
+    pub server: ServerConfig,
+    #[validate(nested)]
+    pub database: DatabaseConfig,
+    #[validate(nested)]
+    pub auth: AuthConfig,
+    #[validate(nested)]
+    pub cors: CorsConfig,
+    #[validate(nested)]
+    pub prover: ProverConfig,
+    #[validate(nested)]
+    pub chain: ChainConfig,
+    pub expiry: ExpiryConfig,
+    #[validate(nested)]
+    pub telemetry: TelemetryConfig,
+    #[validate(nested)]
+    pub rate_limit: RateLimitConfig,
+    #[validate(nested)]
+    pub webhooks: WebhookConfig,
+    #[validate(nested)]
+    pub live: LiveConfig,
+    #[validate(nested)]
+    pub maintenance: MaintenanceConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub bind: SocketAddr,
+    /// Where `/metrics` is served. Kept off `bind` so the API's clients cannot read it.
+    pub metrics_bind: SocketAddr,
+    /// After SIGTERM, how long in-flight requests and proofs get to finish before the
+    /// process exits anyway.
+    #[validate(range(min = 1))]
+    pub shutdown_timeout_secs: u64,
+}
+
+impl ServerConfig {
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind: SocketAddr::from(([0, 0, 0, 0], 8000)),
+            metrics_bind: SocketAddr::from(([127, 0, 0, 1], 9000)),
+            shutdown_timeout_secs: 30,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(default)]
+#[validate(schema(function = "pool_bounds"))]
+pub struct DatabaseConfig {
+    /// Also read from `DATABASE_URL`, as the sqlx tooling expects.
+    #[validate(length(min = 1, message = "must be set, e.g. with DATABASE_URL"))]
+    pub url: String,
+    #[validate(range(min = 1))]
+    pub max_connections: u32,
+    pub min_connections: u32,
+    #[validate(range(min = 1))]
+    pub acquire_timeout_secs: u64,
+    /// Apply pending migrations before serving. When off, `serve` refuses to start until
+    /// `migrate up` has been run.
+    pub migrate_on_startup: bool,
+}
+
+impl DatabaseConfig {
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_timeout_secs)
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            url: String::new(),
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout_secs: 30,
+            migrate_on_startup: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Validate)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Bearer token for operators: sent like an API key, it acts for every merchant with every
+    /// scope. Needed to issue a merchant's first API key. `None` leaves only merchant keys.
+    #[validate(length(min = 32))]
+    pub operator_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Full origins such as `https://app.example.com`; scheme and host, no path.
+    #[validate(custom(function = "origins"))]
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig { allowed_origins: vec!["http://localhost:3000".to_string()] }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Validate)]
+#[serde(default)]
+pub struct ProverConfig {
+    /// Worker threads for proof generation. `None` uses every CPU.
+    #[validate(range(min = 1))]
+    pub threads: Option<usize>,
+    /// Groth16 parameters written by a trusted setup. `None` generates fresh parameters for
+    /// every proof, which is slow and only suitable for development.
+    pub params_path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainBackend {
+    /// Sign-ups and sign-ins are sent to the Solana program.
+    #[default]
+    Solana,
+    /// Proofs are generated and stored but nothing is sent on-chain.
+    Disabled,
+}
+
+/// The Solana client keeps its own RPC endpoint and keypair; only whether it is used is
+/// configured here.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Validate)]
+#[serde(default)]
+pub struct ChainConfig {
+    pub backend: ChainBackend,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One JSON object per line, for log collectors.
+    #[default]
+    Json,
+    /// Human-readable lines, for local development.
+    Pretty,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    pub log_format: LogFormat,
+    /// `tracing` filter directives. `RUST_LOG`, when set, takes precedence.
+    #[validate(length(min = 1))]
+    pub log_filter: String,
+    /// OTLP/gRPC collector to export spans to, e.g. `http://localhost:4317`. `None` disables export.
+    pub otlp_endpoint: Option<String>,
+    #[validate(length(min = 1))]
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            log_format: LogFormat::default(),
+            log_filter: "info,sqlx=warn".to_string(),
+            otlp_endpoint: None,
+            service_name: "dog-registry".to_string(),
+        }
+    }
+}
+
+/// A token bucket: `burst` requests at once, refilled at `per_minute`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Validate)]
+pub struct Bucket {
+    #[validate(range(min = 1))]
+    pub burst: u32,
+    #[validate(range(min = 1))]
+    pub per_minute: u32,
+}
+
+impl Bucket {
+    pub const fn new(burst: u32, per_minute: u32) -> Bucket {
+        Bucket { burst, per_minute }
+    }
+
+    pub fn per_second(&self) -> f64 {
+        self.per_minute as f64 / 60.0
+    }
+}
+
+/// Limits for one class of routes. A request must fit every bucket that applies to it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Validate)]
+#[serde(default)]
+pub struct RateLimitPolicy {
+    /// Per client IP address.
+    #[validate(nested)]
+    pub per_ip: Option<Bucket>,
+    /// Per API key, for requests whose key authenticates.
+    #[validate(nested)]
+    pub per_api_key: Option<Bucket>,
+    /// Per dog, for ZKP requests: by `dog_id` on sign-up and by microchip on sign-in.
+    #[validate(nested)]
+    pub per_dog: Option<Bucket>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackend {
+    /// Buckets live in this process, so each replica enforces its own limits.
+    #[default]
+    Memory,
+    /// Buckets live in `rate_limit_buckets`, so limits hold across replicas.
+    Postgres,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub backend: RateLimitBackend,
+    /// Take the client IP from `X-Forwarded-For`. Only enable behind a proxy that sets it, or
+    /// clients can pick their own bucket.
+    pub trust_forwarded_for: bool,
+    /// Proxies in front of the server that append to `X-Forwarded-For`. The client is the
+    /// entry this many hops from the right; anything further left was sent by the client.
+    #[validate(range(min = 1))]
+    pub trusted_proxies: usize,
+    /// Every API route.
+    #[validate(nested)]
+    pub default: RateLimitPolicy,
+    /// Routes that generate proofs or submit to the chain, on top of `default`.
+    #[validate(nested)]
+    pub zkp: RateLimitPolicy,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            backend: RateLimitBackend::default(),
+            trust_forwarded_for: false,
+            trusted_proxies: 1,
+            default: RateLimitPolicy {
+                per_ip: Some(Bucket::new(60, 300)),
+                per_api_key: Some(Bucket::new(120, 600)),
+                per_dog: None,
+            },
+            zkp: RateLimitPolicy {
+                per_ip: Some(Bucket::new(5, 10)),
+                per_api_key: Some(Bucket::new(10, 30)),
+                per_dog: Some(Bucket::new(3, 5)),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(default)]
+#[validate(schema(function = "backoff_bounds"))]
+pub struct WebhookConfig {
+    /// Run the delivery worker. Events are still written to the outbox when off, and are
+    /// delivered once a replica with the worker enabled picks them up.
+    pub enabled: bool,
+    #[validate(range(min = 10))]
+    pub poll_interval_ms: u64,
+    /// Events fanned out, and deliveries attempted, per poll.
+    #[validate(range(min = 1, max = 1000))]
+    pub batch_size: i64,
+    #[validate(range(min = 1))]
+    pub timeout_secs: u64,
+    /// Attempts before a delivery is moved to the dead-letter queue.
+    #[validate(range(min = 1))]
+    pub max_attempts: i32,
+    /// Delay after the first failure, doubling after each one up to `backoff_max_secs`.
+    #[validate(range(min = 1))]
+    pub backoff_base_secs: u64,
+    pub backoff_max_secs: u64,
+    /// Delivered events older than this many days are deleted. Dead ones are kept.
+    #[validate(range(min = 1))]
+    pub retention_days: i64,
+    /// Deliver to loopback, private and link-local addresses too. For local development only:
+    /// otherwise any merchant could make the worker POST into the internal network.
+    pub allow_private_targets: bool,
+}
+
+impl WebhookConfig {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// How long to wait before the next attempt, after `attempts` failed ones.
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let exponent = attempts.saturating_sub(1).clamp(0, 31) as u32;
+        let secs = self.backoff_base_secs.saturating_mul(1u64 << exponent);
+        Duration::from_secs(secs.min(self.backoff_max_secs))
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            enabled: true,
+            poll_interval_ms: 1000,
+            batch_size: 100,
+            timeout_secs: 10,
+            max_attempts: 12,
+            backoff_base_secs: 10,
+            backoff_max_secs: 6 * 3600,
+            retention_days: 30,
+            allow_private_targets: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(default)]
+pub struct LiveConfig {
+    /// Events held for slow stream clients. One that falls further behind is told it missed
+    /// events and should reload.
+    #[validate(range(min = 16))]
+    pub buffer_size: usize,
+    /// Comment sent on idle SSE streams so proxies do not close them.
+    #[validate(range(min = 1))]
+    pub keep_alive_secs: u64,
+    /// How often a stream re-checks the API key it was opened with, closing once it is
+    /// revoked or rotated.
+    #[validate(range(min = 1))]
+    pub revalidate_secs: u64,
+}
+
+impl LiveConfig {
+    pub fn keep_alive(&self) -> Duration {
+        Duration::from_secs(self.keep_alive_secs)
+    }
+
+    pub fn revalidate(&self) -> Duration {
+        Duration::from_secs(self.revalidate_secs)
+    }
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        LiveConfig {
+            buffer_size: 1024,
+            keep_alive_secs: 15,
+            revalidate_secs: 30,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    /// Run the purge worker on this replica. The purges are safe to run on several at once.
+    pub enabled: bool,
+    /// How often expired idempotency keys, idle rate limit buckets and delivered webhooks
+    /// older than `webhooks.retention_days` are deleted.
+    #[validate(range(min = 1))]
+    pub interval_secs: u64,
+}
+
+impl MaintenanceConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        MaintenanceConfig { enabled: true, interval_secs: 300 }
+    }
+}
+
+impl Config {
+    /// Defaults, then the config file, then `APP_*` variables (and the older unprefixed ones),
+    /// then command-line flags, each layer overriding the one before.
+    pub fn load(cli: &Cli) -> Result<Config, String> {
+        let path = cli.config.as_deref().unwrap_or(Path::new(DEFAULT_CONFIG_PATH));
+        if cli.config.is_some() && !path.exists() {
+            return Err(format!("config file {} does not exist", path.display()));
+        }
+
+        let mut figment = Figment::from(Serialized::defaults(Config::default()))
+            .merge(Toml::file(path))
+            .merge(Env::raw().only(&["DATABASE_URL"]).map(|_| "database.url".into()))
+            .merge(Env::raw().only(&["EXPIRY_SWEEP_INTERVAL_SECS"]).map(|_| "expiry.sweep_interval_secs".into()))
+            .merge(Env::raw().only(&["ARCHIVE_EXPIRED_AFTER_DAYS"]).map(|_| "expiry.archive_after_days".into()))
+            .merge(Env::prefixed(ENV_PREFIX).ignore(&["CONFIG"]).split("__"));
+
+        if let Some(bind) = cli.bind {
+            figment = figment.merge(Serialized::default("server.bind", bind));
+        }
+        if let Some(url) = &cli.database_url {
+            figment = figment.merge(Serialized::default("database.url", url));
+        }
+        if let Some(max_connections) = cli.max_connections {
+            figment = figment.merge(Serialized::default("database.max_connections", max_connections));
+        }
+        if !cli.cors_origins.is_empty() {
+            figment = figment.merge(Serialized::default("cors.allowed_origins", &cli.cors_origins));
+        }
+        if let Some(threads) = cli.prover_threads {
+            figment = figment.merge(Serialized::default("prover.threads", threads));
+        }
+        if let Some(params_path) = &cli.params_path {
+            figment = figment.merge(Serialized::default("prover.params_path", params_path));
+        }
+        if let Some(backend) = cli.chain_backend {
+            figment = figment.merge(Serialized::default("chain.backend", backend));
+        }
+
+        let config: Config = figment.extract().map_err(|e| e.to_string())?;
+        config.validate().map_err(|e| e.to_string())?;
+        Ok(config)
+    }
+
+    /// The config with secrets masked, for logging.
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        config.database.url = redact_url(&config.database.url).into_owned();
+        config.auth.operator_token = config.auth.operator_token.as_ref().map(|_| REDACTED.to_string());
+        config.telemetry.otlp_endpoint = config.telemetry.otlp_endpoint.as_deref().map(|url| redact_url(url).into_owned());
+        config
+    }
+}
+
+/// Masks the secrets a URL may carry: its password, every query value, and path segments
+/// long enough to be tokens, as providers that put an API key in the path use. Anything that
+/// does not parse as a URL is fully masked.
+fn redact_url(raw: &str) -> Cow<'_, str> {
+    let Ok(mut url) = url::Url::parse(raw) else {
+        return Cow::Borrowed(REDACTED);
+    };
+    let mut redacted = false;
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+        redacted = true;
+    }
+    if url.query().is_some() {
+        redacted = true;
+        let keys: Vec<String> = url.query_pairs().map(|(key, _)| key.into_owned()).collect();
+        url.query_pairs_mut().clear().extend_pairs(keys.iter().map(|key| (key, REDACTED)));
+    }
+    let is_token = |segment: &str| segment.len() >= 16;
+    if url.path_segments().is_some_and(|mut segments| segments.any(is_token)) {
+        let segments: Vec<String> = url
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .map(|segment| if is_token(segment) { REDACTED.to_string() } else { segment.to_string() })
+            .collect();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.clear().extend(segments);
+        }
+        redacted = true;
+    }
+    if redacted {
+        Cow::Owned(url.to_string())
+    } else {
+        Cow::Borrowed(raw)
+    }
+}
+
+fn error(code: &'static str, message: String) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(Cow::Owned(message));
+    error
+}
+
+fn pool_bounds(database: &DatabaseConfig) -> Result<(), ValidationError> {
+    if database.min_connections > database.max_connections {
+        return Err(error("pool_bounds", "min_connections cannot exceed max_connections".to_string()));
+    }
+    Ok(())
+}
+
+fn origins(origins: &Vec<String>) -> Result<(), ValidationError> {
+    for origin in origins {
+        let valid = url::Url::parse(origin).is_ok_and(|url| {
+            matches!(url.scheme(), "http" | "https")
+                && url.host().is_some()
+                && url.path() == "/"
+                && !origin.ends_with('/')
+                && url.query().is_none()
+        });
+        if !valid {
+            return Err(error("origin", format!("{} is not an origin like https://example.com", origin)));
+        }
+    }
+    Ok(())
+}
+
+fn backoff_bounds(webhooks: &WebhookConfig) -> Result<(), ValidationError> {
+    if webhooks.backoff_base_secs > webhooks.backoff_max_secs {
+        return Err(error("backoff_bounds", "backoff_base_secs cannot exceed backoff_max_secs".to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,16 @@
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("Expected an even number of hex digits, got {}", hex.len()));
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|chunk| {
+            let digits = std::str::from_utf8(chunk).map_err(|_| "Invalid UTF-8 in hex string".to_string())?;
+            u8::from_str_radix(digits, 16).map_err(|_| format!("Invalid hex digit in '{}'", digits))
+        })
+        .collect()
+}
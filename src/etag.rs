@@ -0,0 +1,73 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::IF_MATCH, request::Parts, HeaderValue, StatusCode},
+    Json,
+};
+use serde_json::json;
+
+/// Strong ETag for a row at `version`.
+pub fn etag(version: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{}\"", version)).expect("a quoted integer is a valid header value")
+}
+
+/// The `If-Match` precondition of a write. Requests without one are refused with 428 so a
+/// client cannot overwrite a change it has not seen.
+#[derive(Debug, Clone, Copy)]
+pub enum IfMatch {
+    /// `If-Match: *` — any current version.
+    Any,
+    Version(i64),
+    /// A tag that can never match, e.g. a weak or foreign ETag.
+    Unmatchable,
+}
+
+impl IfMatch {
+    /// The version a conditional UPDATE must see, or `None` for `*`.
+    pub fn version(&self) -> Option<i64> {
+        match self {
+            IfMatch::Any => None,
+            IfMatch::Version(version) => Some(*version),
+            IfMatch::Unmatchable => Some(-1),
+        }
+    }
+
+    fn parse(value: &str) -> IfMatch {
+        let value = value.trim();
+        if value == "*" {
+            return IfMatch::Any;
+        }
+        value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .and_then(|v| v.parse::<i64>().ok())
+            .map(IfMatch::Version)
+            .unwrap_or(IfMatch::Unmatchable)
+    }
+}
+
+pub fn precondition_failed(entity: &str, id: uuid::Uuid) -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = json!({
+        "status": "fail",
+        "message": format!("{} with ID: {} has changed since it was read; fetch it again and retry", entity, id)
+    });
+    (StatusCode::PRECONDITION_FAILED, Json(error_response))
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match parts.headers.get(IF_MATCH).and_then(|value| value.to_str().ok()) {
+            Some(value) => Ok(IfMatch::parse(value)),
+            None => {
+                let error_response = json!({
+                    "status": "fail",
+                    "message": "If-Match header with the resource's ETag is required",
+                });
+                Err((StatusCode::PRECONDITION_REQUIRED, Json(error_response)))
+            }
+        }
+    }
+}
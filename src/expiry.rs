@@ -0,0 +1,133 @@
+use std::{sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+use crate::{schema::EVENT_MERCHANT_RECORD_EXPIRED, AppState};
+
+pub const MERCHANT_RECORD_EXPIRED_EVENT: &str = EVENT_MERCHANT_RECORD_EXPIRED;
+
+const SWEEP_BATCH_SIZE: i64 = 500;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ExpiryConfig {
+    pub sweep_interval_secs: u64,
+    /// Expired records older than this many days are moved to `merchantsrecord_archive`.
+    /// `None` keeps them in place.
+    pub archive_after_days: Option<i64>,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        ExpiryConfig { sweep_interval_secs: 60, archive_after_days: None }
+    }
+}
+
+impl ExpiryConfig {
+    pub fn sweep_interval(&self) -> Duration {
+        Duration::from_secs(self.sweep_interval_secs)
+    }
+
+    pub fn archive_after(&self) -> Option<chrono::Duration> {
+        self.archive_after_days.map(chrono::Duration::days)
+    }
+}
+
+pub fn spawn_expiry_worker(app_state: Arc<AppState>, config: ExpiryConfig) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.sweep_interval());
+        loop {
+            interval.tick().await;
+            match expire_merchant_records(&app_state.db).await {
+                Ok(0) => {}
+                Ok(expired) => info!(expired, "marked merchant records as expired"),
+                Err(e) => error!(error = %e, "failed to expire merchant records"),
+            }
+            if let Some(archive_after) = config.archive_after() {
+                match archive_expired_merchant_records(&app_state.db, archive_after).await {
+                    Ok(0) => {}
+                    Ok(archived) => info!(archived, "archived expired merchant records"),
+                    Err(e) => error!(error = %e, "failed to archive merchant records"),
+                }
+            }
+        }
+    })
+}
+
+/// Marks every active record past its `valid_until` as expired and records one event per record,
+/// plus one in the outbox for webhooks.
+/// Works in batches so a large backlog never holds locks for long.
+#[tracing::instrument(name = "db.expire_merchant_records", skip_all, err)]
+pub async fn expire_merchant_records(db: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let mut total = 0;
+    loop {
+        let mut tx = db.begin().await?;
+        let expired = sqlx::query(
+            r#"WITH expired AS (
+                UPDATE merchantsrecord SET status = 'expired', expired_at = now()
+                WHERE id IN (
+                    SELECT id FROM merchantsrecord
+                    WHERE status = 'active' AND valid_until <= now() AND deleted_at IS NULL
+                    LIMIT $1 FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, merchant_id, valid_until, expired_at
+            ),
+            events AS (
+                INSERT INTO merchant_record_events (merchant_record_id, merchant_id, event_type, payload)
+                SELECT id, merchant_id, $2, jsonb_build_object('valid_until', valid_until, 'expired_at', expired_at)
+                FROM expired
+            )
+            INSERT INTO outbox_events (event_type, merchant_id, payload)
+            SELECT $2, merchant_id, jsonb_build_object('merchant_record_id', id, 'valid_until', valid_until, 'expired_at', expired_at)
+            FROM expired"#
+        )
+        .bind(SWEEP_BATCH_SIZE)
+        .bind(MERCHANT_RECORD_EXPIRED_EVENT)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        tx.commit().await?;
+
+        total += expired;
+        if expired < SWEEP_BATCH_SIZE as u64 {
+            return Ok(total);
+        }
+    }
+}
+
+#[tracing::instrument(name = "db.archive_expired_merchant_records", skip(db), err)]
+pub async fn archive_expired_merchant_records(
+    db: &Pool<Postgres>,
+    archive_after: chrono::Duration,
+) -> Result<u64, sqlx::Error> {
+    let cutoff = chrono::Utc::now() - archive_after;
+    let mut tx = db.begin().await?;
+    let archived = sqlx::query(
+        r#"WITH moved AS (
+            DELETE FROM merchantsrecord
+            WHERE id IN (
+                SELECT id FROM merchantsrecord
+                WHERE status = 'expired' AND expired_at < $1
+                LIMIT $2 FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+        )
+        INSERT INTO merchantsrecord_archive (
+            id, merchant_id, data_issued, valid_from, valid_until, prev_data_hash, record_type,
+            data_record, data_hash, signing_key_id, signature, status, expired_at, supersedes, deleted_at, format_version
+        )
+        SELECT
+            id, merchant_id, data_issued, valid_from, valid_until, prev_data_hash, record_type,
+            data_record, data_hash, signing_key_id, signature, status, expired_at, supersedes, deleted_at, format_version
+        FROM moved"#
+    )
+    .bind(cutoff)
+    .bind(SWEEP_BATCH_SIZE)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+    Ok(archived)
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{OriginalUri, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    auth::Principal,
+    handlers::merchanthandler::MERCHANT_ENTITY,
+    model::AuditLogModel,
+    pagination::{Keyset, KeysetRow},
+    response::{AuditLogList, ErrorBody, Status},
+    schema::{FilterOptions, SCOPE_ADMIN},
+    validation::ValidatedQuery,
+    AppState,
+};
+
+/// Change history of one entity, oldest first. Operators see any entity's history. A merchant
+/// key with `admin` sees the history of its own merchant and of what belongs to it, judged by
+/// the `merchant_id` each entry recorded; other entities read as having no history.
+#[utoipa::path(
+    get,
+    path = "/api/audit/{entity_type}/{entity_id}",
+    tag = "audit",
+    params(
+        ("entity_type" = String, Path, description = "e.g. dog, merchant, merchant_record"),
+        ("entity_id" = uuid::Uuid, Path),
+        FilterOptions,
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "One page of audit entries", body = AuditLogList),
+        (status = 400, description = "Invalid cursor", body = ErrorBody),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "A merchant key without admin", body = ErrorBody),
+        (status = 422, description = "A parameter failed validation", body = ErrorBody),
+    )
+)]
+pub async fn audit_history_handler(
+    uri: OriginalUri,
+    Path((entity_type, entity_id)): Path<(String, uuid::Uuid)>,
+    ValidatedQuery(opts): ValidatedQuery<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let merchant_id = match &principal {
+        Principal::Operator => None,
+        Principal::Merchant(auth) => {
+            auth.authorize(auth.merchant_id, SCOPE_ADMIN)?;
+            Some(auth.merchant_id)
+        }
+    };
+
+    let keyset = Keyset::timestamp("created_at", "timestamptz", opts.cursor.as_deref(), opts.limit)?;
+    let sql = format!(
+        "SELECT *, {} FROM audit_log WHERE entity_type = $1 AND entity_id = $2
+            AND ($6::uuid IS NULL OR (entity_type = $7 AND entity_id = $6) OR COALESCE(after, before)->>'merchant_id' = $6::text)
+            AND {} ORDER BY {} LIMIT $5",
+        keyset.select_columns(),
+        keyset.predicate(3),
+        keyset.order_by()
+    );
+    let query_result = sqlx::query_as::<_, KeysetRow<AuditLogModel>>(&sql)
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(keyset.cursor_key())
+        .bind(keyset.cursor_id())
+        .bind(keyset.fetch_limit())
+        .bind(merchant_id)
+        .bind(MERCHANT_ENTITY)
+        .fetch_all(&data.db)
+        .await;
+
+    if query_result.is_err() {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "Something bad happened while fetching audit history",
+        });
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    }
+
+    let page = keyset.page(query_result.unwrap());
+
+    Ok(Json(AuditLogList {
+        status: Status::Success,
+        results: page.items.len(),
+        links: page.links(&uri),
+        cursors: page.cursors(),
+        audit_log: page.items,
+    }))
+}
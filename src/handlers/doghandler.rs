@@ -1,35 +1,118 @@
-use std::sync::Arc;
+use std::{pin::pin, sync::Arc};
 
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    body::Body,
+    extract::{OriginalUri, Path, State},
+    http::{header::ETAG, HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
+use futures::StreamExt;
 use serde_json::json;
+use sqlx::{Connection, PgConnection};
+use validator::Validate;
 
 use crate::{
-    model::DogModel,
-    schema::{CreateDogSchema, FilterOptions, UpdateDogSchema},
+    audit::{record_audit, snapshot, Audited, AuditEntry, ACTION_CREATE, ACTION_DELETE, ACTION_RESTORE, ACTION_UPDATE},
+    auth::{Actor, Principal},
+    bulk::{export_response, import_rows, ImportFormat},
+    etag::{etag, precondition_failed, IfMatch},
+    patch::{apply_json_patch, PatchBody},
+    model::{DogModel, FacetCount, NearbyDogModel},
+    openapi::{IdempotencyKeyHeader, IfMatchHeader},
+    pagination::{Keyset, KeysetRow},
+    response::{DogData, DogFacets, DogList, Envelope, ErrorBody, ImportReport, ImportRowError, NearbyDogList, Status},
+    schema::{CreateDogSchema, DogFilterOptions, DogSortField, ExportOptions, NearbyOptions, SortOrder, UpdateDogSchema},
+    validation::{ValidatedJson, ValidatedQuery},
     AppState,
 };
 
 
+pub const DOG_ENTITY: &str = "dog";
+
+/// Filters shared by the dog list and its facet queries. `$1`..`$6` are bound by `bind_dog_filters`.
+const DOG_FILTERS: &str = r#"deleted_at IS NULL
+    AND ($1::text IS NULL OR search_vector @@ websearch_to_tsquery('simple', $1))
+    AND ($2::text IS NULL OR lower(breed) = lower($2))
+    AND ($3::text IS NULL OR lower(color) = lower($3))
+    AND ($4::text IS NULL OR lower(location) = lower($4))
+    AND ($5::int IS NULL OR prooflevel >= $5)
+    AND ($6::int IS NULL OR prooflevel <= $6)"#;
+
+fn bind_dog_filters<'q, O>(
+    query: sqlx::query::QueryAs<'q, sqlx::Postgres, O, sqlx::postgres::PgArguments>,
+    opts: &'q DogFilterOptions,
+    skip_breed: bool,
+    skip_color: bool,
+) -> sqlx::query::QueryAs<'q, sqlx::Postgres, O, sqlx::postgres::PgArguments> {
+    query
+        .bind(opts.q.as_deref())
+        .bind(opts.breed.as_deref().filter(|_| !skip_breed))
+        .bind(opts.color.as_deref().filter(|_| !skip_color))
+        .bind(opts.location.as_deref())
+        .bind(opts.prooflevel_min)
+        .bind(opts.prooflevel_max)
+}
+
+/// Counts per value of `column`, ignoring the filter on that same column so the
+/// front-end can show how many dogs each alternative would match.
+async fn dog_facet(
+    db: &sqlx::Pool<sqlx::Postgres>,
+    opts: &DogFilterOptions,
+    column: &str,
+) -> Result<Vec<FacetCount>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {column} AS value, COUNT(*) AS count FROM dogs WHERE {DOG_FILTERS} GROUP BY {column} ORDER BY count DESC, value"
+    );
+    bind_dog_filters(sqlx::query_as::<_, FacetCount>(&sql), opts, column == "breed", column == "color")
+        .fetch_all(db)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/dogs",
+    tag = "dogs",
+    params(DogFilterOptions),
+    responses(
+        (status = 200, description = "One page of dogs with facet counts", body = DogList),
+        (status = 400, description = "Invalid cursor or sort", body = ErrorBody),
+        (status = 422, description = "A filter failed validation", body = ErrorBody),
+    )
+)]
 pub async fn dog_list_handler(
-    opts: Option<Query<FilterOptions>>, State(data): State<Arc<AppState>>
+    uri: OriginalUri,
+    ValidatedQuery(opts): ValidatedQuery<DogFilterOptions>, State(data): State<Arc<AppState>>
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let Query(opts) = opts.unwrap_or_default();
+    let sort = match opts.sort {
+        Some(DogSortField::Relevance) if opts.q.is_none() => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": "sort=relevance requires a search query in q",
+            });
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+        Some(sort) => sort,
+        None if opts.q.is_some() => DogSortField::Relevance,
+        None => DogSortField::CreatedAt,
+    };
+    let order = opts.order.unwrap_or(if sort == DogSortField::Relevance { SortOrder::Desc } else { SortOrder::Asc });
     
-    let limit = opts.limit.unwrap_or(10);
-    let offset = (opts.page.unwrap_or(1) - 1) * limit;
-    let query_result = sqlx::query_as::<_, DogModel>(
-        r#"SELECT * FROM dogs ORDER by id LIMIT $1 OFFSET $2"#
-    )
-    .bind(limit as i32)
-    .bind(offset as i32)
-    .fetch_all(&data.db)
-    .await;
-    println!("result==========> {:?}", query_result);
+    let keyset = Keyset::new(sort.column(), sort.sql_type(), sort.name(), order, opts.cursor.as_deref(), opts.limit)?;
+    // `id` breaks ties so rows with equal sort values keep a stable order across pages.
+    let sql = format!(
+        "SELECT *, {} FROM dogs WHERE {DOG_FILTERS} AND {} ORDER BY {} LIMIT $9",
+        keyset.select_columns(),
+        keyset.predicate(7),
+        keyset.order_by()
+    );
+    let query_result = bind_dog_filters(sqlx::query_as::<_, KeysetRow<DogModel>>(&sql), &opts, false, false)
+        .bind(keyset.cursor_key())
+        .bind(keyset.cursor_id())
+        .bind(keyset.fetch_limit())
+        .fetch_all(&data.db)
+        .await;
     if query_result.is_err() {
         let error_response = serde_json::json!({
             "status": "fail",
@@ -38,38 +121,164 @@ pub async fn dog_list_handler(
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
 
-    let dogs = query_result.unwrap();
+    let page = keyset.page(query_result.unwrap());
 
-    let json_response = serde_json::json!({
-        "status": "success",
-        "results": dogs.len(),
-        "dogs": dogs
+    let total_sql = format!("SELECT NULL::text AS value, COUNT(*) AS count FROM dogs WHERE {DOG_FILTERS}");
+    let facets = tokio::try_join!(
+        bind_dog_filters(sqlx::query_as::<_, FacetCount>(&total_sql), &opts, false, false).fetch_one(&data.db),
+        dog_facet(&data.db, &opts, "breed"),
+        dog_facet(&data.db, &opts, "color"),
+    );
+    let (total, breed_facets, color_facets) = match facets {
+        Ok(facets) => facets,
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"status": "error","message": format!("{:?}", e)})),
+            ));
+        }
+    };
+
+    Ok(Json(DogList {
+        status: Status::Success,
+        results: page.items.len(),
+        total: total.count,
+        links: page.links(&uri),
+        cursors: page.cursors(),
+        dogs: page.items,
+        facets: DogFacets {
+            breed: breed_facets,
+            color: color_facets,
+        },
+    }))
+}
+
+const DEFAULT_NEARBY_RADIUS_KM: f64 = 5.0;
+
+fn coordinates_error(latitude: Option<f64>, longitude: Option<f64>) -> Option<&'static str> {
+    match (latitude, longitude) {
+        (None, None) => None,
+        (Some(lat), Some(lon)) if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon) => None,
+        (Some(_), Some(_)) => Some("latitude must be within [-90, 90] and longitude within [-180, 180]"),
+        _ => Some("latitude and longitude must be provided together"),
+    }
+}
+
+fn validate_coordinates(
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let Some(message) = coordinates_error(latitude, longitude) else {
+        return Ok(());
+    };
+    let error_response = serde_json::json!({
+        "status": "fail",
+        "message": message,
     });
-    Ok(Json(json_response))
+    Err((StatusCode::BAD_REQUEST, Json(error_response)))
+}
+
+/// Dogs within `radius_km` of a point, nearest first. The `earth_box` test uses the GiST
+/// index; `earth_distance` then trims the box's corners down to the exact radius.
+#[utoipa::path(
+    get,
+    path = "/api/dogs/nearby",
+    tag = "dogs",
+    params(NearbyOptions),
+    responses(
+        (status = 200, description = "Dogs within the radius, nearest first", body = NearbyDogList),
+        (status = 422, description = "Coordinates or radius out of range", body = ErrorBody),
+    )
+)]
+pub async fn nearby_dogs_handler(
+    ValidatedQuery(opts): ValidatedQuery<NearbyOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let radius_km = opts.radius_km.unwrap_or(DEFAULT_NEARBY_RADIUS_KM);
+    let limit = opts.limit.unwrap_or(50);
+
+    let query_result = sqlx::query_as::<_, NearbyDogModel>(
+        r#"SELECT * FROM (
+            SELECT *, earth_distance(ll_to_earth($1, $2), ll_to_earth(latitude, longitude)) / 1000.0 AS distance_km
+            FROM dogs
+            WHERE deleted_at IS NULL AND latitude IS NOT NULL AND longitude IS NOT NULL
+                AND earth_box(ll_to_earth($1, $2), $3 * 1000.0) @> ll_to_earth(latitude, longitude)
+        ) nearby
+        WHERE distance_km <= $3
+        ORDER BY distance_km, id
+        LIMIT $4"#
+    )
+    .bind(opts.lat)
+    .bind(opts.lon)
+    .bind(radius_km)
+    .bind(limit as i32)
+    .fetch_all(&data.db)
+    .await;
+
+    match query_result {
+        Ok(dogs) => Ok(Json(NearbyDogList {
+            status: Status::Success,
+            results: dogs.len(),
+            radius_km,
+            dogs,
+        })),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error","message": format!("{:?}", e)})),
+        )),
+    }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/dogs/",
+    tag = "dogs",
+    params(IdempotencyKeyHeader),
+    request_body = CreateDogSchema,
+    responses(
+        (status = 201, description = "Dog created", body = Envelope<DogData>, headers(("ETag" = String))),
+        (status = 400, description = "Only one of latitude and longitude was given", body = ErrorBody),
+        (status = 409, description = "Duplicate dog, or the Idempotency-Key is still in use", body = ErrorBody),
+        (status = 422, description = "Body failed validation", body = ErrorBody),
+    )
+)]
 pub async fn create_dog_handler(
     State(data): State<Arc<AppState>>,
-    Json(body): Json<CreateDogSchema>,
+    actor: Actor,
+    ValidatedJson(body): ValidatedJson<CreateDogSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    validate_coordinates(body.latitude, body.longitude)?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
     let query_result = sqlx::query_as::<_, DogModel>(
-        r#"INSERT INTO dogs (name,breed,color, location, prooflevel) VALUES ($1, $2, $3, $4, $5) RETURNING *"#
+        r#"INSERT INTO dogs (name,breed,color, location, latitude, longitude, prooflevel) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *"#
     )
     .bind(body.name)
     .bind(body.breed)
     .bind(body.color)
     .bind(body.location)
+    .bind(body.latitude)
+    .bind(body.longitude)
     .bind(body.prooflevel)
-    .fetch_one(&data.db)
+    .fetch_one(&mut *tx)
     .await;
 
     match query_result {
         Ok(dog) => {
-            let dog_response = json!({"status": "success","data": json!({
-                "dog": dog
-            })});
+            record_audit(&mut tx, AuditEntry {
+                actor: actor.as_str(),
+                entity_type: DOG_ENTITY,
+                entity_id: dog.id,
+                action: ACTION_CREATE,
+                before: None,
+                after: snapshot(&dog),
+            })
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
 
-            return Ok((StatusCode::CREATED, Json(dog_response)));
+            let dog_etag = etag(dog.version);
+            Ok((StatusCode::CREATED, [(ETAG, dog_etag)], Envelope::success(DogData { dog })))
         }
         Err(e) => {
             if e.to_string()
@@ -81,112 +290,540 @@ pub async fn create_dog_handler(
                 });
                 return Err((StatusCode::CONFLICT, Json(error_response)));
             }
-            return Err((
+            Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({"status": "error","message": format!("{:?}", e)})),
-            ));
+            ))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/dogs/{id}",
+    tag = "dogs",
+    params(("id" = uuid::Uuid, Path)),
+    responses(
+        (status = 200, description = "The dog", body = Envelope<DogData>, headers(("ETag" = String))),
+        (status = 404, description = "No such dog, or it was deleted", body = ErrorBody),
+    )
+)]
 pub async fn get_dog_handler(
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = sqlx::query_as::<_, DogModel>( r#"SELECT * FROM dogs WHERE id = $1"#)
+    let query_result = sqlx::query_as::<_, DogModel>( r#"SELECT * FROM dogs WHERE id = $1 AND deleted_at IS NULL"#)
         .bind(id)
         .fetch_one(&data.db)
         .await;
 
     match query_result {
         Ok(dog) => {
-            let dog_response = serde_json::json!({"status": "success","data": serde_json::json!({
-                "dog": dog
-            })});
-
-            return Ok(Json(dog_response));
+            let dog_etag = etag(dog.version);
+            Ok(([(ETAG, dog_etag)], Envelope::success(DogData { dog })))
         }
         Err(_) => {
             let error_response = serde_json::json!({
                 "status": "fail",
                 "message": format!("Dog with ID: {} not found", id)
             });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
         }
     }
 }
 
+/// The fields of a dog that a JSON Patch may change, as the document it is applied to.
+fn editable_dog_fields(dog: &DogModel) -> serde_json::Value {
+    json!({
+        "name": dog.name,
+        "breed": dog.breed,
+        "color": dog.color,
+        "location": dog.location,
+        "latitude": dog.latitude,
+        "longitude": dog.longitude,
+        "prooflevel": dog.prooflevel,
+    })
+}
+
+/// Accepts a merge patch (`application/merge-patch+json` or `application/json`) or a JSON
+/// Patch (`application/json-patch+json`), and applies it in one conditional UPDATE so an edit
+/// based on a stale read fails with 412 instead of overwriting someone else's change.
+#[utoipa::path(
+    patch,
+    path = "/api/dogs/{id}",
+    tag = "dogs",
+    params(("id" = uuid::Uuid, Path), IfMatchHeader),
+    request_body(
+        content(
+            (UpdateDogSchema = "application/merge-patch+json"),
+            (UpdateDogSchema = "application/json"),
+            (Vec<serde_json::Value> = "application/json-patch+json"),
+        )
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The updated dog", body = Envelope<DogData>, headers(("ETag" = String))),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "Neither the operator nor an admin key", body = ErrorBody),
+        (status = 400, description = "Only one of latitude and longitude was given", body = ErrorBody),
+        (status = 404, description = "No such dog", body = ErrorBody),
+        (status = 412, description = "The dog changed since the If-Match ETag was read", body = ErrorBody),
+        (status = 415, description = "Not a merge patch or JSON Patch", body = ErrorBody),
+        (status = 422, description = "Patch failed to apply or validate", body = ErrorBody),
+        (status = 428, description = "If-Match header missing", body = ErrorBody),
+    )
+)]
 pub async fn edit_dog_handler(
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
-    Json(body): Json<UpdateDogSchema>,
+    principal: Principal,
+    if_match: IfMatch,
+    body: PatchBody<UpdateDogSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = sqlx::query_as::<_, DogModel>(r#"SELECT * FROM dogs WHERE id = $1"#)
-        .bind(id)
-        .fetch_one(&data.db)
-        .await;
+    principal.authorize_admin()?;
+    let actor = Actor::principal(&principal);
+    let mut expected_version = if_match.version();
+    let body = match body {
+        PatchBody::Merge(body) => body,
+        PatchBody::JsonPatch(operations) => {
+            let dog = sqlx::query_as::<_, DogModel>(r#"SELECT * FROM dogs WHERE id = $1 AND deleted_at IS NULL"#)
+                .bind(id)
+                .fetch_optional(&data.db)
+                .await
+                .map_err(internal_error)?;
+            let Some(dog) = dog else {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": format!("Dog with ID: {} not found", id)
+                });
+                return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            };
+            // The patch was applied to this version, so only write over this version.
+            expected_version = expected_version.or(Some(dog.version));
+            apply_json_patch(editable_dog_fields(&dog), &operations)?
+        }
+    };
 
-    if query_result.is_err() {
+    if body.latitude.is_absent() != body.longitude.is_absent() {
         let error_response = serde_json::json!({
             "status": "fail",
-            "message": format!("Dog with ID: {} not found", id)
+            "message": "latitude and longitude must be provided together",
         });
-        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
     }
+    validate_coordinates(body.latitude.value().copied(), body.longitude.value().copied())?;
+    let name = body.name.required("name")?;
+    let color = body.color.required("color")?;
+    let (set_breed, breed) = body.breed.into_update();
+    let (set_location, location) = body.location.into_update();
+    let (set_coordinates, latitude) = body.latitude.into_update();
+    let (_, longitude) = body.longitude.into_update();
+    let (set_prooflevel, prooflevel) = body.prooflevel.into_update();
 
-    let now = chrono::Utc::now();
-    let dog = query_result.unwrap();
-
-    let query_result = sqlx::query_as::<_, DogModel>(
-        r#"UPDATE dogs SET name = $1, breed = $2, color = $3, location = $4, prooflevel = $5, updated_at = $6 WHERE id = $7 RETURNING *"#
+    // `prev` locks the row and is read only for the audit log's before image; the SET keeps
+    // unpatched fields from the row being updated, so a concurrent edit is never undone.
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let query_result = sqlx::query_as::<_, Audited<DogModel>>(
+        r#"UPDATE dogs SET
+            name = COALESCE($1, dogs.name),
+            color = COALESCE($2, dogs.color),
+            breed = CASE WHEN $3 THEN $4 ELSE dogs.breed END,
+            location = CASE WHEN $5 THEN $6 ELSE dogs.location END,
+            latitude = CASE WHEN $7 THEN $8 ELSE dogs.latitude END,
+            longitude = CASE WHEN $7 THEN $9 ELSE dogs.longitude END,
+            prooflevel = CASE WHEN $10 THEN $11 ELSE dogs.prooflevel END
+        FROM (SELECT * FROM dogs WHERE id = $12 FOR UPDATE) AS prev
+        WHERE dogs.id = $12 AND prev.id = dogs.id AND dogs.deleted_at IS NULL
+            AND ($13::bigint IS NULL OR dogs.version = $13)
+        RETURNING dogs.*, to_jsonb(prev) - 'search_vector' AS before"#
     )
-    .bind(body.name.to_owned().unwrap_or(dog.name))
-    .bind(body.breed.clone().or(dog.breed))
-    .bind(body.color.to_owned().unwrap_or(dog.color))
-    .bind(body.location.clone().or(dog.location))
-    .bind(body.prooflevel.or(dog.prooflevel))
-    .bind(now)
+    .bind(name)
+    .bind(color)
+    .bind(set_breed)
+    .bind(breed)
+    .bind(set_location)
+    .bind(location)
+    .bind(set_coordinates)
+    .bind(latitude)
+    .bind(longitude)
+    .bind(set_prooflevel)
+    .bind(prooflevel)
     .bind(id)
-    .fetch_one(&data.db)
+    .bind(expected_version)
+    .fetch_optional(&mut *tx)
     .await
-    ;
+    .map_err(internal_error)?;
 
-    match query_result {
-        Ok(dog) => {
-            let dog_response = serde_json::json!({"status": "success","data": serde_json::json!({
-                "dog": dog
-            })});
+    let Some(Audited { row: dog, before }) = query_result else {
+        let exists = sqlx::query_scalar::<_, i64>(r#"SELECT version FROM dogs WHERE id = $1 AND deleted_at IS NULL"#)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+        if exists.is_some() {
+            return Err(precondition_failed("Dog", id));
+        }
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Dog with ID: {} not found", id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: DOG_ENTITY,
+        entity_id: dog.id,
+        action: ACTION_UPDATE,
+        before,
+        after: snapshot(&dog),
+    })
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    let dog_etag = etag(dog.version);
+    Ok(([(ETAG, dog_etag)], Envelope::success(DogData { dog })))
+}
+
+const IMPORT_BATCH_ROWS: usize = 500;
+/// Rows listed individually in an import report; later failures are only counted.
+const MAX_REPORTED_IMPORT_ERRORS: usize = 1000;
+
+/// Dogs from one import, buffered until a batch is full.
+struct DogImport<'a> {
+    data: &'a AppState,
+    actor: &'a Actor,
+    pending: Vec<(usize, CreateDogSchema)>,
+    imported: u64,
+    failed: u64,
+    errors: Vec<ImportRowError>,
+}
 
-            return Ok(Json(dog_response));
+impl DogImport<'_> {
+    fn reject(&mut self, row: usize, message: String) {
+        self.failed += 1;
+        if self.errors.len() < MAX_REPORTED_IMPORT_ERRORS {
+            self.errors.push(ImportRowError { row, message });
         }
-        Err(err) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"status": "error","message": format!("{:?}", err)})),
-            ));
+    }
+
+    async fn push(&mut self, row: usize, dog: CreateDogSchema) {
+        if let Err(errors) = dog.validate() {
+            return self.reject(row, errors.to_string());
+        }
+        if let Some(message) = coordinates_error(dog.latitude, dog.longitude) {
+            return self.reject(row, message.to_string());
+        }
+
+        self.pending.push((row, dog));
+        if self.pending.len() == IMPORT_BATCH_ROWS {
+            self.flush().await;
+        }
+    }
+
+    /// Inserts the pending dogs in one transaction. If the database refuses a row, only that
+    /// row is reported; if the transaction itself fails, every row of the batch is.
+    async fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let batch = std::mem::take(&mut self.pending);
+        if let Err(e) = self.insert_batch(&batch).await {
+            for (row, _) in &batch {
+                self.reject(*row, format!("{}", e));
+            }
+        }
+    }
+
+    /// Tries the whole batch as one statement under a savepoint, and when that fails, each
+    /// row under a savepoint of its own.
+    async fn insert_batch(&mut self, batch: &[(usize, CreateDogSchema)]) -> Result<(), sqlx::Error> {
+        let mut tx = self.data.db.begin().await?;
+        let mut savepoint = tx.begin().await?;
+        match insert_dogs(&mut savepoint, self.actor, batch).await {
+            Ok(inserted) => {
+                savepoint.commit().await?;
+                tx.commit().await?;
+                self.imported += inserted;
+                return Ok(());
+            }
+            Err(_) => savepoint.rollback().await?,
+        }
+
+        let mut inserted = 0;
+        let mut refused = Vec::new();
+        for dog in batch {
+            let mut savepoint = tx.begin().await?;
+            match insert_dogs(&mut savepoint, self.actor, std::slice::from_ref(dog)).await {
+                Ok(count) => {
+                    savepoint.commit().await?;
+                    inserted += count;
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    refused.push((dog.0, format!("{}", e)));
+                }
+            }
+        }
+        tx.commit().await?;
+        self.imported += inserted;
+        for (row, message) in refused {
+            self.reject(row, message);
+        }
+        Ok(())
+    }
+}
+
+/// Inserts `dogs` and their audit entries in one statement, returning how many were inserted.
+async fn insert_dogs(conn: &mut PgConnection, actor: &Actor, dogs: &[(usize, CreateDogSchema)]) -> Result<u64, sqlx::Error> {
+    let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+        "WITH inserted AS (INSERT INTO dogs (name, breed, color, location, latitude, longitude, prooflevel) "
+    );
+    builder.push_values(dogs, |mut values, (_, dog)| {
+        values
+            .push_bind(&dog.name)
+            .push_bind(&dog.breed)
+            .push_bind(&dog.color)
+            .push_bind(&dog.location)
+            .push_bind(dog.latitude)
+            .push_bind(dog.longitude)
+            .push_bind(dog.prooflevel);
+    });
+    builder
+        .push(" RETURNING *) INSERT INTO audit_log (actor, entity_type, entity_id, action, after) SELECT ")
+        .push_bind(actor.as_str())
+        .push(", ")
+        .push_bind(DOG_ENTITY)
+        .push(", id, ")
+        .push_bind(ACTION_CREATE)
+        .push(", to_jsonb(inserted) - 'search_vector' FROM inserted");
+
+    Ok(builder.build().execute(conn).await?.rows_affected())
+}
+
+/// Bulk-creates dogs from a CSV (`text/csv`, with a header row) or NDJSON
+/// (`application/x-ndjson`) body shaped like `CreateDogSchema`. The body is parsed as it
+/// streams in and valid rows are inserted in batches of `IMPORT_BATCH_ROWS`, each committed
+/// on its own; the response reports every row that was skipped and why.
+#[utoipa::path(
+    post,
+    path = "/api/dogs/import",
+    tag = "dogs",
+    request_body(
+        content(
+            (String = "text/csv"),
+            (CreateDogSchema = "application/x-ndjson"),
+        ),
+        description = "One dog per CSV row or NDJSON line"
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "Counts of imported and skipped rows", body = ImportReport),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "Neither the operator nor an admin key", body = ErrorBody),
+        (status = 415, description = "Neither CSV nor NDJSON", body = ErrorBody),
+    )
+)]
+pub async fn import_dogs_handler(
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize_admin()?;
+    let actor = Actor::principal(&principal);
+    let format = ImportFormat::from_headers(&headers)?;
+
+    let mut import = DogImport {
+        data: &data,
+        actor: &actor,
+        pending: Vec::with_capacity(IMPORT_BATCH_ROWS),
+        imported: 0,
+        failed: 0,
+        errors: Vec::new(),
+    };
+    let mut rows = pin!(import_rows::<CreateDogSchema>(body, format));
+    let mut read_error = None;
+    while let Some(row) = rows.next().await {
+        match row {
+            Ok((row, Ok(dog))) => import.push(row, dog).await,
+            Ok((row, Err(message))) => import.reject(row, message),
+            Err(e) => {
+                read_error = Some(e.to_string());
+                break;
+            }
         }
     }
+    import.flush().await;
+
+    Ok(Json(ImportReport {
+        status: if read_error.is_some() { Status::Fail } else { Status::Success },
+        message: read_error.map(|e| format!("Import stopped early, the body could not be read: {}", e)),
+        imported: import.imported,
+        failed: import.failed,
+        errors: import.errors,
+    }))
+}
+
+fn dog_export_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("breed", DataType::Utf8, true),
+        Field::new("color", DataType::Utf8, false),
+        Field::new("location", DataType::Utf8, true),
+        Field::new("latitude", DataType::Float64, true),
+        Field::new("longitude", DataType::Float64, true),
+        Field::new("prooflevel", DataType::Int32, true),
+        Field::new("created_at", DataType::Timestamp(TimeUnit::Microsecond, None), true),
+        Field::new("updated_at", DataType::Timestamp(TimeUnit::Microsecond, None), true),
+        Field::new("deleted_at", DataType::Timestamp(TimeUnit::Microsecond, None), true),
+        Field::new("version", DataType::Int64, false),
+    ]))
 }
 
+/// Streams every dog matching the list filters as CSV, NDJSON or Parquet.
+#[utoipa::path(
+    get,
+    path = "/api/dogs/export",
+    tag = "dogs",
+    params(ExportOptions, DogFilterOptions),
+    responses(
+        (
+            status = 200,
+            description = "A download of every matching dog",
+            content(
+                (String = "text/csv"),
+                (DogModel = "application/x-ndjson"),
+                (Vec<u8> = "application/vnd.apache.parquet"),
+            )
+        ),
+        (status = 422, description = "A filter failed validation", body = ErrorBody),
+    )
+)]
+pub async fn export_dogs_handler(
+    ValidatedQuery(export): ValidatedQuery<ExportOptions>,
+    ValidatedQuery(opts): ValidatedQuery<DogFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let rows = async_stream::stream! {
+        let sql = format!("SELECT * FROM dogs WHERE {DOG_FILTERS} ORDER BY created_at, id");
+        let mut rows = bind_dog_filters(sqlx::query_as::<_, DogModel>(&sql), &opts, false, false).fetch(&data.db);
+        while let Some(row) = rows.next().await {
+            yield row;
+        }
+    };
+    export_response(rows, export.format.unwrap_or_default(), dog_export_schema(), "dogs")
+}
+
+/// Soft delete: the row stays so `zkptable` and lost reports keep a valid `dog_id`.
+#[utoipa::path(
+    delete,
+    path = "/api/dogs/{id}",
+    tag = "dogs",
+    params(("id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 204, description = "Dog deleted"),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "Neither the operator nor an admin key", body = ErrorBody),
+        (status = 404, description = "No such dog, or it is already deleted", body = ErrorBody),
+    )
+)]
 pub async fn delete_dog_handler(
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
+    principal: Principal,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let rows_affected = sqlx::query(r#"DELETE FROM dogs  WHERE id = $1"#)
+    principal.authorize_admin()?;
+    let actor = Actor::principal(&principal);
+    set_dog_deleted(&data, id, &actor, true).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/dogs/{id}/restore",
+    tag = "dogs",
+    params(("id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The restored dog", body = Envelope<DogData>, headers(("ETag" = String))),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "Neither the operator nor an admin key", body = ErrorBody),
+        (status = 404, description = "No deleted dog with that ID", body = ErrorBody),
+    )
+)]
+pub async fn restore_dog_handler(
+    Path(id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize_admin()?;
+    let actor = Actor::principal(&principal);
+    let dog = set_dog_deleted(&data, id, &actor, false).await?;
+
+    let dog_etag = etag(dog.version);
+    Ok(([(ETAG, dog_etag)], Envelope::success(DogData { dog })))
+}
+
+async fn set_dog_deleted(
+    data: &AppState,
+    id: uuid::Uuid,
+    actor: &Actor,
+    deleted: bool,
+) -> Result<DogModel, (StatusCode, Json<serde_json::Value>)> {
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let before = sqlx::query_as::<_, DogModel>(
+        r#"SELECT * FROM dogs WHERE id = $1 AND (deleted_at IS NULL) = $2 FOR UPDATE"#
+    )
     .bind(id)
-        .execute(&data.db)
-        .await
-        .unwrap()
-        .rows_affected();
+    .bind(deleted)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
 
-    if rows_affected == 0 {
+    let Some(before) = before else {
         let error_response = serde_json::json!({
             "status": "fail",
-            "message": format!("Note with ID: {} not found", id)
+            "message": if deleted {
+                format!("Dog with ID: {} not found", id)
+            } else {
+                format!("Deleted dog with ID: {} not found", id)
+            }
         });
         return Err((StatusCode::NOT_FOUND, Json(error_response)));
-    }
+    };
 
-    Ok(StatusCode::NO_CONTENT)
+    let dog = sqlx::query_as::<_, DogModel>(
+        r#"UPDATE dogs SET deleted_at = CASE WHEN $2 THEN now() ELSE NULL END WHERE id = $1 RETURNING *"#
+    )
+    .bind(id)
+    .bind(deleted)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: DOG_ENTITY,
+        entity_id: id,
+        action: if deleted { ACTION_DELETE } else { ACTION_RESTORE },
+        before: snapshot(&before),
+        after: snapshot(&dog),
+    })
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(dog)
 }
 
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+    Json,
+};
+use futures::{Stream, StreamExt};
+
+use crate::{
+    auth::MerchantAuth,
+    live::{feed, Delivery, Subscription},
+    response::ErrorBody,
+    schema::EventStreamOptions,
+    validation::ValidatedQuery,
+    AppState,
+};
+
+/// Close code telling WebSocket clients the server is going away and they should reconnect.
+const CLOSE_GOING_AWAY: u16 = 1001;
+/// Close code telling WebSocket clients their API key was revoked and reconnecting with it
+/// will not help.
+const CLOSE_POLICY_VIOLATION: u16 = 1008;
+
+#[utoipa::path(
+    get,
+    path = "/api/events/stream",
+    tag = "events",
+    params(EventStreamOptions),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "A `text/event-stream` of live events; each event's name is its `type` and its data the event as JSON. `lagged` means the client fell behind and missed `data.missed` events; `revoked`, sent last, means the API key was revoked or rotated", content_type = "text/event-stream", body = String),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "A merchant topic was requested by a key without records:read", body = ErrorBody),
+        (status = 422, description = "Unknown topics", body = ErrorBody),
+    )
+)]
+pub async fn event_stream_handler(
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+    ValidatedQuery(opts): ValidatedQuery<EventStreamOptions>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let subscription = Subscription::new(&auth, opts)?;
+
+    // Subscribed before responding, so nothing committed after the response starts is missed.
+    let events = feed(
+        data.live.subscribe(),
+        subscription,
+        data.db.clone(),
+        data.config.live.revalidate(),
+        data.shutdown.clone(),
+    )
+    .map(|delivery| {
+        Event::default().event(delivery.event_type()).json_data(delivery.to_json())
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::new().interval(data.config.live.keep_alive())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/events/ws",
+    tag = "events",
+    params(EventStreamOptions),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 101, description = "Upgraded to a WebSocket carrying the same events as `/api/events/stream`, one JSON text message each. Closed with 1008 once the API key is revoked or rotated"),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "A merchant topic was requested by a key without records:read", body = ErrorBody),
+        (status = 422, description = "Unknown topics", body = ErrorBody),
+    )
+)]
+pub async fn event_socket_handler(
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+    ValidatedQuery(opts): ValidatedQuery<EventStreamOptions>,
+    upgrade: WebSocketUpgrade,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let subscription = Subscription::new(&auth, opts)?;
+
+    let events = feed(
+        data.live.subscribe(),
+        subscription,
+        data.db.clone(),
+        data.config.live.revalidate(),
+        data.shutdown.clone(),
+    );
+    Ok(upgrade.on_upgrade(move |socket| forward(socket, events)))
+}
+
+/// Sends events until the client goes away, its key is revoked or shutdown ends the feed.
+/// Messages from the client are read only to notice it closing; the subscription is fixed at
+/// connect time.
+async fn forward(mut socket: WebSocket, events: impl Stream<Item = Delivery>) {
+    let mut events = std::pin::pin!(events);
+    loop {
+        tokio::select! {
+            delivery = events.next() => {
+                let Some(delivery) = delivery else {
+                    let close = CloseFrame { code: CLOSE_GOING_AWAY, reason: "server shutting down".into() };
+                    let _ = socket.send(Message::Close(Some(close))).await;
+                    return;
+                };
+                if let Delivery::Revoked = delivery {
+                    let close = CloseFrame { code: CLOSE_POLICY_VIOLATION, reason: "API key revoked".into() };
+                    let _ = socket.send(Message::Close(Some(close))).await;
+                    return;
+                }
+                if socket.send(Message::Text(delivery.to_json().to_string())).await.is_err() {
+                    return;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,661 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{OriginalUri, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::PgConnection;
+
+use crate::{
+    audit::{record_audit, snapshot, AuditEntry, ACTION_CREATE, ACTION_UPDATE},
+    auth::{Actor, Principal},
+    handlers::{doghandler::DOG_ENTITY, zkphandler::hash_to_array},
+    live::ProofJob,
+    model::{DogModel, LostReportModel, SightingModel, ZkpModel},
+    outbox::{enqueue, OutboxEvent},
+    pagination::{Keyset, KeysetRow},
+    response::{Envelope, ErrorBody, LostReportData, LostReportDetailData, LostReportList, SightingData, SightingMatchData, Status},
+    schema::{CreateLostReportSchema, CreateSightingSchema, LostReportFilterOptions, SightingMatchSchema, EVENT_ZKP_SIGNIN_FAILED, EVENT_ZKP_SIGNIN_SUCCEEDED},
+    zkpgenerate::{scalar_from_hex, zkpproof_sign_in, zkpproof_verify},
+    validation::{validate, ValidatedJson, ValidatedQuery},
+    AppState,
+};
+
+pub const DOG_FOUND_EVENT: &str = "dog.found";
+pub const DOG_REUNITED_EVENT: &str = "dog.reunited";
+
+pub const LOST_REPORT_ENTITY: &str = "lost_report";
+pub const SIGHTING_ENTITY: &str = "sighting";
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
+
+/// Rejects the request unless `principal` is the operator or a key of the merchant that
+/// opened `lost_report`.
+fn authorize_owner(principal: &Principal, lost_report: &LostReportModel) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    match principal {
+        Principal::Merchant(auth) if lost_report.owner_merchant_id != Some(auth.merchant_id) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": format!("Only the owner or the operator may change lost report with ID: {}", lost_report.id)
+            });
+            Err((StatusCode::FORBIDDEN, Json(error_response)))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/dogs/{id}/lost",
+    tag = "lost-found",
+    params(("id" = uuid::Uuid, Path, description = "Dog ID")),
+    request_body(content = Option<CreateLostReportSchema>, description = "Location fields default to the dog's own"),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "Lost report opened", body = Envelope<LostReportData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 404, description = "No such dog", body = ErrorBody),
+        (status = 409, description = "The dog already has an open lost report", body = ErrorBody),
+        (status = 422, description = "Body failed validation", body = ErrorBody),
+    )
+)]
+pub async fn create_lost_report_handler(
+    Path(dog_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+    body: Option<Json<CreateLostReportSchema>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let actor = Actor::principal(&principal);
+    // The merchant opening the report owns it; the operator's reports have no owner.
+    let owner_merchant_id = match &principal {
+        Principal::Operator => None,
+        Principal::Merchant(auth) => Some(auth.merchant_id),
+    };
+    let Json(body) = body.unwrap_or_default();
+    validate(&body)?;
+
+    let dog = sqlx::query_as::<_, DogModel>(r#"SELECT * FROM dogs WHERE id = $1 AND deleted_at IS NULL"#)
+        .bind(dog_id)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let Some(dog) = dog else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Dog with ID: {} not found", dog_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let query_result = sqlx::query_as::<_, LostReportModel>(
+        r#"INSERT INTO lost_reports (dog_id, owner_merchant_id, last_seen_location, latitude, longitude, description) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *"#
+    )
+    .bind(dog_id)
+    .bind(owner_merchant_id)
+    .bind(body.last_seen_location.or(dog.location))
+    .bind(body.latitude.or(dog.latitude))
+    .bind(body.longitude.or(dog.longitude))
+    .bind(body.description)
+    .fetch_one(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(lost_report) => {
+            record_audit(&mut tx, AuditEntry {
+                actor: actor.as_str(),
+                entity_type: LOST_REPORT_ENTITY,
+                entity_id: lost_report.id,
+                action: ACTION_CREATE,
+                before: None,
+                after: snapshot(&lost_report),
+            })
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
+
+            Ok((StatusCode::CREATED, Envelope::success(LostReportData { lost_report })))
+        }
+        Err(e) => {
+            if e.to_string()
+                .contains("duplicate key value violates unique constraint")
+            {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": format!("Dog with ID: {} already has an open lost report", dog_id),
+                });
+                return Err((StatusCode::CONFLICT, Json(error_response)));
+            }
+            Err(internal_error(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/lostreports",
+    tag = "lost-found",
+    params(LostReportFilterOptions),
+    responses(
+        (status = 200, description = "One page of lost reports, oldest first", body = LostReportList),
+        (status = 400, description = "Invalid cursor", body = ErrorBody),
+        (status = 422, description = "A parameter failed validation", body = ErrorBody),
+    )
+)]
+pub async fn lost_report_list_handler(
+    uri: OriginalUri,
+    ValidatedQuery(opts): ValidatedQuery<LostReportFilterOptions>, State(data): State<Arc<AppState>>
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let keyset = Keyset::timestamp("created_at", "timestamp", opts.cursor.as_deref(), opts.limit)?;
+    let sql = format!(
+        "SELECT *, {} FROM lost_reports WHERE ($1::text IS NULL OR status = $1) AND {} ORDER BY {} LIMIT $4",
+        keyset.select_columns(),
+        keyset.predicate(2),
+        keyset.order_by()
+    );
+    let query_result = sqlx::query_as::<_, KeysetRow<LostReportModel>>(&sql)
+        .bind(opts.status)
+        .bind(keyset.cursor_key())
+        .bind(keyset.cursor_id())
+        .bind(keyset.fetch_limit())
+        .fetch_all(&data.db)
+        .await;
+
+    if query_result.is_err() {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "Something bad happened while fetching lost reports",
+        });
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    }
+
+    let page = keyset.page(query_result.unwrap());
+
+    Ok(Json(LostReportList {
+        status: Status::Success,
+        results: page.items.len(),
+        links: page.links(&uri),
+        cursors: page.cursors(),
+        lost_reports: page.items,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/lostreports/{id}",
+    tag = "lost-found",
+    params(("id" = uuid::Uuid, Path)),
+    responses(
+        (status = 200, description = "The lost report and its sightings", body = Envelope<LostReportDetailData>),
+        (status = 404, description = "No such lost report", body = ErrorBody),
+    )
+)]
+pub async fn get_lost_report_handler(
+    Path(lost_report_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let lost_report = sqlx::query_as::<_, LostReportModel>(r#"SELECT * FROM lost_reports WHERE id = $1"#)
+        .bind(lost_report_id)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let Some(lost_report) = lost_report else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Lost report with ID: {} not found", lost_report_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let sightings = sqlx::query_as::<_, SightingModel>(
+        r#"SELECT * FROM sightings WHERE lost_report_id = $1 ORDER by created_at"#
+    )
+    .bind(lost_report_id)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Envelope::success(LostReportDetailData { lost_report, sightings }))
+}
+
+/// Moves a report from one of `from` to `to` on `conn`, or explains why the transition is not
+/// allowed. Only the report's owner or the operator may move it.
+async fn transition_lost_report(
+    conn: &mut PgConnection,
+    principal: &Principal,
+    lost_report_id: uuid::Uuid,
+    from: &[&str],
+    to: &str,
+) -> Result<LostReportModel, (StatusCode, Json<serde_json::Value>)> {
+    let current = sqlx::query_as::<_, LostReportModel>(r#"SELECT * FROM lost_reports WHERE id = $1 FOR UPDATE"#)
+        .bind(lost_report_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(internal_error)?;
+
+    let Some(current) = current else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Lost report with ID: {} not found", lost_report_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+    authorize_owner(principal, &current)?;
+
+    if !from.contains(&current.status.as_str()) {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Lost report with ID: {} is '{}' and cannot become '{}'", lost_report_id, current.status, to)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let lost_report = sqlx::query_as::<_, LostReportModel>(
+        r#"UPDATE lost_reports SET status = $1, resolved_at = CASE WHEN $1 IN ('reunited', 'cancelled') THEN now() ELSE resolved_at END WHERE id = $2 RETURNING *"#
+    )
+    .bind(to)
+    .bind(lost_report_id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(internal_error)?;
+
+    record_audit(conn, AuditEntry {
+        actor: Actor::principal(principal).as_str(),
+        entity_type: LOST_REPORT_ENTITY,
+        entity_id: lost_report.id,
+        action: ACTION_UPDATE,
+        before: snapshot(&current),
+        after: snapshot(&lost_report),
+    })
+    .await
+    .map_err(internal_error)?;
+
+    Ok(lost_report)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/lostreports/{id}/cancel",
+    tag = "lost-found",
+    params(("id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The cancelled lost report", body = Envelope<LostReportData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "Neither the report's owner nor the operator", body = ErrorBody),
+        (status = 404, description = "No such lost report", body = ErrorBody),
+        (status = 409, description = "The report is already closed", body = ErrorBody),
+    )
+)]
+pub async fn cancel_lost_report_handler(
+    Path(lost_report_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let lost_report = transition_lost_report(&mut tx, &principal, lost_report_id, &["lost", "found"], "cancelled").await?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Envelope::success(LostReportData { lost_report }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/lostreports/{id}/reunite",
+    tag = "lost-found",
+    params(("id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The reunited lost report", body = Envelope<LostReportData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "Neither the report's owner nor the operator", body = ErrorBody),
+        (status = 404, description = "No such lost report", body = ErrorBody),
+        (status = 409, description = "The dog has not been found yet", body = ErrorBody),
+    )
+)]
+pub async fn reunite_lost_report_handler(
+    Path(lost_report_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let lost_report = transition_lost_report(&mut tx, &principal, lost_report_id, &["found"], "reunited").await?;
+
+    sqlx::query(r#"INSERT INTO dog_events (dog_id, event_type, payload) VALUES ($1, $2, $3)"#)
+        .bind(lost_report.dog_id)
+        .bind(DOG_REUNITED_EVENT)
+        .bind(json!({"lost_report_id": lost_report.id}))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Envelope::success(LostReportData { lost_report }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/lostreports/{id}/sightings",
+    tag = "lost-found",
+    params(("id" = uuid::Uuid, Path, description = "Lost report ID")),
+    request_body = CreateSightingSchema,
+    responses(
+        (status = 201, description = "Sighting reported", body = Envelope<SightingData>),
+        (status = 404, description = "No open lost report with that ID", body = ErrorBody),
+        (status = 422, description = "Body failed validation", body = ErrorBody),
+    )
+)]
+pub async fn create_sighting_handler(
+    Path(lost_report_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    actor: Actor,
+    ValidatedJson(body): ValidatedJson<CreateSightingSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let query_result = sqlx::query_as::<_, SightingModel>(
+        r#"INSERT INTO sightings (lost_report_id, location, latitude, longitude, note)
+        SELECT id, $2, $3, $4, $5 FROM lost_reports WHERE id = $1 AND status = 'lost'
+        RETURNING *"#
+    )
+    .bind(lost_report_id)
+    .bind(body.location)
+    .bind(body.latitude)
+    .bind(body.longitude)
+    .bind(body.note)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    match query_result {
+        Some(sighting) => {
+            record_audit(&mut tx, AuditEntry {
+                actor: actor.as_str(),
+                entity_type: SIGHTING_ENTITY,
+                entity_id: sighting.id,
+                action: ACTION_CREATE,
+                before: None,
+                after: snapshot(&sighting),
+            })
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
+
+            Ok((StatusCode::CREATED, Envelope::success(SightingData { sighting })))
+        }
+        None => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": format!("No open lost report with ID: {}", lost_report_id)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+    }
+}
+
+/// The finder's chip/photo scan for a sighting, submitted by the report's owner or the
+/// operator. A sign-in proof is generated from the scan and verified against the commitment
+/// stored at sign-up; only a verified match marks the dog as found. The outcome is sent to the
+/// owner's webhooks alone; reports the operator opened have no owner to notify.
+#[utoipa::path(
+    post,
+    path = "/api/sightings/{id}/match",
+    tag = "lost-found",
+    params(("id" = uuid::Uuid, Path, description = "Sighting ID")),
+    request_body = SightingMatchSchema,
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The scan matched; the dog is marked found", body = Envelope<SightingMatchData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "Neither the report's owner nor the operator", body = ErrorBody),
+        (status = 404, description = "No such sighting", body = ErrorBody),
+        (status = 409, description = "The sighting or its lost report is no longer open", body = ErrorBody),
+        (status = 422, description = "The scan does not match, or the dog has no commitment", body = ErrorBody),
+    )
+)]
+pub async fn match_sighting_handler(
+    Path(sighting_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+    ValidatedJson(body): ValidatedJson<SightingMatchSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let actor = Actor::principal(&principal);
+    let sighting = sqlx::query_as::<_, SightingModel>(r#"SELECT * FROM sightings WHERE id = $1"#)
+        .bind(sighting_id)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let Some(sighting) = sighting else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Sighting with ID: {} not found", sighting_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    if sighting.status != "reported" {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Sighting with ID: {} has already been {}", sighting_id, sighting.status)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let lost_report = sqlx::query_as::<_, LostReportModel>(r#"SELECT * FROM lost_reports WHERE id = $1"#)
+        .bind(sighting.lost_report_id)
+        .fetch_one(&data.db)
+        .await
+        .map_err(internal_error)?;
+    authorize_owner(&principal, &lost_report)?;
+
+    if lost_report.status != "lost" {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Lost report with ID: {} is '{}'", lost_report.id, lost_report.status)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let embeddinghash_num_arr = match hash_to_array(&body.embedding_hash) {
+        Ok(arr) => arr,
+        Err(e) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": e
+            });
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+    let microchipid_num_arr = body.microchip_id.to_be_bytes();
+
+    let commitments = sqlx::query_as::<_, ZkpModel>(r#"SELECT * FROM zkptable WHERE dog_id = $1 AND submission_status = 'submitted'"#)
+        .bind(lost_report.dog_id)
+        .fetch_all(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let commitments: Vec<Vec<_>> = commitments
+        .iter()
+        .filter_map(|zkp| zkp.public_input.iter().map(|s| scalar_from_hex(s)).collect::<Option<Vec<_>>>())
+        .collect();
+
+    if commitments.is_empty() {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Dog with ID: {} has no stored ZKP commitment", lost_report.dog_id)
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+
+    // Proof generation takes seconds of CPU; keep it off the async workers.
+    let job = ProofJob::start(&data.db, "sighting_match", Some(lost_report.dog_id)).await;
+    let verified = tokio::task::spawn_blocking(move || {
+        let (proof, vk) = zkpproof_sign_in(embeddinghash_num_arr, microchipid_num_arr);
+        commitments.iter().any(|public_input| zkpproof_verify(&proof, &vk, public_input))
+    })
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error","message": format!("{:?}", e)})),
+        )
+    })?;
+    job.finish(verified).await;
+
+    if !verified {
+        let mut tx = data.db.begin().await.map_err(internal_error)?;
+        let rejected = sqlx::query_as::<_, SightingModel>(
+            r#"UPDATE sightings SET status = 'rejected' WHERE id = $1 AND status = 'reported' RETURNING *"#
+        )
+        .bind(sighting_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+        if let Some(rejected) = rejected {
+            record_audit(&mut tx, AuditEntry {
+                actor: actor.as_str(),
+                entity_type: SIGHTING_ENTITY,
+                entity_id: rejected.id,
+                action: ACTION_UPDATE,
+                before: snapshot(&sighting),
+                after: snapshot(&rejected),
+            })
+            .await
+            .map_err(internal_error)?;
+        }
+        if let Some(owner) = lost_report.owner_merchant_id {
+            enqueue(&mut tx, OutboxEvent {
+                event_type: EVENT_ZKP_SIGNIN_FAILED,
+                merchant_id: Some(owner),
+                payload: json!({"dog_id": lost_report.dog_id, "source": "sighting_match", "sighting_id": sighting_id}),
+            })
+            .await
+            .map_err(internal_error)?;
+        }
+        tx.commit().await.map_err(internal_error)?;
+
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "Scan does not match the dog's stored commitment"
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let reported_sighting = sighting;
+    let sighting = sqlx::query_as::<_, SightingModel>(
+        r#"UPDATE sightings SET status = 'verified', verified_at = now() WHERE id = $1 AND status = 'reported' RETURNING *"#
+    )
+    .bind(sighting_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let lost_report_before = lost_report;
+    let lost_report = sqlx::query_as::<_, LostReportModel>(
+        r#"UPDATE lost_reports SET status = 'found' WHERE id = $1 AND status = 'lost' RETURNING *"#
+    )
+    .bind(lost_report_before.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let (Some(sighting), Some(lost_report)) = (sighting, lost_report) else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "The lost report or sighting changed while the scan was being verified"
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    };
+
+    let dog_before = sqlx::query_as::<_, DogModel>(r#"SELECT * FROM dogs WHERE id = $1 FOR UPDATE"#)
+        .bind(lost_report.dog_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    // The dog's location now reflects where it was actually found.
+    let dog = sqlx::query_as::<_, DogModel>(
+        r#"UPDATE dogs SET location = COALESCE($1, location), latitude = COALESCE($2, latitude), longitude = COALESCE($3, longitude) WHERE id = $4 RETURNING *"#
+    )
+    .bind(&sighting.location)
+    .bind(sighting.latitude)
+    .bind(sighting.longitude)
+    .bind(lost_report.dog_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    for entry in [
+        AuditEntry {
+            actor: actor.as_str(),
+            entity_type: SIGHTING_ENTITY,
+            entity_id: sighting.id,
+            action: ACTION_UPDATE,
+            before: snapshot(&reported_sighting),
+            after: snapshot(&sighting),
+        },
+        AuditEntry {
+            actor: actor.as_str(),
+            entity_type: LOST_REPORT_ENTITY,
+            entity_id: lost_report.id,
+            action: ACTION_UPDATE,
+            before: snapshot(&lost_report_before),
+            after: snapshot(&lost_report),
+        },
+        AuditEntry {
+            actor: actor.as_str(),
+            entity_type: DOG_ENTITY,
+            entity_id: dog.id,
+            action: ACTION_UPDATE,
+            before: snapshot(&dog_before),
+            after: snapshot(&dog),
+        },
+    ] {
+        record_audit(&mut tx, entry).await.map_err(internal_error)?;
+    }
+
+    sqlx::query(r#"INSERT INTO dog_events (dog_id, event_type, payload) VALUES ($1, $2, $3)"#)
+        .bind(lost_report.dog_id)
+        .bind(DOG_FOUND_EVENT)
+        .bind(json!({
+            "lost_report_id": lost_report.id,
+            "sighting_id": sighting.id,
+            "location": sighting.location,
+            "latitude": sighting.latitude,
+            "longitude": sighting.longitude
+        }))
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    if let Some(owner) = lost_report.owner_merchant_id {
+        enqueue(&mut tx, OutboxEvent {
+            event_type: EVENT_ZKP_SIGNIN_SUCCEEDED,
+            merchant_id: Some(owner),
+            payload: json!({"dog_id": lost_report.dog_id, "source": "sighting_match", "sighting_id": sighting.id}),
+        })
+        .await
+        .map_err(internal_error)?;
+    }
+
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Envelope::success(SightingMatchData {
+        verified: true,
+        lost_report,
+        sighting,
+    }))
+}
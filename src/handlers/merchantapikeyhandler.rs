@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::PgConnection;
+
+use crate::{
+    audit::{record_audit, snapshot, AuditEntry, Audited, ACTION_CREATE, ACTION_DELETE},
+    auth::{api_key_prefix, generate_api_key, hash_api_key, Actor, Principal},
+    model::MerchantApiKeyModel,
+    response::{Envelope, ErrorBody, MerchantApiKeyData, MerchantApiKeyList, Status},
+    schema::{CreateMerchantApiKeySchema, DEFAULT_SCOPES, SCOPE_ADMIN},
+    validation::validate,
+    AppState,
+};
+
+pub const MERCHANT_API_KEY_ENTITY: &str = "merchant_api_key";
+
+#[utoipa::path(
+    post,
+    path = "/api/merchants/{id}/apikeys",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID")),
+    request_body(content = Option<CreateMerchantApiKeySchema>, description = "Scopes default to every scope but admin"),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "Key issued; `key` is shown only this once", body = Envelope<MerchantApiKeyData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+        (status = 404, description = "No such merchant", body = ErrorBody),
+        (status = 422, description = "Unknown or empty scopes", body = ErrorBody),
+    )
+)]
+pub async fn create_merchant_api_key_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+    body: Option<Json<CreateMerchantApiKeySchema>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(merchant_id, SCOPE_ADMIN)?;
+    let Json(body) = body.unwrap_or_default();
+    validate(&body)?;
+    let scopes = body
+        .scopes
+        .unwrap_or_else(|| DEFAULT_SCOPES.iter().map(|s| s.to_string()).collect());
+
+    let actor = Actor::principal(&principal);
+
+    let key = generate_api_key();
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let query_result = sqlx::query_as::<_, MerchantApiKeyModel>(
+        r#"INSERT INTO merchant_api_keys (merchant_id, key_prefix, key_hash, scopes) VALUES ($1, $2, $3, $4) RETURNING *"#
+    )
+    .bind(merchant_id)
+    .bind(api_key_prefix(&key))
+    .bind(hash_api_key(&key))
+    .bind(scopes)
+    .fetch_one(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(api_key) => {
+            record_audit(&mut tx, AuditEntry {
+                actor: actor.as_str(),
+                entity_type: MERCHANT_API_KEY_ENTITY,
+                entity_id: api_key.id,
+                action: ACTION_CREATE,
+                before: None,
+                after: snapshot(&api_key),
+            })
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
+
+            Ok((StatusCode::CREATED, Envelope::success(MerchantApiKeyData { api_key, key })))
+        }
+        Err(e) => {
+            if e.to_string().contains("violates foreign key constraint") {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": format!("Merchant with ID: {} not found", merchant_id)
+                });
+                return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"status": "error","message": format!("{:?}", e)})),
+            ))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/merchants/{id}/apikeys",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID")),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The merchant's API keys, without their secrets", body = MerchantApiKeyList),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+    )
+)]
+pub async fn merchant_api_key_list_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(merchant_id, SCOPE_ADMIN)?;
+
+    let query_result = sqlx::query_as::<_, MerchantApiKeyModel>(
+        r#"SELECT * FROM merchant_api_keys WHERE merchant_id = $1 ORDER by created_at"#
+    )
+    .bind(merchant_id)
+    .fetch_all(&data.db)
+    .await;
+
+    if query_result.is_err() {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "Something bad happened while fetching API keys",
+        });
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    }
+
+    let api_keys = query_result.unwrap();
+
+    Ok(Json(MerchantApiKeyList {
+        status: Status::Success,
+        results: api_keys.len(),
+        api_keys,
+    }))
+}
+
+/// Issues a replacement key with the same scopes and revokes the old one in a single transaction.
+#[utoipa::path(
+    post,
+    path = "/api/merchants/{id}/apikeys/{key_id}/rotate",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID"), ("key_id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "Replacement key issued; `key` is shown only this once", body = Envelope<MerchantApiKeyData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+        (status = 404, description = "No active API key with that ID", body = ErrorBody),
+    )
+)]
+pub async fn rotate_merchant_api_key_handler(
+    Path((merchant_id, key_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(merchant_id, SCOPE_ADMIN)?;
+    let actor = Actor::principal(&principal);
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let old_key = revoke_key(&mut tx, merchant_id, key_id).await.map_err(internal_error)?;
+
+    let Some(Audited { row: old_key, before }) = old_key else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Active API key with ID: {} not found", key_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let key = generate_api_key();
+    let api_key = sqlx::query_as::<_, MerchantApiKeyModel>(
+        r#"INSERT INTO merchant_api_keys (merchant_id, key_prefix, key_hash, scopes, rotated_from) VALUES ($1, $2, $3, $4, $5) RETURNING *"#
+    )
+    .bind(merchant_id)
+    .bind(api_key_prefix(&key))
+    .bind(hash_api_key(&key))
+    .bind(&old_key.scopes)
+    .bind(old_key.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: MERCHANT_API_KEY_ENTITY,
+        entity_id: old_key.id,
+        action: ACTION_DELETE,
+        before,
+        after: snapshot(&old_key),
+    })
+    .await
+    .map_err(internal_error)?;
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: MERCHANT_API_KEY_ENTITY,
+        entity_id: api_key.id,
+        action: ACTION_CREATE,
+        before: None,
+        after: snapshot(&api_key),
+    })
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Envelope::success(MerchantApiKeyData { api_key, key })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/merchants/{id}/apikeys/{key_id}",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID"), ("key_id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 204, description = "API key revoked"),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+        (status = 404, description = "No active API key with that ID", body = ErrorBody),
+    )
+)]
+pub async fn revoke_merchant_api_key_handler(
+    Path((merchant_id, key_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(merchant_id, SCOPE_ADMIN)?;
+    let actor = Actor::principal(&principal);
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let revoked = revoke_key(&mut tx, merchant_id, key_id).await.map_err(internal_error)?;
+
+    let Some(Audited { row: revoked, before }) = revoked else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Active API key with ID: {} not found", key_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: MERCHANT_API_KEY_ENTITY,
+        entity_id: revoked.id,
+        action: ACTION_DELETE,
+        before,
+        after: snapshot(&revoked),
+    })
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revokes one of the merchant's active keys, returning it with the key as it was before.
+async fn revoke_key(
+    conn: &mut PgConnection,
+    merchant_id: uuid::Uuid,
+    key_id: uuid::Uuid,
+) -> Result<Option<Audited<MerchantApiKeyModel>>, sqlx::Error> {
+    sqlx::query_as::<_, Audited<MerchantApiKeyModel>>(
+        r#"UPDATE merchant_api_keys SET revoked_at = now()
+        FROM (SELECT * FROM merchant_api_keys WHERE id = $1 FOR UPDATE) AS prev
+        WHERE merchant_api_keys.id = $1 AND prev.id = merchant_api_keys.id
+            AND merchant_api_keys.merchant_id = $2 AND merchant_api_keys.revoked_at IS NULL
+        RETURNING merchant_api_keys.*, to_jsonb(prev) - 'key_hash' AS before"#
+    )
+    .bind(key_id)
+    .bind(merchant_id)
+    .fetch_optional(conn)
+    .await
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
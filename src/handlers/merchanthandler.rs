@@ -1,35 +1,58 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{OriginalUri, Path, State},
+    http::{header::ETAG, StatusCode},
     response::IntoResponse,
     Json,
 };
 use serde_json::json;
 
 use crate::{
+    audit::{record_audit, snapshot, Audited, AuditEntry, ACTION_CREATE, ACTION_DELETE, ACTION_RESTORE, ACTION_UPDATE},
+    auth::{Actor, Principal},
+    etag::{etag, precondition_failed, IfMatch},
+    patch::{apply_json_patch, PatchBody},
     model::MerchantModel,
-    schema::{CreateMerchantSchema, FilterOptions, UpdateMerchantSchema},
+    openapi::IfMatchHeader,
+    pagination::{Keyset, KeysetRow},
+    response::{Envelope, ErrorBody, MerchantData, MerchantList, Status},
+    schema::{CreateMerchantSchema, FilterOptions, UpdateMerchantSchema, SCOPE_ADMIN},
+    validation::{ValidatedJson, ValidatedQuery},
     AppState,
 };
 
+pub const MERCHANT_ENTITY: &str = "merchant";
 
+#[utoipa::path(
+    get,
+    path = "/api/merchants",
+    tag = "merchants",
+    params(FilterOptions),
+    responses(
+        (status = 200, description = "One page of merchants, oldest first", body = MerchantList),
+        (status = 400, description = "Invalid cursor", body = ErrorBody),
+        (status = 422, description = "A parameter failed validation", body = ErrorBody),
+    )
+)]
 pub async fn merchant_list_handler(
-    opts: Option<Query<FilterOptions>>, State(data): State<Arc<AppState>>
+    uri: OriginalUri,
+    ValidatedQuery(opts): ValidatedQuery<FilterOptions>, State(data): State<Arc<AppState>>
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let Query(opts) = opts.unwrap_or_default();
     
-    let limit = opts.limit.unwrap_or(10);
-    let offset = (opts.page.unwrap_or(1) - 1) * limit;
-    let query_result = sqlx::query_as::<_, MerchantModel>(
-        r#"SELECT * FROM merchants ORDER by id LIMIT $1 OFFSET $2"#
-    )
-    .bind(limit as i32)
-    .bind(offset as i32)
+    let keyset = Keyset::timestamp("created_at", "timestamp", opts.cursor.as_deref(), opts.limit)?;
+    let sql = format!(
+        "SELECT *, {} FROM merchants WHERE deleted_at IS NULL AND {} ORDER BY {} LIMIT $3",
+        keyset.select_columns(),
+        keyset.predicate(1),
+        keyset.order_by()
+    );
+    let query_result = sqlx::query_as::<_, KeysetRow<MerchantModel>>(&sql)
+    .bind(keyset.cursor_key())
+    .bind(keyset.cursor_id())
+    .bind(keyset.fetch_limit())
     .fetch_all(&data.db)
     .await;
-    println!("result==========> {:?}", query_result);
     if query_result.is_err() {
         let error_response = serde_json::json!({
             "status": "fail",
@@ -38,34 +61,57 @@ pub async fn merchant_list_handler(
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
 
-    let merchants = query_result.unwrap();
+    let page = keyset.page(query_result.unwrap());
 
-    let json_response = serde_json::json!({
-        "status": "success",
-        "results": merchants.len(),
-        "merchants": merchants
-    });
-    Ok(Json(json_response))
+    Ok(Json(MerchantList {
+        status: Status::Success,
+        results: page.items.len(),
+        links: page.links(&uri),
+        cursors: page.cursors(),
+        merchants: page.items,
+    }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/merchants/",
+    tag = "merchants",
+    request_body = CreateMerchantSchema,
+    responses(
+        (status = 201, description = "Merchant created", body = Envelope<MerchantData>, headers(("ETag" = String))),
+        (status = 409, description = "Duplicate merchant", body = ErrorBody),
+        (status = 422, description = "Body failed validation", body = ErrorBody),
+    )
+)]
 pub async fn create_merchant_handler(
     State(data): State<Arc<AppState>>,
-    Json(body): Json<CreateMerchantSchema>,
+    actor: Actor,
+    ValidatedJson(body): ValidatedJson<CreateMerchantSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
     let query_result = sqlx::query_as::<_, MerchantModel>(
         r#"INSERT INTO merchants (last_data_hash) VALUES ($1) RETURNING *"#
     )
     .bind(body.last_data_hash)
-    .fetch_one(&data.db)
+    .fetch_one(&mut *tx)
     .await;
 
     match query_result {
         Ok(merchant) => {
-            let merchant_response = json!({"status": "success","data": json!({
-                "merchant": merchant
-            })});
+            record_audit(&mut tx, AuditEntry {
+                actor: actor.as_str(),
+                entity_type: MERCHANT_ENTITY,
+                entity_id: merchant.id,
+                action: ACTION_CREATE,
+                before: None,
+                after: snapshot(&merchant),
+            })
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
 
-            return Ok((StatusCode::CREATED, Json(merchant_response)));
+            let merchant_etag = etag(merchant.version);
+            Ok((StatusCode::CREATED, [(ETAG, merchant_etag)], Envelope::success(MerchantData { merchant })))
         }
         Err(e) => {
             if e.to_string()
@@ -77,108 +123,260 @@ pub async fn create_merchant_handler(
                 });
                 return Err((StatusCode::CONFLICT, Json(error_response)));
             }
-            return Err((
+            Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({"status": "error","message": format!("{:?}", e)})),
-            ));
+            ))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/merchants/{id}",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path)),
+    responses(
+        (status = 200, description = "The merchant", body = Envelope<MerchantData>, headers(("ETag" = String))),
+        (status = 404, description = "No such merchant, or it was deleted", body = ErrorBody),
+    )
+)]
 pub async fn get_merchant_handler(
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = sqlx::query_as::<_, MerchantModel>( r#"SELECT * FROM merchants WHERE id = $1"#)
+    let query_result = sqlx::query_as::<_, MerchantModel>( r#"SELECT * FROM merchants WHERE id = $1 AND deleted_at IS NULL"#)
         .bind(id)
         .fetch_one(&data.db)
         .await;
 
     match query_result {
         Ok(merchant) => {
-            let merchant_response = serde_json::json!({"status": "success","data": serde_json::json!({
-                "merchant": merchant
-            })});
-
-            return Ok(Json(merchant_response));
+            let merchant_etag = etag(merchant.version);
+            Ok(([(ETAG, merchant_etag)], Envelope::success(MerchantData { merchant })))
         }
         Err(_) => {
             let error_response = serde_json::json!({
                 "status": "fail",
                 "message": format!("Merchant with ID: {} not found", id)
             });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
         }
     }
 }
 
 
+/// Accepts a merge patch or a JSON Patch like `edit_dog_handler`, applied in one conditional
+/// UPDATE so an edit based on a stale read fails with 412.
+#[utoipa::path(
+    patch,
+    path = "/api/merchants/{id}",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path), IfMatchHeader),
+    request_body(
+        content(
+            (UpdateMerchantSchema = "application/merge-patch+json"),
+            (UpdateMerchantSchema = "application/json"),
+            (Vec<serde_json::Value> = "application/json-patch+json"),
+        )
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The updated merchant", body = Envelope<MerchantData>, headers(("ETag" = String))),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+        (status = 404, description = "No such merchant", body = ErrorBody),
+        (status = 412, description = "The merchant changed since the If-Match ETag was read", body = ErrorBody),
+        (status = 415, description = "Not a merge patch or JSON Patch", body = ErrorBody),
+        (status = 422, description = "Patch failed to apply or validate", body = ErrorBody),
+        (status = 428, description = "If-Match header missing", body = ErrorBody),
+    )
+)]
 pub async fn edit_merchant_handler(
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
-    Json(body): Json<UpdateMerchantSchema>,
+    principal: Principal,
+    if_match: IfMatch,
+    body: PatchBody<UpdateMerchantSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = sqlx::query_as::<_, MerchantModel>(r#"SELECT * FROM merchants WHERE id = $1"#)
-        .bind(id)
-        .fetch_one(&data.db)
-        .await;
+    principal.authorize(id, SCOPE_ADMIN)?;
+    let actor = Actor::principal(&principal);
+    let mut expected_version = if_match.version();
+    let body = match body {
+        PatchBody::Merge(body) => body,
+        PatchBody::JsonPatch(operations) => {
+            let merchant = sqlx::query_as::<_, MerchantModel>(r#"SELECT * FROM merchants WHERE id = $1 AND deleted_at IS NULL"#)
+                .bind(id)
+                .fetch_optional(&data.db)
+                .await
+                .map_err(internal_error)?;
+            let Some(merchant) = merchant else {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": format!("Merchant with ID: {} not found", id)
+                });
+                return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            };
+            expected_version = expected_version.or(Some(merchant.version));
+            apply_json_patch(json!({"last_data_hash": merchant.last_data_hash}), &operations)?
+        }
+    };
+    let last_data_hash = body.last_data_hash.required("last_data_hash")?;
 
-    if query_result.is_err() {
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let query_result = sqlx::query_as::<_, Audited<MerchantModel>>(
+        r#"UPDATE merchants SET last_data_hash = COALESCE($1, merchants.last_data_hash), last_updated = now()
+        FROM (SELECT * FROM merchants WHERE id = $2 FOR UPDATE) AS prev
+        WHERE merchants.id = $2 AND prev.id = merchants.id AND merchants.deleted_at IS NULL
+            AND ($3::bigint IS NULL OR merchants.version = $3)
+        RETURNING merchants.*, to_jsonb(prev) AS before"#
+    )
+    .bind(last_data_hash)
+    .bind(id)
+    .bind(expected_version)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(Audited { row: merchant, before }) = query_result else {
+        let exists = sqlx::query_scalar::<_, i64>(r#"SELECT version FROM merchants WHERE id = $1 AND deleted_at IS NULL"#)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+        if exists.is_some() {
+            return Err(precondition_failed("Merchant", id));
+        }
         let error_response = serde_json::json!({
             "status": "fail",
             "message": format!("Merchant with ID: {} not found", id)
         });
         return Err((StatusCode::NOT_FOUND, Json(error_response)));
-    }
+    };
 
-    let now = chrono::Utc::now();
-    let merchant: MerchantModel = query_result.unwrap();
-
-    let query_result = sqlx::query_as::<_, MerchantModel>(
-        r#"UPDATE merchants SET last_data_hash = $1 last_updated = $2 WHERE id = $3 RETURNING *"#
-    )
-    .bind(body.last_data_hash.to_owned().unwrap_or(merchant.last_data_hash))
-    .bind(now)
-    .bind(id)
-    .fetch_one(&data.db)
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: MERCHANT_ENTITY,
+        entity_id: merchant.id,
+        action: ACTION_UPDATE,
+        before,
+        after: snapshot(&merchant),
+    })
     .await
-    ;
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
 
-    match query_result {
-        Ok(merchant) => {
-            let merchant_response = serde_json::json!({"status": "success","data": serde_json::json!({
-                "merchant": merchant
-            })});
-
-            return Ok(Json(merchant_response));
-        }
-        Err(err) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"status": "error","message": format!("{:?}", err)})),
-            ));
-        }
-    }
+    let merchant_etag = etag(merchant.version);
+    Ok(([(ETAG, merchant_etag)], Envelope::success(MerchantData { merchant })))
 }
 
+/// Soft delete: the row stays so merchant records and keys keep a valid `merchant_id`.
+#[utoipa::path(
+    delete,
+    path = "/api/merchants/{id}",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 204, description = "Merchant deleted"),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+        (status = 404, description = "No such merchant, or it is already deleted", body = ErrorBody),
+    )
+)]
 pub async fn delete_merchant_handler(
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
+    principal: Principal,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let rows_affected = sqlx::query(r#"DELETE FROM merchants  WHERE id = $1"#)
+    principal.authorize(id, SCOPE_ADMIN)?;
+    let actor = Actor::principal(&principal);
+    set_merchant_deleted(&data, id, &actor, true).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/merchants/{id}/restore",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The restored merchant", body = Envelope<MerchantData>, headers(("ETag" = String))),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "Not the operator; a deleted merchant's own keys no longer authenticate", body = ErrorBody),
+        (status = 404, description = "No deleted merchant with that ID", body = ErrorBody),
+    )
+)]
+pub async fn restore_merchant_handler(
+    Path(id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(id, SCOPE_ADMIN)?;
+    let actor = Actor::principal(&principal);
+    let merchant = set_merchant_deleted(&data, id, &actor, false).await?;
+
+    let merchant_etag = etag(merchant.version);
+    Ok(([(ETAG, merchant_etag)], Envelope::success(MerchantData { merchant })))
+}
+
+async fn set_merchant_deleted(
+    data: &AppState,
+    id: uuid::Uuid,
+    actor: &Actor,
+    deleted: bool,
+) -> Result<MerchantModel, (StatusCode, Json<serde_json::Value>)> {
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let before = sqlx::query_as::<_, MerchantModel>(
+        r#"SELECT * FROM merchants WHERE id = $1 AND (deleted_at IS NULL) = $2 FOR UPDATE"#
+    )
     .bind(id)
-        .execute(&data.db)
-        .await
-        .unwrap()
-        .rows_affected();
+    .bind(deleted)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
 
-    if rows_affected == 0 {
+    let Some(before) = before else {
         let error_response = serde_json::json!({
             "status": "fail",
-            "message": format!("Merchant with ID: {} not found", id)
+            "message": if deleted {
+                format!("Merchant with ID: {} not found", id)
+            } else {
+                format!("Deleted merchant with ID: {} not found", id)
+            }
         });
         return Err((StatusCode::NOT_FOUND, Json(error_response)));
-    }
+    };
 
-    Ok(StatusCode::NO_CONTENT)
+    let merchant = sqlx::query_as::<_, MerchantModel>(
+        r#"UPDATE merchants SET deleted_at = CASE WHEN $2 THEN now() ELSE NULL END WHERE id = $1 RETURNING *"#
+    )
+    .bind(id)
+    .bind(deleted)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: MERCHANT_ENTITY,
+        entity_id: id,
+        action: if deleted { ACTION_DELETE } else { ACTION_RESTORE },
+        before: snapshot(&before),
+        after: snapshot(&merchant),
+    })
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(merchant)
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
 }
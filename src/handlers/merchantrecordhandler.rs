@@ -1,77 +1,359 @@
 use std::sync::Arc;
-use chrono::Utc;
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, SubsecRound, Utc};
 use axum::{
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
+use futures::StreamExt;
+use metrics::counter;
+use serde::Serialize;
 use serde_json::json;
 
 use crate::{
-    model::MerchantRecordModel,
-    schema::{CreateMerchantRecordSchema, FilterOptions},
+    audit::{record_audit, snapshot, AuditEntry, ACTION_CREATE, ACTION_DELETE},
+    auth::{Actor, MerchantAuth, Principal},
+    bulk::export_response,
+    canonical::{merchant_record_hash, to_canonical_json},
+    handlers::merchantrecordtypehandler::validate_record_payload,
+    model::{MerchantRecordModel, MerchantSigningKeyModel},
+    openapi::IdempotencyKeyHeader,
+    outbox::{enqueue, OutboxEvent},
+    pagination::{Keyset, KeysetRow},
+    response::{Envelope, ErrorBody, MerchantRecordData, MerchantRecordList, MerchantRecordStatusData, Status},
+    schema::{CreateMerchantRecordSchema, ExportOptions, MerchantRecordFilterOptions, RecordStatus, RecordStatusFilter, RenewMerchantRecordSchema, EVENT_MERCHANT_RECORD_CREATED, SCOPE_RECORDS_READ, SCOPE_RECORDS_WRITE},
+    signing::{verify_signature, SignedMerchantRecord, MERCHANT_RECORD_FORMAT_VERSION},
+    validation::{ValidatedJson, ValidatedQuery},
     AppState,
 };
 
-pub async fn create_merchant_record_handler(
-    State(data): State<Arc<AppState>>,
-    Json(body): Json<CreateMerchantRecordSchema>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let valid_until = Utc::now().naive_utc() + chrono::Duration::days( body.valid_until);
+pub const MERCHANT_RECORD_ENTITY: &str = "merchant_record";
+
+/// A record about to be appended to a merchant's hash chain.
+struct NewMerchantRecord {
+    id: uuid::Uuid,
+    merchant_id: uuid::Uuid,
+    record_type: String,
+    data_record: serde_json::Value,
+    prev_data_hash: String,
+    valid_from: DateTime<Utc>,
+    valid_until: DateTime<Utc>,
+    supersedes: Option<uuid::Uuid>,
+    signing_key_id: uuid::Uuid,
+    signature: String,
+}
+
+/// Validates, verifies and appends `record` to its merchant's chain, advancing `last_data_hash`.
+async fn append_merchant_record(
+    data: &AppState,
+    actor: &Actor,
+    record: NewMerchantRecord,
+) -> Result<MerchantRecordModel, (StatusCode, Json<serde_json::Value>)> {
+    if record.valid_until <= record.valid_from {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "valid_until must be later than valid_from"
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+
+    let signing_key = sqlx::query_as::<_, MerchantSigningKeyModel>(
+        r#"SELECT * FROM merchant_signing_keys WHERE id = $1 AND merchant_id = $2 AND revoked_at IS NULL"#
+    )
+    .bind(record.signing_key_id)
+    .bind(record.merchant_id)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(signing_key) = signing_key else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Active signing key with ID: {} not found for merchant {}", record.signing_key_id, record.merchant_id)
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    };
+
+    validate_record_payload(&data.db, record.merchant_id, &record.record_type, &record.data_record).await?;
+
+    let canonical_data = to_canonical_json(&record.data_record);
+    let signature = record.signature.trim().to_lowercase();
+    let canonical_bytes = SignedMerchantRecord {
+        id: &record.id,
+        merchant_id: &record.merchant_id,
+        record_type: &record.record_type,
+        prev_data_hash: &record.prev_data_hash,
+        data_record: &canonical_data,
+        valid_from: &record.valid_from,
+        valid_until: &record.valid_until,
+        supersedes: record.supersedes.as_ref(),
+    }
+    .canonical_bytes();
+    if let Err(e) = verify_signature(&signing_key.public_key, &canonical_bytes, &signature) {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": e
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    // Lock the merchant row so concurrent inserts cannot fork the hash chain.
+    let chain_head = sqlx::query_scalar::<_, String>(r#"SELECT last_data_hash FROM merchants WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"#)
+        .bind(record.merchant_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    let Some(chain_head) = chain_head else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Merchant with ID: {} not found", record.merchant_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    if chain_head != record.prev_data_hash {
+        counter!("merchant_record_chain_conflicts_total").increment(1);
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "prev_data_hash does not match the merchant's current chain head",
+            "last_data_hash": chain_head
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let data_hash = merchant_record_hash(&record.prev_data_hash, &record.record_type, &canonical_data);
     let query_result = sqlx::query_as::<_, MerchantRecordModel>(
-        r#"INSERT INTO merchantsrecord (merchant_id, valid_until, prev_data_hash, data_record) VALUES ($1, $2, $3, $4) RETURNING *"#
+        r#"INSERT INTO merchantsrecord (id, merchant_id, valid_from, valid_until, prev_data_hash, record_type, data_record, data_hash, signing_key_id, signature, supersedes, format_version) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) RETURNING *"#
     )
-    .bind(body.merchant_id)
-    .bind(valid_until)
-    .bind(body.prev_data_hash)
-    .bind(body.data_record)
-    .fetch_one(&data.db)
+    .bind(record.id)
+    .bind(record.merchant_id)
+    .bind(record.valid_from)
+    .bind(record.valid_until)
+    .bind(&record.prev_data_hash)
+    .bind(&record.record_type)
+    .bind(&record.data_record)
+    .bind(&data_hash)
+    .bind(signing_key.id)
+    .bind(signature)
+    .bind(record.supersedes)
+    .bind(MERCHANT_RECORD_FORMAT_VERSION)
+    .fetch_one(&mut *tx)
     .await;
 
-    match query_result {
-        Ok(merchant_record) => {
-            let merchant_record_response = json!({"status": "success","data": json!({
-                "merchant_record": merchant_record
-            })});
-
-            return Ok((StatusCode::CREATED, Json(merchant_record_response)));
-        }
+    let merchant_record = match query_result {
+        Ok(merchant_record) => merchant_record,
         Err(e) => {
             if e.to_string()
                 .contains("duplicate key value violates unique constraint")
             {
+                let message = if e.to_string().contains("merchantsrecord_supersedes_idx") {
+                    "Merchant record has already been renewed"
+                } else {
+                    "Merchant record with that ID already exists"
+                };
                 let error_response = serde_json::json!({
                     "status": "fail",
-                    "message": "Merchant with that title already exists",
+                    "message": message,
                 });
                 return Err((StatusCode::CONFLICT, Json(error_response)));
             }
+            return Err(internal_error(e));
+        }
+    };
+
+    sqlx::query(r#"UPDATE merchants SET last_data_hash = $1 WHERE id = $2"#)
+        .bind(&data_hash)
+        .bind(record.merchant_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: MERCHANT_RECORD_ENTITY,
+        entity_id: merchant_record.id,
+        action: ACTION_CREATE,
+        before: None,
+        after: snapshot(&merchant_record),
+    })
+    .await
+    .map_err(internal_error)?;
+    enqueue(&mut tx, OutboxEvent {
+        event_type: EVENT_MERCHANT_RECORD_CREATED,
+        merchant_id: Some(merchant_record.merchant_id),
+        payload: json!({"merchant_record": &merchant_record}),
+    })
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    // Not labelled by merchant: one series per merchant would grow without bound. Per-merchant
+    // chain heads are read from `merchants` at scrape time instead.
+    counter!("merchant_record_chain_appends_total").increment(1);
+
+    Ok(merchant_record)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/merchantrecords/",
+    tag = "merchant-records",
+    params(IdempotencyKeyHeader),
+    request_body = CreateMerchantRecordSchema,
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "Record appended to the merchant's chain", body = Envelope<MerchantRecordData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks records:write", body = ErrorBody),
+        (status = 404, description = "No such merchant", body = ErrorBody),
+        (status = 409, description = "prev_data_hash is stale, or the Idempotency-Key is still in use", body = ErrorBody),
+        (status = 422, description = "Body, payload schema or signature failed validation", body = ErrorBody),
+    )
+)]
+pub async fn create_merchant_record_handler(
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+    ValidatedJson(body): ValidatedJson<CreateMerchantRecordSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(body.merchant_id, SCOPE_RECORDS_WRITE)?;
+
+    let merchant_record = append_merchant_record(&data, &Actor::merchant(&auth), NewMerchantRecord {
+        id: body.id,
+        merchant_id: body.merchant_id,
+        record_type: body.record_type,
+        data_record: body.data_record,
+        prev_data_hash: body.prev_data_hash,
+        valid_from: body.valid_from.trunc_subsecs(6),
+        valid_until: body.valid_until.trunc_subsecs(6),
+        supersedes: None,
+        signing_key_id: body.signing_key_id,
+        signature: body.signature,
+    })
+    .await?;
+
+    Ok((StatusCode::CREATED, Envelope::success(MerchantRecordData { merchant_record })))
+}
+
+/// Appends a new record carrying the old record's payload and a fresh validity window.
+/// The old record is left untouched; the new one points back to it through `supersedes`.
+#[utoipa::path(
+    post,
+    path = "/api/merchantrecords/{merchant_record_id}/renew",
+    tag = "merchant-records",
+    params(("merchant_record_id" = uuid::Uuid, Path)),
+    request_body = RenewMerchantRecordSchema,
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "Renewed record appended to the merchant's chain", body = Envelope<MerchantRecordData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks records:write", body = ErrorBody),
+        (status = 404, description = "No such record", body = ErrorBody),
+        (status = 409, description = "prev_data_hash is stale", body = ErrorBody),
+        (status = 422, description = "Body or signature failed validation, or the record has no typed payload", body = ErrorBody),
+    )
+)]
+pub async fn renew_merchant_record_handler(
+    Path(merchant_record_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+    ValidatedJson(body): ValidatedJson<RenewMerchantRecordSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let query_result = sqlx::query_as::<_, MerchantRecordModel>( r#"SELECT * FROM merchantsrecord WHERE id = $1 AND deleted_at IS NULL"#)
+        .bind(merchant_record_id)
+        .fetch_optional(&data.db)
+        .await;
+
+    let previous = match query_result {
+        Ok(Some(previous)) => previous,
+        Ok(None) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": format!("Merchant Record with ID: {} not found", merchant_record_id)
+            });
+            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        }
+        Err(e) => {
             return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({"status": "error","message": format!("{:?}", e)})),
             ));
         }
-    }
+    };
+
+    auth.authorize(previous.merchant_id, SCOPE_RECORDS_WRITE)?;
+
+    let (Some(record_type), Some(data_record)) = (previous.record_type, previous.data_record) else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Merchant Record with ID: {} has no typed payload and cannot be renewed", merchant_record_id)
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    };
+
+    let merchant_record = append_merchant_record(&data, &Actor::merchant(&auth), NewMerchantRecord {
+        id: body.id,
+        merchant_id: previous.merchant_id,
+        record_type,
+        data_record,
+        prev_data_hash: body.prev_data_hash,
+        valid_from: body.valid_from.trunc_subsecs(6),
+        valid_until: body.valid_until.trunc_subsecs(6),
+        supersedes: Some(previous.id),
+        signing_key_id: body.signing_key_id,
+        signature: body.signature,
+    })
+    .await?;
+
+    Ok((StatusCode::CREATED, Envelope::success(MerchantRecordData { merchant_record })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/merchantrecords/by_merchant/{merchant_id}",
+    tag = "merchant-records",
+    params(("merchant_id" = uuid::Uuid, Path), MerchantRecordFilterOptions),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "One page of the merchant's records, archived ones included, oldest first", body = MerchantRecordList),
+        (status = 400, description = "Invalid cursor or contains filter", body = ErrorBody),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks records:read", body = ErrorBody),
+        (status = 422, description = "A filter failed validation", body = ErrorBody),
+    )
+)]
 pub async fn merchant_record_list_handler(
+    uri: OriginalUri,
     Path(merchant_id): Path<uuid::Uuid>,
-    opts: Option<Query<FilterOptions>>, State(data): State<Arc<AppState>>
+    ValidatedQuery(opts): ValidatedQuery<MerchantRecordFilterOptions>, State(data): State<Arc<AppState>>,
+    principal: Principal,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let Query(opts) = opts.unwrap_or_default();
-    
-    let limit = opts.limit.unwrap_or(10);
-    let offset = (opts.page.unwrap_or(1) - 1) * limit;
-    let query_result = sqlx::query_as::<_, MerchantRecordModel>(
-        r#"SELECT * FROM merchantsrecord WHERE merchant_id = $3 ORDER by id LIMIT $1 OFFSET $2"#
-    )
-    .bind(limit as i32)
-    .bind(offset as i32)
+    principal.authorize(merchant_id, SCOPE_RECORDS_READ)?;
+    let contains = parse_contains(opts.contains.as_deref())?;
+
+    let keyset = Keyset::timestamp("data_issued", "timestamptz", opts.cursor.as_deref(), opts.limit)?;
+    let sql = format!(
+        "SELECT *, {} FROM {} WHERE {MERCHANT_RECORD_FILTERS} AND {} ORDER BY {} LIMIT $7",
+        keyset.select_columns(),
+        merchant_record_source(),
+        keyset.predicate(5),
+        keyset.order_by()
+    );
+    let query_result = sqlx::query_as::<_, KeysetRow<MerchantRecordModel>>(&sql)
     .bind(merchant_id)
+    .bind(opts.record_type)
+    .bind(contains)
+    .bind(opts.status.map(|status| status.as_str()))
+    .bind(keyset.cursor_key())
+    .bind(keyset.cursor_id())
+    .bind(keyset.fetch_limit())
     .fetch_all(&data.db)
     .await;
-    println!("result==========> {:?}", query_result);
     if query_result.is_err() {
         let error_response = serde_json::json!({
             "status": "fail",
@@ -80,61 +362,314 @@ pub async fn merchant_record_list_handler(
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
 
-    let merchant_records = query_result.unwrap();
+    let page = keyset.page(query_result.unwrap());
+
+    Ok(Json(MerchantRecordList {
+        status: Status::Success,
+        results: page.items.len(),
+        links: page.links(&uri),
+        cursors: page.cursors(),
+        merchant_records: page.items,
+    }))
+}
+
+/// Filters shared by the record list and export. `$1`..`$4` are the merchant, record type,
+/// `contains` object and status.
+const MERCHANT_RECORD_FILTERS: &str = "merchant_id = $1 AND deleted_at IS NULL AND ($2::text IS NULL OR record_type = $2) AND ($3::jsonb IS NULL OR data_record @> $3) AND ($4::text IS NULL OR ($4 = 'expired') = (valid_until <= now()))";
+
+/// The live records, plus the archived ones unless only active records (`$4`) were asked
+/// for: the expiry worker only archives expired records.
+fn merchant_record_source() -> String {
+    let columns = "id, merchant_id, data_issued, valid_from, valid_until, prev_data_hash, data_record, record_type, data_hash, signing_key_id, signature, status, expired_at, supersedes, deleted_at, format_version";
+    format!(
+        "(SELECT {columns} FROM merchantsrecord UNION ALL SELECT {columns} FROM merchantsrecord_archive WHERE $4::text IS NULL OR $4 = 'expired') AS records"
+    )
+}
+
+fn parse_contains(contains: Option<&str>) -> Result<Option<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    match contains.map(serde_json::from_str::<serde_json::Value>) {
+        None => Ok(None),
+        Some(Ok(value)) if value.is_object() => Ok(Some(value)),
+        Some(_) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": "contains must be a JSON object",
+            });
+            Err((StatusCode::BAD_REQUEST, Json(error_response)))
+        }
+    }
+}
+
+/// A merchant record flattened for export: `data_record` is carried as canonical JSON text
+/// so every format has a single column for it.
+#[derive(Serialize)]
+struct MerchantRecordExportRow {
+    id: uuid::Uuid,
+    merchant_id: uuid::Uuid,
+    data_issued: Option<DateTime<Utc>>,
+    valid_from: DateTime<Utc>,
+    valid_until: DateTime<Utc>,
+    prev_data_hash: String,
+    record_type: Option<String>,
+    data_record: Option<String>,
+    data_hash: Option<String>,
+    signing_key_id: Option<uuid::Uuid>,
+    signature: Option<String>,
+    status: &'static str,
+    expired_at: Option<DateTime<Utc>>,
+    supersedes: Option<uuid::Uuid>,
+    format_version: i16,
+}
+
+impl From<MerchantRecordModel> for MerchantRecordExportRow {
+    fn from(record: MerchantRecordModel) -> Self {
+        let status = if record.is_expired() { RecordStatus::Expired } else { RecordStatus::Active };
+        MerchantRecordExportRow {
+            id: record.id,
+            merchant_id: record.merchant_id,
+            data_issued: record.data_issued,
+            valid_from: record.valid_from,
+            valid_until: record.valid_until,
+            prev_data_hash: record.prev_data_hash,
+            record_type: record.record_type,
+            data_record: record.data_record.as_ref().map(to_canonical_json),
+            data_hash: record.data_hash,
+            signing_key_id: record.signing_key_id,
+            signature: record.signature,
+            status: status.as_str(),
+            expired_at: record.expired_at,
+            supersedes: record.supersedes,
+            format_version: record.format_version,
+        }
+    }
+}
+
+fn merchant_record_export_schema() -> Arc<Schema> {
+    let timestamp = || DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()));
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("merchant_id", DataType::Utf8, false),
+        Field::new("data_issued", timestamp(), true),
+        Field::new("valid_from", timestamp(), false),
+        Field::new("valid_until", timestamp(), false),
+        Field::new("prev_data_hash", DataType::Utf8, false),
+        Field::new("record_type", DataType::Utf8, true),
+        Field::new("data_record", DataType::Utf8, true),
+        Field::new("data_hash", DataType::Utf8, true),
+        Field::new("signing_key_id", DataType::Utf8, true),
+        Field::new("signature", DataType::Utf8, true),
+        Field::new("status", DataType::Utf8, false),
+        Field::new("expired_at", timestamp(), true),
+        Field::new("supersedes", DataType::Utf8, true),
+        Field::new("format_version", DataType::Int16, false),
+    ]))
+}
 
-    let json_response = serde_json::json!({
-        "status": "success",
-        "results": merchant_records.len(),
-        "merchant records": merchant_records
-    });
-    Ok(Json(json_response))
+/// Streams a merchant's records, archived ones included, oldest first, as CSV, NDJSON or
+/// Parquet. Takes the same filters as the record list.
+#[utoipa::path(
+    get,
+    path = "/api/merchantrecords/by_merchant/{merchant_id}/export",
+    tag = "merchant-records",
+    params(("merchant_id" = uuid::Uuid, Path), ExportOptions, MerchantRecordFilterOptions),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (
+            status = 200,
+            description = "A download of every matching record, `data_record` as canonical JSON text",
+            content(
+                (String = "text/csv"),
+                (String = "application/x-ndjson"),
+                (Vec<u8> = "application/vnd.apache.parquet"),
+            )
+        ),
+        (status = 400, description = "Invalid contains filter", body = ErrorBody),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks records:read", body = ErrorBody),
+        (status = 422, description = "A filter failed validation", body = ErrorBody),
+    )
+)]
+pub async fn export_merchant_records_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    ValidatedQuery(export): ValidatedQuery<ExportOptions>,
+    ValidatedQuery(opts): ValidatedQuery<MerchantRecordFilterOptions>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(merchant_id, SCOPE_RECORDS_READ)?;
+    let contains = parse_contains(opts.contains.as_deref())?;
+
+    let rows = async_stream::stream! {
+        let sql = format!("SELECT * FROM {} WHERE {MERCHANT_RECORD_FILTERS} ORDER BY data_issued, id", merchant_record_source());
+        let mut rows = sqlx::query_as::<_, MerchantRecordModel>(&sql)
+            .bind(merchant_id)
+            .bind(opts.record_type.as_deref())
+            .bind(contains.as_ref())
+            .bind(opts.status.map(|status| status.as_str()))
+            .fetch(&data.db);
+        while let Some(row) = rows.next().await {
+            yield row.map(MerchantRecordExportRow::from);
+        }
+    };
+    let filename = format!("merchant-{}-records", merchant_id);
+    Ok(export_response(rows, export.format.unwrap_or_default(), merchant_record_export_schema(), &filename))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/merchantrecords/{merchant_record_id}",
+    tag = "merchant-records",
+    params(("merchant_record_id" = uuid::Uuid, Path), RecordStatusFilter),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The record, read from the archive if it has been moved there", body = Envelope<MerchantRecordStatusData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks records:read", body = ErrorBody),
+        (status = 404, description = "No such record, or it does not have the requested status", body = ErrorBody),
+    )
+)]
 pub async fn get_merchant_record_handler(
     Path(merchant_record_id): Path<uuid::Uuid>,
+    ValidatedQuery(opts): ValidatedQuery<RecordStatusFilter>,
     State(data): State<Arc<AppState>>,
+    principal: Principal,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = sqlx::query_as::<_, MerchantRecordModel>( r#"SELECT * FROM merchantsrecord WHERE id = $1"#)
+    let query_result = sqlx::query_as::<_, MerchantRecordModel>( r#"SELECT * FROM merchantsrecord WHERE id = $1 AND deleted_at IS NULL"#)
         .bind(merchant_record_id)
-        .fetch_one(&data.db)
+        .fetch_optional(&data.db)
         .await;
 
-    match query_result {
-        Ok(merchant_record) => {
-            let merchant_record_response = serde_json::json!({"status": "success","data": serde_json::json!({
-                "merchant_record": merchant_record
-            })});
-
-            return Ok(Json(merchant_record_response));
+    let (merchant_record, archived) = match query_result {
+        Ok(Some(merchant_record)) => (merchant_record, false),
+        Ok(None) => {
+            // Expired records may have been moved out of the live table by the expiry worker.
+            let archived = sqlx::query_as::<_, MerchantRecordModel>( r#"SELECT * FROM merchantsrecord_archive WHERE id = $1 AND deleted_at IS NULL"#)
+                .bind(merchant_record_id)
+                .fetch_optional(&data.db)
+                .await;
+            match archived {
+                Ok(Some(merchant_record)) => (merchant_record, true),
+                _ => {
+                    let error_response = serde_json::json!({
+                        "status": "fail",
+                        "message": format!("Merchant Record with ID: {} not found", merchant_record_id)
+                    });
+                    return Err((StatusCode::NOT_FOUND, Json(error_response)));
+                }
+            }
         }
-        Err(_) => {
-            let error_response = serde_json::json!({
-                "status": "fail",
-                "message": format!("Merchant Record with ID: {} not found", merchant_record_id)
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"status": "error","message": format!("{:?}", e)})),
+            ));
         }
+    };
+
+    principal.authorize(merchant_record.merchant_id, SCOPE_RECORDS_READ)?;
+
+    let record_status = if merchant_record.is_expired() { RecordStatus::Expired } else { RecordStatus::Active };
+    if let Some(status) = opts.status.filter(|status| *status != record_status) {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Merchant Record with ID: {} is not {}", merchant_record_id, status.as_str())
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
     }
+
+    let superseded_by = sqlx::query_scalar::<_, uuid::Uuid>(r#"SELECT id FROM merchantsrecord WHERE supersedes = $1
+        UNION ALL SELECT id FROM merchantsrecord_archive WHERE supersedes = $1 LIMIT 1"#)
+        .bind(merchant_record_id)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let message = match record_status {
+        RecordStatus::Active => None,
+        RecordStatus::Expired => Some(format!(
+            "Merchant Record with ID: {} expired at {}",
+            merchant_record_id,
+            merchant_record.valid_until.to_rfc3339()
+        )),
+    };
+    Ok(Json(Envelope {
+        status: Status::Success,
+        message,
+        data: MerchantRecordStatusData {
+            archived: (record_status == RecordStatus::Expired).then_some(archived),
+            merchant_record,
+            record_status,
+            superseded_by,
+        },
+    }))
 }
 
+/// Soft-deletes the record: it stays in the table, so the merchant's hash chain and the
+/// record's audit trail stay intact, but it is no longer listed, exported or served.
+#[utoipa::path(
+    delete,
+    path = "/api/merchantrecords/{merchant_record_id}",
+    tag = "merchant-records",
+    params(("merchant_record_id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 204, description = "Record deleted"),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks records:write", body = ErrorBody),
+        (status = 404, description = "No such record", body = ErrorBody),
+    )
+)]
 pub async fn delete_merchant_record_handler(
     Path(merchant_record_id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
+    principal: Principal,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let rows_affected = sqlx::query(r#"DELETE FROM merchantsrecord  WHERE id = $1"#)
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let before = sqlx::query_as::<_, MerchantRecordModel>(
+        r#"SELECT * FROM merchantsrecord WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"#
+    )
     .bind(merchant_record_id)
-        .execute(&data.db)
-        .await
-        .unwrap()
-        .rows_affected();
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
 
-    if rows_affected == 0 {
+    let Some(before) = before else {
         let error_response = serde_json::json!({
             "status": "fail",
             "message": format!("Merchant Record with ID: {} not found", merchant_record_id)
         });
         return Err((StatusCode::NOT_FOUND, Json(error_response)));
-    }
+    };
+
+    principal.authorize(before.merchant_id, SCOPE_RECORDS_WRITE)?;
+    let actor = Actor::principal(&principal);
+
+    let deleted = sqlx::query_as::<_, MerchantRecordModel>(
+        r#"UPDATE merchantsrecord SET deleted_at = now() WHERE id = $1 RETURNING *"#
+    )
+    .bind(merchant_record_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: MERCHANT_RECORD_ENTITY,
+        entity_id: merchant_record_id,
+        action: ACTION_DELETE,
+        before: snapshot(&before),
+        after: snapshot(&deleted),
+    })
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
 
     Ok(StatusCode::NO_CONTENT)
-}
\ No newline at end of file
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
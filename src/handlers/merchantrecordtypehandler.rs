@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use jsonschema::JSONSchema;
+use serde_json::json;
+
+use crate::{
+    audit::{record_audit, snapshot, AuditEntry, ACTION_CREATE},
+    auth::{Actor, Principal},
+    canonical::unsafe_integer_paths,
+    model::MerchantRecordTypeModel,
+    response::{Envelope, ErrorBody, MerchantRecordTypeData, MerchantRecordTypeList, Status},
+    schema::{CreateMerchantRecordTypeSchema, SCOPE_ADMIN},
+    validation::ValidatedJson,
+    AppState,
+};
+
+pub const MERCHANT_RECORD_TYPE_ENTITY: &str = "merchant_record_type";
+
+#[utoipa::path(
+    post,
+    path = "/api/merchants/{id}/recordtypes",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID")),
+    request_body = CreateMerchantRecordTypeSchema,
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "Record type registered", body = Envelope<MerchantRecordTypeData>),
+        (status = 400, description = "json_schema is not a valid JSON Schema", body = ErrorBody),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+        (status = 404, description = "No such merchant", body = ErrorBody),
+        (status = 409, description = "The merchant already has a record type with that name", body = ErrorBody),
+        (status = 422, description = "Body failed validation", body = ErrorBody),
+    )
+)]
+pub async fn create_merchant_record_type_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+    ValidatedJson(body): ValidatedJson<CreateMerchantRecordTypeSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(merchant_id, SCOPE_ADMIN)?;
+    let actor = Actor::principal(&principal);
+
+    if let Err(e) = JSONSchema::compile(&body.json_schema) {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Invalid JSON Schema: {}", e)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let query_result = sqlx::query_as::<_, MerchantRecordTypeModel>(
+        r#"INSERT INTO merchant_record_types (merchant_id, record_type, json_schema) VALUES ($1, $2, $3) RETURNING *"#
+    )
+    .bind(merchant_id)
+    .bind(body.record_type)
+    .bind(body.json_schema)
+    .fetch_one(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(record_type) => {
+            record_audit(&mut tx, AuditEntry {
+                actor: actor.as_str(),
+                entity_type: MERCHANT_RECORD_TYPE_ENTITY,
+                entity_id: record_type.id,
+                action: ACTION_CREATE,
+                before: None,
+                after: snapshot(&record_type),
+            })
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
+
+            Ok((StatusCode::CREATED, Envelope::success(MerchantRecordTypeData { record_type })))
+        }
+        Err(e) => {
+            if e.to_string()
+                .contains("duplicate key value violates unique constraint")
+            {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": "Record type with that name already exists for this merchant",
+                });
+                return Err((StatusCode::CONFLICT, Json(error_response)));
+            }
+            if e.to_string().contains("violates foreign key constraint") {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": format!("Merchant with ID: {} not found", merchant_id)
+                });
+                return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"status": "error","message": format!("{:?}", e)})),
+            ))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/merchants/{id}/recordtypes",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID")),
+    responses(
+        (status = 200, description = "The merchant's record types by name", body = MerchantRecordTypeList),
+    )
+)]
+pub async fn merchant_record_type_list_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let query_result = sqlx::query_as::<_, MerchantRecordTypeModel>(
+        r#"SELECT * FROM merchant_record_types WHERE merchant_id = $1 ORDER by record_type"#
+    )
+    .bind(merchant_id)
+    .fetch_all(&data.db)
+    .await;
+
+    if query_result.is_err() {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "Something bad happened while fetching record types",
+        });
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    }
+
+    let record_types = query_result.unwrap();
+
+    Ok(Json(MerchantRecordTypeList {
+        status: Status::Success,
+        results: record_types.len(),
+        record_types,
+    }))
+}
+
+/// Validates `data_record` against the schema registered for `(merchant_id, record_type)`, and
+/// refuses integers that canonical JSON could not carry exactly (I-JSON).
+pub async fn validate_record_payload(
+    db: &sqlx::Pool<sqlx::Postgres>,
+    merchant_id: uuid::Uuid,
+    record_type: &str,
+    data_record: &serde_json::Value,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let query_result = sqlx::query_as::<_, MerchantRecordTypeModel>(
+        r#"SELECT * FROM merchant_record_types WHERE merchant_id = $1 AND record_type = $2"#
+    )
+    .bind(merchant_id)
+    .bind(record_type)
+    .fetch_optional(db)
+    .await;
+
+    let record_type_model = match query_result {
+        Ok(Some(record_type_model)) => record_type_model,
+        Ok(None) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": format!("Record type '{}' is not registered for merchant {}", record_type, merchant_id)
+            });
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+        }
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"status": "error","message": format!("{:?}", e)})),
+            ));
+        }
+    };
+
+    let compiled = JSONSchema::compile(&record_type_model.json_schema).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error","message": format!("Stored schema for '{}' is invalid: {}", record_type, e)})),
+        )
+    })?;
+
+    if let Err(errors) = compiled.validate(data_record) {
+        let errors: Vec<serde_json::Value> = errors
+            .map(|e| json!({"path": e.instance_path.to_string(), "message": e.to_string()}))
+            .collect();
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("data_record does not match the '{}' schema", record_type),
+            "errors": errors
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+
+    let unsafe_integers = unsafe_integer_paths(data_record);
+    if !unsafe_integers.is_empty() {
+        let errors: Vec<serde_json::Value> = unsafe_integers
+            .into_iter()
+            .map(|path| json!({"path": path, "message": "integers must be between -(2^53 - 1) and 2^53 - 1; send larger ones as strings"}))
+            .collect();
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "data_record holds integers too large to sign exactly",
+            "errors": errors
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
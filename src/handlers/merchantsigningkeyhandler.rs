@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    audit::{record_audit, snapshot, AuditEntry, ACTION_CREATE, ACTION_DELETE},
+    auth::{Actor, Principal},
+    model::MerchantSigningKeyModel,
+    response::{Envelope, ErrorBody, MerchantSigningKeyData, MerchantSigningKeyList, Status},
+    schema::{CreateMerchantSigningKeySchema, SCOPE_ADMIN},
+    signing::parse_public_key,
+    validation::ValidatedJson,
+    AppState,
+};
+
+pub const MERCHANT_SIGNING_KEY_ENTITY: &str = "merchant_signing_key";
+
+#[utoipa::path(
+    post,
+    path = "/api/merchants/{id}/signingkeys",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID")),
+    request_body = CreateMerchantSigningKeySchema,
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "Signing key registered", body = Envelope<MerchantSigningKeyData>),
+        (status = 400, description = "Not a valid Ed25519 public key", body = ErrorBody),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+        (status = 404, description = "No such merchant", body = ErrorBody),
+        (status = 409, description = "The public key is already registered", body = ErrorBody),
+        (status = 422, description = "Body failed validation", body = ErrorBody),
+    )
+)]
+pub async fn create_merchant_signing_key_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+    ValidatedJson(body): ValidatedJson<CreateMerchantSigningKeySchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(merchant_id, SCOPE_ADMIN)?;
+    let actor = Actor::principal(&principal);
+
+    let public_key = body.public_key.trim().to_lowercase();
+    if let Err(e) = parse_public_key(&public_key) {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": e
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let query_result = sqlx::query_as::<_, MerchantSigningKeyModel>(
+        r#"INSERT INTO merchant_signing_keys (merchant_id, public_key) VALUES ($1, $2) RETURNING *"#
+    )
+    .bind(merchant_id)
+    .bind(public_key)
+    .fetch_one(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(signing_key) => {
+            record_audit(&mut tx, AuditEntry {
+                actor: actor.as_str(),
+                entity_type: MERCHANT_SIGNING_KEY_ENTITY,
+                entity_id: signing_key.id,
+                action: ACTION_CREATE,
+                before: None,
+                after: snapshot(&signing_key),
+            })
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
+
+            Ok((StatusCode::CREATED, Envelope::success(MerchantSigningKeyData { signing_key })))
+        }
+        Err(e) => {
+            if e.to_string()
+                .contains("duplicate key value violates unique constraint")
+            {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": "Signing key with that public key already exists",
+                });
+                return Err((StatusCode::CONFLICT, Json(error_response)));
+            }
+            if e.to_string().contains("violates foreign key constraint") {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": format!("Merchant with ID: {} not found", merchant_id)
+                });
+                return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"status": "error","message": format!("{:?}", e)})),
+            ))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/merchants/{id}/signingkeys",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID")),
+    responses(
+        (status = 200, description = "The merchant's signing keys, revoked ones included", body = MerchantSigningKeyList),
+    )
+)]
+pub async fn merchant_signing_key_list_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let query_result = sqlx::query_as::<_, MerchantSigningKeyModel>(
+        r#"SELECT * FROM merchant_signing_keys WHERE merchant_id = $1 ORDER by created_at"#
+    )
+    .bind(merchant_id)
+    .fetch_all(&data.db)
+    .await;
+
+    if query_result.is_err() {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "Something bad happened while fetching signing keys",
+        });
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    }
+
+    let signing_keys = query_result.unwrap();
+
+    Ok(Json(MerchantSigningKeyList {
+        status: Status::Success,
+        results: signing_keys.len(),
+        signing_keys,
+    }))
+}
+
+/// Revoked keys can no longer sign new records but stay listed so older signatures remain verifiable.
+#[utoipa::path(
+    delete,
+    path = "/api/merchants/{id}/signingkeys/{key_id}",
+    tag = "merchants",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID"), ("key_id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 204, description = "Signing key revoked"),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks admin", body = ErrorBody),
+        (status = 404, description = "No active signing key with that ID", body = ErrorBody),
+    )
+)]
+pub async fn revoke_merchant_signing_key_handler(
+    Path((merchant_id, key_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    State(data): State<Arc<AppState>>,
+    principal: Principal,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    principal.authorize(merchant_id, SCOPE_ADMIN)?;
+    let actor = Actor::principal(&principal);
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let revoked = sqlx::query_as::<_, MerchantSigningKeyModel>(
+        r#"UPDATE merchant_signing_keys SET revoked_at = now() WHERE id = $1 AND merchant_id = $2 AND revoked_at IS NULL RETURNING *"#
+    )
+    .bind(key_id)
+    .bind(merchant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(revoked) = revoked else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Active signing key with ID: {} not found", key_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    record_audit(&mut tx, AuditEntry {
+        actor: actor.as_str(),
+        entity_type: MERCHANT_SIGNING_KEY_ENTITY,
+        entity_id: revoked.id,
+        action: ACTION_DELETE,
+        before: None,
+        after: snapshot(&revoked),
+    })
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
@@ -1,4 +1,11 @@
+pub mod audithandler;
 pub mod doghandler;
+pub mod livehandler;
+pub mod lostfoundhandler;
 pub mod merchanthandler;
+pub mod merchantapikeyhandler;
 pub mod merchantrecordhandler;
+pub mod merchantrecordtypehandler;
+pub mod merchantsigningkeyhandler;
+pub mod webhookhandler;
 pub mod zkphandler;
\ No newline at end of file
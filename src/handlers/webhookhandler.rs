@@ -0,0 +1,331 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{OriginalUri, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    audit::{record_audit, snapshot, AuditEntry, ACTION_CREATE, ACTION_DELETE},
+    auth::{Actor, MerchantAuth},
+    model::{WebhookDeliveryModel, WebhookSubscriptionModel},
+    pagination::{Keyset, KeysetRow},
+    response::{
+        Envelope, ErrorBody, Status, WebhookDeliveryData, WebhookDeliveryList, WebhookSubscriptionData,
+        WebhookSubscriptionList,
+    },
+    schema::{CreateWebhookSubscriptionSchema, WebhookDeliveryFilterOptions, SCOPE_WEBHOOKS},
+    validation::{ValidatedJson, ValidatedQuery},
+    webhook::{generate_secret, requeue},
+    AppState,
+};
+
+pub const WEBHOOK_SUBSCRIPTION_ENTITY: &str = "webhook_subscription";
+
+#[utoipa::path(
+    post,
+    path = "/api/merchants/{id}/webhooks",
+    tag = "webhooks",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID")),
+    request_body = CreateWebhookSubscriptionSchema,
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "Subscription created; `secret` is shown only this once", body = Envelope<WebhookSubscriptionData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks webhooks", body = ErrorBody),
+        (status = 404, description = "No such merchant", body = ErrorBody),
+        (status = 422, description = "Invalid URL or unknown event types", body = ErrorBody),
+    )
+)]
+pub async fn create_webhook_subscription_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+    ValidatedJson(body): ValidatedJson<CreateWebhookSubscriptionSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(merchant_id, SCOPE_WEBHOOKS)?;
+
+    let secret = generate_secret();
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let query_result = sqlx::query_as::<_, WebhookSubscriptionModel>(
+        r#"INSERT INTO webhook_subscriptions (merchant_id, url, secret, event_types) VALUES ($1, $2, $3, $4) RETURNING *"#
+    )
+    .bind(merchant_id)
+    .bind(body.url)
+    .bind(&secret)
+    .bind(body.event_types.unwrap_or_default())
+    .fetch_one(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(subscription) => {
+            record_audit(&mut tx, AuditEntry {
+                actor: Actor::merchant(&auth).as_str(),
+                entity_type: WEBHOOK_SUBSCRIPTION_ENTITY,
+                entity_id: subscription.id,
+                action: ACTION_CREATE,
+                before: None,
+                after: snapshot(&subscription),
+            })
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
+
+            Ok((StatusCode::CREATED, Envelope::success(WebhookSubscriptionData { subscription, secret })))
+        }
+        Err(e) => {
+            if e.to_string().contains("violates foreign key constraint") {
+                let error_response = serde_json::json!({
+                    "status": "fail",
+                    "message": format!("Merchant with ID: {} not found", merchant_id)
+                });
+                return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            }
+            Err(internal_error(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/merchants/{id}/webhooks",
+    tag = "webhooks",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID")),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The merchant's subscriptions, disabled ones included, without their secrets", body = WebhookSubscriptionList),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks webhooks", body = ErrorBody),
+    )
+)]
+pub async fn webhook_subscription_list_handler(
+    Path(merchant_id): Path<uuid::Uuid>,
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(merchant_id, SCOPE_WEBHOOKS)?;
+
+    let subscriptions = sqlx::query_as::<_, WebhookSubscriptionModel>(
+        r#"SELECT * FROM webhook_subscriptions WHERE merchant_id = $1 ORDER by created_at"#
+    )
+    .bind(merchant_id)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Json(WebhookSubscriptionList {
+        status: Status::Success,
+        results: subscriptions.len(),
+        subscriptions,
+    }))
+}
+
+/// Stops deliveries to the subscription. Pending deliveries are moved to the dead-letter queue.
+#[utoipa::path(
+    delete,
+    path = "/api/merchants/{id}/webhooks/{webhook_id}",
+    tag = "webhooks",
+    params(("id" = uuid::Uuid, Path, description = "Merchant ID"), ("webhook_id" = uuid::Uuid, Path)),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 204, description = "Subscription disabled"),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks webhooks", body = ErrorBody),
+        (status = 404, description = "No active subscription with that ID", body = ErrorBody),
+    )
+)]
+pub async fn disable_webhook_subscription_handler(
+    Path((merchant_id, webhook_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(merchant_id, SCOPE_WEBHOOKS)?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let disabled = sqlx::query_as::<_, WebhookSubscriptionModel>(
+        r#"UPDATE webhook_subscriptions SET disabled_at = now() WHERE id = $1 AND merchant_id = $2 AND disabled_at IS NULL RETURNING *"#
+    )
+    .bind(webhook_id)
+    .bind(merchant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(disabled) = disabled else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Active webhook subscription with ID: {} not found", webhook_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    sqlx::query(
+        r#"UPDATE webhook_deliveries SET status = 'dead', last_error = 'subscription disabled' WHERE subscription_id = $1 AND status = 'pending'"#
+    )
+    .bind(disabled.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    record_audit(&mut tx, AuditEntry {
+        actor: Actor::merchant(&auth).as_str(),
+        entity_type: WEBHOOK_SUBSCRIPTION_ENTITY,
+        entity_id: disabled.id,
+        action: ACTION_DELETE,
+        before: None,
+        after: snapshot(&disabled),
+    })
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Deliveries to one subscription, oldest first. `status=dead` lists its dead-letter queue.
+#[utoipa::path(
+    get,
+    path = "/api/merchants/{id}/webhooks/{webhook_id}/deliveries",
+    tag = "webhooks",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Merchant ID"),
+        ("webhook_id" = uuid::Uuid, Path),
+        WebhookDeliveryFilterOptions,
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "One page of deliveries", body = WebhookDeliveryList),
+        (status = 400, description = "Invalid cursor", body = ErrorBody),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks webhooks", body = ErrorBody),
+        (status = 422, description = "A parameter failed validation", body = ErrorBody),
+    )
+)]
+pub async fn webhook_delivery_list_handler(
+    uri: OriginalUri,
+    Path((merchant_id, webhook_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    ValidatedQuery(opts): ValidatedQuery<WebhookDeliveryFilterOptions>,
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(merchant_id, SCOPE_WEBHOOKS)?;
+
+    let keyset = Keyset::timestamp("created_at", "timestamptz", opts.cursor.as_deref(), opts.limit)?;
+    let sql = format!(
+        "SELECT *, {} FROM webhook_deliveries WHERE subscription_id = $1 AND subscription_id IN (SELECT id FROM webhook_subscriptions WHERE merchant_id = $2) AND ($3::text IS NULL OR status = $3) AND {} ORDER BY {} LIMIT $6",
+        keyset.select_columns(),
+        keyset.predicate(4),
+        keyset.order_by()
+    );
+    let rows = sqlx::query_as::<_, KeysetRow<WebhookDeliveryModel>>(&sql)
+        .bind(webhook_id)
+        .bind(merchant_id)
+        .bind(opts.status.map(|status| status.as_str()))
+        .bind(keyset.cursor_key())
+        .bind(keyset.cursor_id())
+        .bind(keyset.fetch_limit())
+        .fetch_all(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let page = keyset.page(rows);
+
+    Ok(Json(WebhookDeliveryList {
+        status: Status::Success,
+        results: page.items.len(),
+        links: page.links(&uri),
+        cursors: page.cursors(),
+        deliveries: page.items,
+    }))
+}
+
+/// Queues a delivered or dead-lettered delivery to be sent again, with a fresh set of attempts.
+#[utoipa::path(
+    post,
+    path = "/api/merchants/{id}/webhooks/{webhook_id}/deliveries/{delivery_id}/replay",
+    tag = "webhooks",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Merchant ID"),
+        ("webhook_id" = uuid::Uuid, Path),
+        ("delivery_id" = uuid::Uuid, Path),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+    responses(
+        (status = 202, description = "Delivery queued", body = Envelope<WebhookDeliveryData>),
+        (status = 401, description = "Missing, invalid or revoked API key", body = ErrorBody),
+        (status = 403, description = "The key is for another merchant or lacks webhooks", body = ErrorBody),
+        (status = 404, description = "No such delivery", body = ErrorBody),
+        (status = 409, description = "The delivery is still pending, or the subscription is disabled", body = ErrorBody),
+    )
+)]
+pub async fn replay_webhook_delivery_handler(
+    Path((merchant_id, webhook_id, delivery_id)): Path<(uuid::Uuid, uuid::Uuid, uuid::Uuid)>,
+    State(data): State<Arc<AppState>>,
+    auth: MerchantAuth,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(merchant_id, SCOPE_WEBHOOKS)?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let subscription = sqlx::query_as::<_, WebhookSubscriptionModel>(
+        r#"SELECT * FROM webhook_subscriptions WHERE id = $1 AND merchant_id = $2"#
+    )
+    .bind(webhook_id)
+    .bind(merchant_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(subscription) = subscription else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Webhook subscription with ID: {} not found", webhook_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+    if subscription.disabled_at.is_some() {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Webhook subscription with ID: {} is disabled", webhook_id)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let delivery = sqlx::query_as::<_, WebhookDeliveryModel>(
+        r#"SELECT * FROM webhook_deliveries WHERE id = $1 AND subscription_id = $2 FOR UPDATE"#
+    )
+    .bind(delivery_id)
+    .bind(webhook_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(delivery) = delivery else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Webhook delivery with ID: {} not found", delivery_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+    if delivery.status == "pending" {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Webhook delivery with ID: {} is still pending", delivery_id)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let delivery = requeue(&mut tx, delivery.id).await.map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok((StatusCode::ACCEPTED, Envelope::success(WebhookDeliveryData { delivery })))
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
@@ -1,17 +1,33 @@
+use std::{
+    panic,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 use axum::{
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 
 use crate::{
-    schema::ZkpSignUpSchema,
-    zkpgenerate::{zkpproof_sign_in, zkpproof_sign_up},
+    config::ChainBackend,
+    live::ProofJob,
+    model::ZkpModel,
+    openapi::IdempotencyKeyHeader,
+    outbox::{enqueue, enqueue_held, release, OutboxEvent},
+    response::{Envelope, ErrorBody, ZkpData},
+    schema::{ZkpSignInSchema, ZkpSignUpSchema, EVENT_DOG_ENROLLED, EVENT_ZKP_SIGNIN_FAILED, EVENT_ZKP_SIGNIN_SUCCEEDED},
+    zkpgenerate::{scalar_to_hex, zkpproof_sign_in, zkpproof_sign_up},
+    validation::ValidatedJson,
+    AppState,
 };
 
 use bellman::groth16::{Proof, VerifyingKey};
 use bls12_381::{Bls12, Scalar, G1Affine, G2Affine};
+use metrics::{counter, histogram};
+use tracing::{info_span, Instrument};
 
 pub use borsh::{BorshDeserialize, BorshSerialize};
 
@@ -24,48 +40,231 @@ impl From<Scalar> for ScalarWrapper {
     }
 }
 
-impl Into<Scalar> for ScalarWrapper {
-    fn into(self) -> Scalar {
-        Scalar::from_bytes(&self.0).unwrap()
+impl From<ScalarWrapper> for Scalar {
+    fn from(val: ScalarWrapper) -> Self {
+        Scalar::from_bytes(&val.0).unwrap()
     }
 }
 
 use crate::sol_connect::{user_sign_up, user_sign_in};
 
+#[utoipa::path(
+    post,
+    path = "/api/zkp/signup",
+    tag = "zkp",
+    params(IdempotencyKeyHeader),
+    request_body = ZkpSignUpSchema,
+    responses(
+        (status = 200, description = "Commitment stored and sent on-chain, or already enrolled with it"),
+        (status = 404, description = "No such dog", body = ErrorBody),
+        (status = 409, description = "The dog is enrolled with another commitment, or the Idempotency-Key is still in use", body = ErrorBody),
+        (status = 422, description = "Body failed validation", body = ErrorBody),
+        (status = 502, description = "The chain did not take the commitment; retrying submits it again", body = ErrorBody),
+    )
+)]
 pub async fn zkp_signup(
-    Json(body): Json<ZkpSignUpSchema>,
+    State(data): State<Arc<AppState>>,
+    ValidatedJson(body): ValidatedJson<ZkpSignUpSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let embeddinghash_num = hash_to_array(&body.embedding_hash);
-    let embeddinghash_num_arr = embeddinghash_num.unwrap();
+    let embeddinghash_num_arr = hash_to_array(&body.embedding_hash).map_err(invalid_embedding_hash)?;
     let microchipid_num_arr = body.microchip_id.to_be_bytes();
+
+    let dog_exists = sqlx::query_scalar::<_, bool>(r#"SELECT EXISTS (SELECT 1 FROM dogs WHERE id = $1 AND deleted_at IS NULL)"#)
+        .bind(body.dog_id)
+        .fetch_one(&data.db)
+        .await
+        .map_err(internal_error)?;
+    if !dog_exists {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Dog with ID: {} not found", body.dog_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    let job = ProofJob::start(&data.db, "signup", Some(body.dog_id)).await;
     let public_input = zkpproof_sign_up(embeddinghash_num_arr, microchipid_num_arr);
-    println!("Public Input : {:?}", public_input);
+
+    // Keep the commitment so later scans (e.g. a lost-dog match) can be verified against it.
+    // It is written as pending along with its `dog.enrolled` event, which stays held until the
+    // chain has the commitment. A retry after a failed submit finds the pending row and
+    // submits it again.
+    let stored_input: Vec<String> = public_input.iter().map(scalar_to_hex).collect();
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    sqlx::query(r#"INSERT INTO zkptable (dog_id, public_input) VALUES ($1, $2) ON CONFLICT (dog_id) DO NOTHING"#)
+        .bind(body.dog_id)
+        .bind(&stored_input)
+        .execute(&mut *tx)
+        .instrument(info_span!("db.insert", table = "zkptable"))
+        .await
+        .map_err(internal_error)?;
+    let commitment = sqlx::query_as::<_, ZkpModel>(r#"SELECT * FROM zkptable WHERE dog_id = $1 FOR UPDATE"#)
+        .bind(body.dog_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    if commitment.submission_status == "submitted" {
+        if commitment.public_input == stored_input {
+            job.finish(true).await;
+            return Ok(());
+        }
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Dog with ID: {} is already enrolled with another commitment", body.dog_id)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let event_id = match commitment.enrolled_event_id {
+        Some(event_id) => event_id,
+        None => enqueue_held(&mut tx, OutboxEvent {
+            event_type: EVENT_DOG_ENROLLED,
+            merchant_id: None,
+            payload: serde_json::json!({"dog_id": body.dog_id}),
+        })
+        .await
+        .map_err(internal_error)?,
+    };
+    sqlx::query(r#"UPDATE zkptable SET public_input = $1, enrolled_event_id = $2 WHERE id = $3"#)
+        .bind(&stored_input)
+        .bind(event_id)
+        .bind(commitment.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
     let public_input_to_send = vec![ScalarWrapper::from(public_input[0]), ScalarWrapper::from(public_input[1])];
-    println!("Public Input to send : {:?}", public_input_to_send);
-    user_sign_up(public_input_to_send);
-    Ok({})
+    let backend = data.config.chain.backend;
+    blocking(move || submit_to_chain(backend, "sign_up", || user_sign_up(public_input_to_send)))
+        .await
+        .map_err(chain_error)?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    sqlx::query(r#"UPDATE zkptable SET submission_status = 'submitted' WHERE id = $1"#)
+        .bind(commitment.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    release(&mut tx, event_id).await.map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    job.finish(true).await;
+    Ok(())
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/zkp/signin/",
+    tag = "zkp",
+    request_body = ZkpSignInSchema,
+    responses(
+        (status = 200, description = "Proof generated and sent on-chain", body = Envelope<ZkpData>),
+        (status = 422, description = "Body failed validation", body = ErrorBody),
+        (status = 502, description = "The chain did not take the proof", body = ErrorBody),
+    )
+)]
 pub async fn zkp_signin(
-    Json(body): Json<ZkpSignUpSchema>,
+    State(data): State<Arc<AppState>>,
+    ValidatedJson(body): ValidatedJson<ZkpSignInSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let embeddinghash_num = hash_to_array(&body.embedding_hash);
-    let embeddinghash_num_arr = embeddinghash_num.unwrap();
+    let embeddinghash_num_arr = hash_to_array(&body.embedding_hash).map_err(invalid_embedding_hash)?;
     let microchipid_num_arr = body.microchip_id.to_be_bytes();
-    let (proof, vk) = zkpproof_sign_in(embeddinghash_num_arr, microchipid_num_arr);
-    println!("Proof: {:?}", proof);
+    let job = ProofJob::start(&data.db, "signin", None).await;
+    let (proof, vk) = blocking(move || zkpproof_sign_in(embeddinghash_num_arr, microchipid_num_arr)).await;
     let proof_bytes = serialize_proof(&proof);
     let vk_to_send = serialize_verifying_key(&vk);
-    user_sign_in(proof_bytes, vk_to_send);
+    let backend = data.config.chain.backend;
+    let submitted = blocking(move || submit_to_chain(backend, "sign_in", || user_sign_in(proof_bytes, vk_to_send))).await;
+
+    // Sign-in proves knowledge of the dog's secrets without naming it, so the event does not
+    // either. Nothing else is written here, so a lost event is logged rather than failing the proof.
+    let event = OutboxEvent {
+        event_type: if submitted.is_ok() { EVENT_ZKP_SIGNIN_SUCCEEDED } else { EVENT_ZKP_SIGNIN_FAILED },
+        merchant_id: None,
+        payload: serde_json::json!({"dog_id": null, "source": "signin"}),
+    };
+    let enqueued = match data.db.acquire().await {
+        Ok(mut conn) => enqueue(&mut conn, event).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = enqueued {
+        tracing::error!(error = %e, "failed to record the sign-in event");
+    }
+    job.finish(submitted.is_ok()).await;
+    submitted.map_err(chain_error)?;
+
+    Ok(Envelope::success(ZkpData {
+        zkp: "Proof generated successfuly!".to_string(),
+    }))
+}
+
+/// Runs `f` on the blocking pool, in the caller's span, so proving and the synchronous Solana
+/// client do not hold up the async workers. A panic in `f` carries on in the caller.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    let span = tracing::Span::current();
+    match tokio::task::spawn_blocking(move || span.in_scope(f)).await {
+        Ok(value) => value,
+        Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+        Err(e) => panic!("blocking task failed: {}", e),
+    }
+}
+
+/// Outcome and time of the latest chain submission, `None` before the first.
+static LAST_SUBMISSION: Mutex<Option<(bool, chrono::DateTime<chrono::Utc>)>> = Mutex::new(None);
+
+/// Whether the latest chain submission succeeded, and when it was made; for `/readyz`.
+pub fn last_chain_submission() -> Option<(bool, chrono::DateTime<chrono::Utc>)> {
+    *LAST_SUBMISSION.lock().unwrap()
+}
+
+/// Runs `submit` against the chain backend, counting the outcome in `chain_submissions_total`.
+fn submit_to_chain(
+    backend: ChainBackend,
+    instruction: &'static str,
+    submit: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+    if backend == ChainBackend::Disabled {
+        counter!("chain_submissions_total", "instruction" => instruction, "outcome" => "skipped").increment(1);
+        return Ok(());
+    }
+    let start = Instant::now();
+    let result = info_span!("chain.submit", backend = "solana", instruction).in_scope(submit);
+    if let Err(e) = &result {
+        tracing::warn!(error = %e, instruction, "chain submission failed");
+    }
+    histogram!("chain_submission_duration_seconds", "instruction" => instruction).record(start.elapsed().as_secs_f64());
+    *LAST_SUBMISSION.lock().unwrap() = Some((result.is_ok(), chrono::Utc::now()));
+    let outcome = if result.is_ok() { "submitted" } else { "failed" };
+    counter!("chain_submissions_total", "instruction" => instruction, "outcome" => outcome).increment(1);
+    result
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
 
-    let zkp_response = serde_json::json!({"status": "success","data": serde_json::json!({
-        "zkp": "Proof generated successfuly!"
-    })});
+fn chain_error(e: String) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::BAD_GATEWAY,
+        Json(serde_json::json!({"status": "error","message": format!("Chain submission failed: {}", e)})),
+    )
+}
 
-    return Ok(Json(zkp_response));
+fn invalid_embedding_hash(e: String) -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = serde_json::json!({
+        "status": "fail",
+        "message": "Validation failed",
+        "errors": {"embedding_hash": [{"code": "hex", "message": e}]}
+    });
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(error_response))
 }
 
-fn hash_to_array(hash: &str) -> Result<[u8; 64], String> {
+pub fn hash_to_array(hash: &str) -> Result<[u8; 64], String> {
     if hash.len() != 64 {
         return Err(format!("Expected 64 characters, got {}", hash.len()));
     }
@@ -111,112 +310,59 @@ fn serialize_verifying_key(vk: &VerifyingKey<Bls12>) -> Vec<u8> {
     bytes
 }
 
+#[cfg(test)]
+mod tests {
+    use sqlx::{Pool, Postgres};
 
+    use super::*;
+    use crate::{config::Config, migrate::MIGRATOR};
 
-// fn deserialize_g1(bytes: &[u8]) -> G1Affine{
-
-
-//     // Convert slice to a fixed-size array
-//     let fixed_bytes: [u8; 48] = bytes.try_into().unwrap();
-
-//     // Deserialize the compressed G1Affine point
-//     return G1Affine::from_compressed(&fixed_bytes).unwrap();
-// }
-
-// fn deserialize_g2(bytes: &[u8]) -> G2Affine {
-//     // Convert slice to a fixed-size array
-//     let fixed_bytes: [u8; 96] = bytes.try_into().unwrap();
-
-//     // Deserialize the compressed G1Affine point
-//     return G2Affine::from_compressed(&fixed_bytes).unwrap();
-// }
-
-// fn deserialize_proof(bytes: &[u8]) -> Proof<Bls12> {
-//     assert_eq!(bytes.len(), 192, "Invalid proof length");
-
-//     let a_bytes = &bytes[..48];
-//     let b_bytes = &bytes[48..144];
-//     let c_bytes = &bytes[144..];
-
-//     let a = deserialize_g1(a_bytes);
-//     let b = deserialize_g2(b_bytes);
-//     let c = deserialize_g1(c_bytes);
-
-//     Proof { a, b, c }
-// }
-
-// fn deserialize_verifying_key(data: &[u8]) -> Option<VerifyingKey<Bls12>> {
-//     if data.len() < 48 * 3 + 96 * 3 {
-//         return None;
-//     }
-
-//     let alpha_g1_bytes = &data[..48];
-//     let beta_g1_bytes = &data[48..96];
-//     let beta_g2_bytes = &data[96..192];
-//     let gamma_g2_bytes = &data[192..288];
-//     let delta_g1_bytes = &data[288..336];
-//     let delta_g2_bytes = &data[336..432];
-
-//     println!("Alpha G1 bytes: {:?}", alpha_g1_bytes);
-//     println!("Beta G1 bytes: {:?}", beta_g1_bytes);
-//     println!("Beta G2 bytes: {:?}", beta_g2_bytes);
-//     println!("Gamma G2 bytes: {:?}", gamma_g2_bytes);
-//     println!("Delta G1 bytes: {:?}", delta_g1_bytes);
-//     println!("Delta G2 bytes: {:?}", delta_g2_bytes);
-
-//     let alpha_g1 = deserialize_g1(alpha_g1_bytes);
-//     println!("Alpha G1: {:?}", alpha_g1);
-//     let beta_g1 = deserialize_g1(beta_g1_bytes);
-//     println!("Beta G1: {:?}", beta_g1);
-//     let beta_g2 = deserialize_g2(beta_g2_bytes);
-//     println!("Beta G2: {:?}", beta_g2);
-//     let gamma_g2 = deserialize_g2(gamma_g2_bytes);
-//     println!("Gamma G2: {:?}", gamma_g2);
-//     let delta_g1 = deserialize_g1(delta_g1_bytes);
-//     println!("Delta G1: {:?}", delta_g1);
-//     let delta_g2 = deserialize_g2(delta_g2_bytes);
-//     println!("Delta G2: {:?}", delta_g2);
-
-//     let ic_data = &data[432..];
-//     let ic_count = ic_data.len() / 48;
-//     let mut ic = Vec::new();
-//     for i in 0..ic_count {
-//         let ic_bytes: [u8; 48] = ic_data[i * 48..(i + 1) * 48].try_into().ok()?;
-//         let ic_point = deserialize_g1(&ic_bytes);
-//         ic.push(ic_point);
-//     }
-
-//     Some(VerifyingKey {
-//         alpha_g1,
-//         beta_g1,
-//         beta_g2,
-//         gamma_g2,
-//         delta_g1,
-//         delta_g2,
-//         ic,
-//     })
-// }
-
-
-// pub fn user_sign_in(proof_bytes: Vec<u8>, vk_bytes: Vec<u8>, public_input_bytes: Vec<ScalarWrapper>) {
-//     print!("Begin user_sign_in instruction");
-
-//     println!("Proof bytes length: {}", proof_bytes.len());
-//     println!("VK bytes length: {}", vk_bytes.len());
-
-//     let proof = deserialize_proof(&proof_bytes);
-//     println!("proof===========> {:?}", proof);
-//     let vk = deserialize_verifying_key(&vk_bytes).unwrap();
-    
-//     let pvk = prepare_verifying_key(&vk);
-
-//     let public_input = vec![
-//         public_input_bytes[0].clone().into(),
-//         public_input_bytes[1].clone().into(),
-//     ];
-//     println!("proof===========> {:?}", public_input);
-//     print!("Verifying proof...");
-//     let result = verify_proof(&pvk, &proof, &public_input);
-//     println!("Proof verification result: {}", result.is_ok());
-
-// }
\ No newline at end of file
+    fn state(db: &Pool<Postgres>, backend: ChainBackend) -> State<Arc<AppState>> {
+        let mut config = Config::default();
+        config.chain.backend = backend;
+        State(Arc::new(AppState::for_tests(db.clone(), config)))
+    }
+
+    fn sign_up(dog_id: uuid::Uuid, microchip_id: u128) -> ValidatedJson<ZkpSignUpSchema> {
+        ValidatedJson(ZkpSignUpSchema { dog_id, embedding_hash: "ab".repeat(32), microchip_id })
+    }
+
+    /// (submission status, enrolled event held) of each commitment for `dog_id`.
+    async fn enrollment(db: &Pool<Postgres>, dog_id: uuid::Uuid) -> Vec<(String, Option<bool>)> {
+        sqlx::query_as(
+            r#"SELECT z.submission_status, e.held FROM zkptable z LEFT JOIN outbox_events e ON e.id = z.enrolled_event_id WHERE z.dog_id = $1"#,
+        )
+        .bind(dog_id)
+        .fetch_all(db)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn sign_up_announces_only_submitted_commitments_once(db: Pool<Postgres>) {
+        let dog_id = sqlx::query_scalar::<_, uuid::Uuid>(r#"INSERT INTO dogs (name, color) VALUES ('Rex', 'brown') RETURNING id"#)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+
+        // Nothing is listening for the Solana client, so the submit fails and the commitment
+        // and its event wait for a retry.
+        let failed = zkp_signup(state(&db, ChainBackend::Solana), sign_up(dog_id, 7)).await.err().unwrap();
+        assert_eq!(failed.0, StatusCode::BAD_GATEWAY);
+        assert_eq!(enrollment(&db, dog_id).await, [("pending".to_string(), Some(true))]);
+
+        for _ in 0..2 {
+            assert!(zkp_signup(state(&db, ChainBackend::Disabled), sign_up(dog_id, 7)).await.is_ok());
+            assert_eq!(enrollment(&db, dog_id).await, [("submitted".to_string(), Some(false))]);
+        }
+        let events = sqlx::query_scalar::<_, i64>(r#"SELECT count(*) FROM outbox_events WHERE event_type = $1"#)
+            .bind(EVENT_DOG_ENROLLED)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(events, 1);
+
+        let other = zkp_signup(state(&db, ChainBackend::Disabled), sign_up(dog_id, 8)).await.err().unwrap();
+        assert_eq!(other.0, StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,81 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{config::ChainBackend, handlers::zkphandler::last_chain_submission, zkpgenerate, AppState};
+
+pub const HEALTHZ_PATH: &str = "/healthz";
+pub const READYZ_PATH: &str = "/readyz";
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Liveness: the process is up and serving. Deliberately checks nothing else, so a database
+/// outage does not get the process restarted.
+pub async fn healthz_handler() -> impl IntoResponse {
+    Json(json!({"status": "success"}))
+}
+
+/// Readiness: whether this instance should be sent traffic. Fails while shutting down, and
+/// when the database or the configured Groth16 parameters are unavailable. The chain is
+/// reported but does not fail readiness: an outage there is shared by every instance, and
+/// everything but chain submission keeps working through it.
+pub async fn readyz_handler(State(data): State<Arc<AppState>>) -> impl IntoResponse {
+    let database = check_database(&data).await;
+    let params = check_params(&data);
+    let chain = check_chain(&data);
+    let draining = data.shutdown.is_cancelled();
+
+    let ready = !draining && database.is_ok() && params.is_ok();
+    let status_code = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = json!({
+        "status": if ready { "success" } else { "fail" },
+        "draining": draining,
+        "checks": {
+            "database": report(database),
+            "params": report(params),
+            "chain": report(chain),
+        }
+    });
+    (status_code, Json(body))
+}
+
+/// `Ok` carries a note such as `disabled` when a check does not apply.
+type Check = Result<&'static str, String>;
+
+fn report(check: Check) -> serde_json::Value {
+    match check {
+        Ok(state) => json!({"status": state}),
+        Err(message) => json!({"status": "unavailable", "message": message}),
+    }
+}
+
+async fn check_database(data: &AppState) -> Check {
+    let query = sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&data.db);
+    match tokio::time::timeout(CHECK_TIMEOUT, query).await {
+        Ok(Ok(_)) => Ok("ok"),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("timed out".to_string()),
+    }
+}
+
+fn check_params(data: &AppState) -> Check {
+    match (&data.config.prover.params_path, zkpgenerate::params_loaded()) {
+        (None, _) => Ok("generated per proof"),
+        (Some(_), true) => Ok("ok"),
+        (Some(path), false) => Err(format!("parameters from {} are not loaded", path.display())),
+    }
+}
+
+/// The Solana client owns its connection, so the chain is judged by how the latest
+/// submission went rather than probed.
+fn check_chain(data: &AppState) -> Check {
+    if data.config.chain.backend == ChainBackend::Disabled {
+        return Ok("disabled");
+    }
+    match last_chain_submission() {
+        None => Ok("no submissions yet"),
+        Some((true, _)) => Ok("ok"),
+        Some((false, at)) => Err(format!("the latest submission, at {}, failed", at.to_rfc3339())),
+    }
+}
@@ -0,0 +1,304 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{
+        header::{CONTENT_TYPE, ETAG, LOCATION},
+        HeaderMap, HeaderName, HeaderValue, StatusCode,
+    },
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    auth::{extract_api_key, hash_api_key},
+    encoding::to_hex,
+    model::IdempotencyKeyModel,
+    AppState,
+};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// How long a key and its stored response are kept. After that the key may be reused.
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+/// How long a claimed key with no stored response blocks retries. Claims are released when
+/// the request fails, panics or is dropped; this covers a replica dying mid-request.
+const CLAIM_LEASE_SECS: f64 = 300.0;
+
+/// Times the key is claimed again after the row it conflicted with was released or purged
+/// before it could be read.
+const CLAIM_ATTEMPTS: usize = 3;
+
+const MAX_KEY_LEN: usize = 255;
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Response headers stored alongside the body and sent again on replay.
+const REPLAYED_HEADERS: [HeaderName; 3] = [CONTENT_TYPE, ETAG, LOCATION];
+
+/// Route middleware for POST endpoints that create things. A request carrying an
+/// `Idempotency-Key` runs at most once: retries with the same key and body get the stored
+/// response back, and reusing the key for a different request is a 422.
+///
+/// Keys belong to the caller that sent them, identified by the hash of its API key, so two
+/// callers picking the same key never see each other's responses. Callers without a key
+/// share one scope.
+///
+/// Server errors are not stored, so a request that failed with a 5xx can be retried with
+/// the same key.
+pub async fn idempotency(
+    State(data): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let key = match request.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        None => return Ok(next.run(request).await),
+        Some(value) => value.to_str().map(|v| v.trim().to_string()).unwrap_or_default(),
+    };
+    if key.is_empty() || key.len() > MAX_KEY_LEN || !key.bytes().all(|b| (0x21..=0x7e).contains(&b)) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("Idempotency-Key must be between 1 and {} visible ASCII characters", MAX_KEY_LEN),
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_BYTES).await.map_err(|_| {
+        let error_response = json!({
+            "status": "fail",
+            "message": "Request body is too large",
+        });
+        (StatusCode::PAYLOAD_TOO_LARGE, Json(error_response))
+    })?;
+    let caller = extract_api_key(&parts.headers)
+        .filter(|api_key| !api_key.is_empty())
+        .map(|api_key| hash_api_key(&api_key))
+        .unwrap_or_default();
+    let request_hash = hash_request(&caller, parts.method.as_str(), parts.uri.path(), &body);
+
+    let mut attempts = 0;
+    while !claim_key(&data.db, &caller, &key, &request_hash).await.map_err(internal_error)? {
+        if let Some(response) = replay(&data.db, &caller, &key, &request_hash).await? {
+            return Ok(response);
+        }
+        attempts += 1;
+        if attempts == CLAIM_ATTEMPTS {
+            let error_response = json!({
+                "status": "fail",
+                "message": "A request with this Idempotency-Key is still being processed",
+            });
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+    }
+
+    // Released on every path that does not store a response, including the handler
+    // panicking and the client going away, which drop this future mid-request.
+    let mut claim = Claim { db: data.db.clone(), caller, key, stored: false };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, usize::MAX).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error","message": format!("{:?}", e)})),
+        )
+    })?;
+
+    if !parts.status.is_server_error() {
+        let headers: serde_json::Map<String, serde_json::Value> = REPLAYED_HEADERS
+            .iter()
+            .filter_map(|name| {
+                let value = parts.headers.get(name)?.to_str().ok()?;
+                Some((name.to_string(), json!(value)))
+            })
+            .collect();
+        sqlx::query(
+            r#"UPDATE idempotency_keys SET response_status = $1, response_headers = $2, response_body = $3, completed_at = now() WHERE caller = $4 AND idempotency_key = $5"#
+        )
+        .bind(parts.status.as_u16() as i16)
+        .bind(serde_json::Value::Object(headers))
+        .bind(body.as_ref())
+        .bind(&claim.caller)
+        .bind(&claim.key)
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+        claim.stored = true;
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Claims the key, taking over one whose previous use has outlived the TTL or whose
+/// unfinished claim has outlived its lease. `false` when another request holds it.
+async fn claim_key(db: &Pool<Postgres>, caller: &str, key: &str, request_hash: &str) -> Result<bool, sqlx::Error> {
+    let claimed = sqlx::query_scalar::<_, String>(
+        r#"INSERT INTO idempotency_keys (caller, idempotency_key, request_hash) VALUES ($1, $2, $3)
+        ON CONFLICT (caller, idempotency_key) DO UPDATE SET
+            request_hash = EXCLUDED.request_hash,
+            response_status = NULL,
+            response_headers = NULL,
+            response_body = NULL,
+            created_at = now(),
+            completed_at = NULL
+        WHERE idempotency_keys.created_at < now() - make_interval(hours => $4)
+            OR (idempotency_keys.completed_at IS NULL AND idempotency_keys.created_at < now() - make_interval(secs => $5))
+        RETURNING idempotency_key"#
+    )
+    .bind(caller)
+    .bind(key)
+    .bind(request_hash)
+    .bind(IDEMPOTENCY_KEY_TTL_HOURS as i32)
+    .bind(CLAIM_LEASE_SECS)
+    .fetch_optional(db)
+    .await?;
+    Ok(claimed.is_some())
+}
+
+/// The stored response for the key, or `None` if its row has gone since the claim failed.
+async fn replay(
+    db: &Pool<Postgres>,
+    caller: &str,
+    key: &str,
+    request_hash: &str,
+) -> Result<Option<Response>, (StatusCode, Json<serde_json::Value>)> {
+    let stored = sqlx::query_as::<_, IdempotencyKeyModel>(
+        r#"SELECT * FROM idempotency_keys WHERE caller = $1 AND idempotency_key = $2"#
+    )
+    .bind(caller)
+    .bind(key)
+    .fetch_optional(db)
+    .await
+    .map_err(internal_error)?;
+    let Some(stored) = stored else {
+        return Ok(None);
+    };
+
+    if stored.request_hash != request_hash {
+        let error_response = json!({
+            "status": "fail",
+            "message": "Idempotency-Key has already been used for a different request",
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+
+    let Some(status) = stored.response_status else {
+        let error_response = json!({
+            "status": "fail",
+            "message": "A request with this Idempotency-Key is still being processed",
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Some(serde_json::Value::Object(stored_headers)) = stored.response_headers {
+        for (name, value) in stored_headers {
+            let (Ok(name), Some(Ok(value))) = (
+                HeaderName::try_from(name.as_str()),
+                value.as_str().map(HeaderValue::from_str),
+            ) else {
+                continue;
+            };
+            headers.insert(name, value);
+        }
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+
+    let mut response = Response::new(Body::from(stored.response_body.unwrap_or_default()));
+    *response.status_mut() = StatusCode::from_u16(status as u16).unwrap_or(StatusCode::OK);
+    *response.headers_mut() = headers;
+    Ok(Some(response))
+}
+
+/// A claimed key. Dropped without a stored response, it frees the key so a retry can run
+/// the request again.
+struct Claim {
+    db: Pool<Postgres>,
+    caller: String,
+    key: String,
+    stored: bool,
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        if self.stored {
+            return;
+        }
+        let db = self.db.clone();
+        let caller = std::mem::take(&mut self.caller);
+        let key = std::mem::take(&mut self.key);
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move { release_key(&db, &caller, &key).await });
+        }
+    }
+}
+
+async fn release_key(db: &Pool<Postgres>, caller: &str, key: &str) {
+    if let Err(e) = sqlx::query(
+        r#"DELETE FROM idempotency_keys WHERE caller = $1 AND idempotency_key = $2 AND response_status IS NULL"#
+    )
+    .bind(caller)
+    .bind(key)
+    .execute(db)
+    .await
+    {
+        tracing::error!(error = %e, "failed to release idempotency key");
+    }
+}
+
+/// Drops keys past their TTL.
+#[tracing::instrument(name = "db.purge_expired_idempotency_keys", skip_all, err)]
+pub async fn purge_expired_idempotency_keys(db: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let purged = sqlx::query(
+        r#"DELETE FROM idempotency_keys WHERE created_at < now() - make_interval(hours => $1)"#
+    )
+    .bind(IDEMPOTENCY_KEY_TTL_HOURS as i32)
+    .execute(db)
+    .await?
+    .rows_affected();
+    Ok(purged)
+}
+
+fn hash_request(caller: &str, method: &str, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(caller.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(method.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(path.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(body);
+    to_hex(&hasher.finalize())
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error","message": format!("{:?}", e)})),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrate::MIGRATOR;
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn a_released_key_is_claimed_again_instead_of_replayed(db: Pool<Postgres>) {
+        assert!(claim_key(&db, "caller", "key-1", "hash").await.unwrap());
+        assert!(!claim_key(&db, "caller", "key-1", "hash").await.unwrap());
+        let in_flight = replay(&db, "caller", "key-1", "hash").await.unwrap_err();
+        assert_eq!(in_flight.0, StatusCode::CONFLICT);
+
+        release_key(&db, "caller", "key-1").await;
+        assert!(replay(&db, "caller", "key-1", "hash").await.unwrap().is_none());
+        assert!(claim_key(&db, "caller", "key-1", "hash").await.unwrap());
+    }
+}
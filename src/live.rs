@@ -0,0 +1,302 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{http::StatusCode, Json};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{postgres::PgListener, Pool, Postgres};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{
+    auth::MerchantAuth,
+    schema::{EventStreamOptions, LIVE_TOPICS, SCOPE_RECORDS_READ, TOPIC_CHAIN_HEAD, TOPIC_MERCHANT_RECORDS, TOPIC_PROOFS},
+};
+
+/// Postgres channel every replica listens on. The triggers in
+/// `migrations/*_live_event_triggers.up.sql` notify it too.
+pub const LIVE_CHANNEL: &str = "live_events";
+
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+pub type LiveSender = broadcast::Sender<Arc<LiveEvent>>;
+
+/// Clients that fall more than `buffer_size` events behind skip ahead and are sent `lagged`.
+pub fn channel(buffer_size: usize) -> LiveSender {
+    broadcast::channel(buffer_size).0
+}
+
+/// A change announced on `LIVE_CHANNEL`, as sent to stream clients.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LiveEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// One of `schema::LIVE_TOPICS`.
+    pub topic: String,
+    /// Set on events only the merchant's own API keys may see.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merchant_id: Option<uuid::Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dog_id: Option<uuid::Uuid>,
+    pub data: serde_json::Value,
+}
+
+/// What a stream client is sent: an event, word that it fell behind and missed some, or
+/// word that its API key was revoked, after which the stream ends.
+pub enum Delivery {
+    Event(Arc<LiveEvent>),
+    Lagged(u64),
+    Revoked,
+}
+
+impl Delivery {
+    pub fn event_type(&self) -> &str {
+        match self {
+            Delivery::Event(event) => &event.event_type,
+            Delivery::Lagged(_) => "lagged",
+            Delivery::Revoked => "revoked",
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Delivery::Event(event) => json!(event),
+            Delivery::Lagged(missed) => json!({"type": "lagged", "data": {"missed": missed}}),
+            Delivery::Revoked => json!({"type": "revoked", "data": {}}),
+        }
+    }
+}
+
+/// Publishes `event` to every replica's stream clients. Best effort: a failure is logged and
+/// the caller carries on, as nothing depends on a client having seen the event.
+pub async fn publish(db: &Pool<Postgres>, event: &LiveEvent) {
+    let payload = serde_json::to_string(event).unwrap_or_default();
+    if let Err(e) = sqlx::query(r#"SELECT pg_notify($1, $2)"#)
+        .bind(LIVE_CHANNEL)
+        .bind(payload)
+        .execute(db)
+        .await
+    {
+        warn!(error = %e, event_type = %event.event_type, "failed to publish a live event");
+    }
+}
+
+/// Relays `LIVE_CHANNEL` to this replica's stream clients until `shutdown` is cancelled.
+/// Notifications sent while the connection is down are lost; clients are not told.
+pub fn spawn_listener(
+    db: Pool<Postgres>,
+    sender: LiveSender,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut listener = loop {
+            let listener = async {
+                let mut listener = PgListener::connect_with(&db).await?;
+                listener.listen(LIVE_CHANNEL).await?;
+                Ok::<_, sqlx::Error>(listener)
+            };
+            match listener.await {
+                Ok(listener) => break listener,
+                Err(e) => warn!(error = %e, "failed to listen for live events"),
+            }
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = tokio::time::sleep(RETRY_DELAY) => {}
+            }
+        };
+        info!(channel = LIVE_CHANNEL, "listening for live events");
+
+        loop {
+            let notification = tokio::select! {
+                _ = shutdown.cancelled() => return,
+                notification = listener.recv() => notification,
+            };
+            match notification {
+                Ok(notification) => match serde_json::from_str::<LiveEvent>(notification.payload()) {
+                    // Sending only fails when no client is connected.
+                    Ok(event) => {
+                        let _ = sender.send(Arc::new(event));
+                    }
+                    Err(e) => warn!(error = %e, "ignoring a malformed live event"),
+                },
+                // `recv` reconnects on the next call; back off in case the database is down.
+                Err(e) => {
+                    warn!(error = %e, "lost the live event connection");
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+            }
+        }
+    })
+}
+
+/// Which events one stream client is sent.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    key_id: uuid::Uuid,
+    merchant_id: uuid::Uuid,
+    topics: Vec<String>,
+    dog_id: Option<uuid::Uuid>,
+    record_type: Option<String>,
+}
+
+impl Subscription {
+    /// Merchant record and chain head events need `records:read` and are limited to the key's
+    /// own merchant. Dog and proof events concern public data and go to any valid key.
+    pub fn new(auth: &MerchantAuth, opts: EventStreamOptions) -> Result<Subscription, (StatusCode, Json<serde_json::Value>)> {
+        let merchant_topic = |topic: &str| topic == TOPIC_MERCHANT_RECORDS || topic == TOPIC_CHAIN_HEAD;
+        let topics: Vec<String> = match opts.topics.as_deref() {
+            Some(topics) => {
+                let topics: Vec<String> = topics.split(',').map(|topic| topic.trim().to_string()).collect();
+                if topics.iter().any(|topic| merchant_topic(topic)) && !auth.has_scope(SCOPE_RECORDS_READ) {
+                    let error_response = json!({
+                        "status": "fail",
+                        "message": format!("API key is missing the '{}' scope", SCOPE_RECORDS_READ)
+                    });
+                    return Err((StatusCode::FORBIDDEN, Json(error_response)));
+                }
+                topics
+            }
+            None => LIVE_TOPICS
+                .iter()
+                .filter(|topic| auth.has_scope(SCOPE_RECORDS_READ) || !merchant_topic(topic))
+                .map(|topic| topic.to_string())
+                .collect(),
+        };
+        Ok(Subscription {
+            key_id: auth.key_id,
+            merchant_id: auth.merchant_id,
+            topics,
+            dog_id: opts.dog_id,
+            record_type: opts.record_type,
+        })
+    }
+
+    pub fn matches(&self, event: &LiveEvent) -> bool {
+        if !self.topics.contains(&event.topic) {
+            return false;
+        }
+        if event.merchant_id.is_some_and(|merchant_id| merchant_id != self.merchant_id) {
+            return false;
+        }
+        if let Some(dog_id) = self.dog_id {
+            if (event.topic == TOPIC_PROOFS || event.dog_id.is_some()) && event.dog_id != Some(dog_id) {
+                return false;
+            }
+        }
+        if let Some(record_type) = &self.record_type {
+            if event.topic == TOPIC_MERCHANT_RECORDS && event.data["record_type"].as_str() != Some(record_type) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// The events `subscription` lets through, ending when `shutdown` is cancelled. Every
+/// `revalidate` the subscription's API key is checked again; once it has been revoked, which
+/// rotating it also does, `Revoked` is sent and the feed ends.
+pub fn feed(
+    mut receiver: broadcast::Receiver<Arc<LiveEvent>>,
+    subscription: Subscription,
+    db: Pool<Postgres>,
+    revalidate: Duration,
+    shutdown: CancellationToken,
+) -> impl Stream<Item = Delivery> {
+    async_stream::stream! {
+        let mut revalidation = tokio::time::interval_at(tokio::time::Instant::now() + revalidate, revalidate);
+        loop {
+            let received = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = revalidation.tick() => None,
+                received = receiver.recv() => Some(received),
+            };
+            let Some(received) = received else {
+                if key_revoked(&db, subscription.key_id).await {
+                    yield Delivery::Revoked;
+                    break;
+                }
+                continue;
+            };
+            match received {
+                Ok(event) if subscription.matches(&event) => yield Delivery::Event(event),
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => yield Delivery::Lagged(missed),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+/// Whether the API key `key_id` has been revoked. A failed check is logged and the stream kept
+/// open, so a database blip does not disconnect every client.
+async fn key_revoked(db: &Pool<Postgres>, key_id: uuid::Uuid) -> bool {
+    let active = sqlx::query_scalar::<_, bool>(r#"SELECT EXISTS (
+            SELECT 1 FROM merchant_api_keys JOIN merchants ON merchants.id = merchant_api_keys.merchant_id
+            WHERE merchant_api_keys.id = $1 AND merchant_api_keys.revoked_at IS NULL AND merchants.deleted_at IS NULL
+        )"#)
+        .bind(key_id)
+        .fetch_one(db)
+        .await;
+    match active {
+        Ok(active) => !active,
+        Err(e) => {
+            warn!(error = %e, "failed to re-check a live stream's API key");
+            false
+        }
+    }
+}
+
+/// Publishes `proof.status` events for one proof: `started` when created, then `succeeded` or
+/// `failed`. Dropping the guard unfinished, because the request failed or was cancelled or the
+/// prover panicked, counts as `failed`.
+pub struct ProofJob {
+    db: Pool<Postgres>,
+    id: uuid::Uuid,
+    kind: &'static str,
+    dog_id: Option<uuid::Uuid>,
+    finished: bool,
+}
+
+impl ProofJob {
+    /// `kind` is what the proof is for: `signup`, `signin` or `sighting_match`.
+    pub async fn start(db: &Pool<Postgres>, kind: &'static str, dog_id: Option<uuid::Uuid>) -> ProofJob {
+        let job = ProofJob {
+            db: db.clone(),
+            id: uuid::Uuid::new_v4(),
+            kind,
+            dog_id,
+            finished: false,
+        };
+        publish(db, &job.event("started")).await;
+        job
+    }
+
+    pub async fn finish(mut self, succeeded: bool) {
+        self.finished = true;
+        publish(&self.db, &self.event(if succeeded { "succeeded" } else { "failed" })).await;
+    }
+
+    fn event(&self, status: &str) -> LiveEvent {
+        LiveEvent {
+            event_type: "proof.status".to_string(),
+            topic: TOPIC_PROOFS.to_string(),
+            merchant_id: None,
+            dog_id: self.dog_id,
+            data: json!({"job_id": self.id, "kind": self.kind, "status": status, "dog_id": self.dog_id}),
+        }
+    }
+}
+
+impl Drop for ProofJob {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let db = self.db.clone();
+        let event = self.event("failed");
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move { publish(&db, &event).await });
+        }
+    }
+}
@@ -1,55 +1,271 @@
+mod audit;
+mod auth;
+mod bulk;
+mod canonical;
+mod cli;
+mod config;
+mod encoding;
+mod etag;
+mod expiry;
 mod handlers;
-mod model;
+mod health;
+mod idempotency;
+mod live;
+mod maintenance;
+mod migrate;
+mod monitoring;
+mod openapi;
+mod outbox;
+mod ratelimit;
 mod route;
-mod schema;
+mod shutdown;
+mod signing;
+mod telemetry;
 mod zkpgenerate;
 mod sol_connect;
-use crate::handlers::{doghandler, merchanthandler, merchantrecordhandler};
+mod webhook;
+// Shared with the client crate; see `types/`.
+use dogregistry_types::{model, pagination, patch, response, schema, validation};
+use crate::handlers::{audithandler, doghandler, livehandler, lostfoundhandler, merchantapikeyhandler, merchanthandler, merchantrecordhandler, merchantrecordtypehandler, merchantsigningkeyhandler, webhookhandler};
 
-use std::sync::Arc;
+use std::{net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
 
 use axum::http::{
-    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
-    Method, HeaderValue
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_MATCH},
+    HeaderName, Method, HeaderValue
 };
+use clap::Parser;
+use metrics_exporter_prometheus::PrometheusHandle;
+use ratelimit::RateLimiter;
+use cli::{Cli, Command};
+use config::Config;
 use dotenv::dotenv;
-use route::create_router;
-use tower_http::cors::CorsLayer;
+use route::{create_metrics_router, create_router};
+use tower::ServiceBuilder;
+use tower_http::{
+    cors::CorsLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
 
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    ConnectOptions, Pool, Postgres,
+};
 
 pub struct AppState {
     db: Pool<Postgres>,
+    config: Config,
+    metrics: PrometheusHandle,
+    /// Cancelled when shutdown starts.
+    shutdown: CancellationToken,
+    rate_limiter: RateLimiter,
+    /// Events relayed from `live::LIVE_CHANNEL` to this replica's stream clients.
+    live: live::LiveSender,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State for tests on `db`, with nothing running in the background.
+    fn for_tests(db: Pool<Postgres>, config: Config) -> AppState {
+        AppState {
+            rate_limiter: RateLimiter::new(&config.rate_limit, &db),
+            live: live::channel(16),
+            metrics: metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder().handle(),
+            shutdown: CancellationToken::new(),
+            db,
+            config,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
-    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    
-    let pool = match PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&database_url)
-        .await
-    {
-        Ok(pool) => {
-            println!("✅Connection to the database is successful! {:?}", pool);
-            pool
+    let cli = Cli::parse();
+    let config = match Config::load(&cli) {
+        Ok(config) => config,
+        Err(err) => {
+            // Logging is configured by the config, so it is not set up yet.
+            eprintln!("invalid configuration: {}", err);
+            std::process::exit(1);
         }
+    };
+    let _telemetry = match telemetry::init(&config.telemetry) {
+        Ok(guard) => guard,
         Err(err) => {
-            println!("🔥 Failed to connect to the database: {:?}", err);
+            eprintln!("{}", err);
             std::process::exit(1);
         }
     };
+    match serde_json::to_string(&config.redacted()) {
+        Ok(effective) => info!(config = %effective, "effective configuration"),
+        Err(err) => error!(error = %err, "failed to serialize the configuration"),
+    }
+    if let Err(err) = zkpgenerate::configure(&config.prover) {
+        error!(error = %err, "failed to load the prover parameters");
+        std::process::exit(1);
+    }
+
+    // Every statement is a debug event inside the span of the request that ran it.
+    let connect_options = match PgConnectOptions::from_str(&config.database.url) {
+        Ok(options) => options
+            .log_statements(log::LevelFilter::Debug)
+            .log_slow_statements(log::LevelFilter::Warn, Duration::from_secs(1)),
+        Err(err) => {
+            error!(error = %err, "invalid database url");
+            std::process::exit(1);
+        }
+    };
+    // Connections are opened on first use, so an unreachable database shows up in `/readyz`
+    // rather than stopping the process.
+    let pool = PgPoolOptions::new()
+        .max_connections(config.database.max_connections)
+        .min_connections(config.database.min_connections)
+        .acquire_timeout(config.database.acquire_timeout())
+        .connect_lazy_with(connect_options);
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(pool, config).await,
+        Command::Migrate { action } => {
+            if let Err(err) = migrate::run(&pool, action).await {
+                error!(error = %err, "migration failed");
+                std::process::exit(1);
+            }
+            pool.close().await;
+        }
+    }
+}
+
+async fn serve(pool: Pool<Postgres>, config: Config) {
+    let shutdown = CancellationToken::new();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown::signal().await;
+            shutdown.cancel();
+        }
+    });
+
+    if !shutdown::wait_for_database(&pool, shutdown.clone().cancelled_owned()).await {
+        return;
+    }
+    info!("connected to the database");
+    if config.database.migrate_on_startup {
+        if let Err(err) = migrate::MIGRATOR.run(&pool).await {
+            error!(error = %err, "failed to apply migrations");
+            std::process::exit(1);
+        }
+    }
+    if let Err(err) = migrate::check_up_to_date(&pool).await {
+        error!(error = %err, "refusing to serve; run `migrate status` for details");
+        std::process::exit(1);
+    }
+
+    // Origins were checked by `Config::load`, so every one is a valid header value.
+    let origins: Vec<HeaderValue> = config.cors.allowed_origins.iter().map(|origin| origin.parse().unwrap()).collect();
     let cors = CorsLayer::new()
-        .allow_origin("localhost:3000".parse::<HeaderValue>().unwrap())
+        .allow_origin(origins)
         .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
         .allow_credentials(true)
-        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
-    
-        let app = create_router(Arc::new(AppState { db: pool.clone() })).layer(cors);
-
-        println!("🚀 Server started successfully");
-        let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await.unwrap();
-        axum::serve(listener, app).await.unwrap();
-}
\ No newline at end of file
+        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE, IF_MATCH, HeaderName::from_static(auth::API_KEY_HEADER), HeaderName::from_static(idempotency::IDEMPOTENCY_KEY_HEADER), HeaderName::from_static(telemetry::REQUEST_ID_HEADER)])
+        .expose_headers([ETAG, HeaderName::from_static(idempotency::IDEMPOTENT_REPLAYED_HEADER), HeaderName::from_static(telemetry::REQUEST_ID_HEADER)]);
+    let request_id = HeaderName::from_static(telemetry::REQUEST_ID_HEADER);
+    let tracing = ServiceBuilder::new()
+        .layer(SetRequestIdLayer::new(request_id.clone(), MakeRequestUuid))
+        .layer(TraceLayer::new_for_http().make_span_with(telemetry::request_span))
+        .layer(PropagateRequestIdLayer::new(request_id));
+
+    let bind = config.server.bind;
+    let metrics_bind = config.server.metrics_bind;
+    let shutdown_timeout = config.server.shutdown_timeout();
+    let expiry_config = config.expiry.clone();
+    let webhook_config = config.webhooks.clone();
+    let maintenance_config = config.maintenance.clone();
+    let metrics = match monitoring::install() {
+        Ok(metrics) => metrics,
+        Err(err) => {
+            error!(error = %err, "failed to install the metrics recorder");
+            std::process::exit(1);
+        }
+    };
+    let rate_limiter = RateLimiter::new(&config.rate_limit, &pool);
+    let live = live::channel(config.live.buffer_size);
+    let app_state = Arc::new(AppState {
+        db: pool.clone(),
+        config,
+        metrics,
+        shutdown: shutdown.clone(),
+        rate_limiter,
+        live: live.clone(),
+    });
+    let expiry_worker = expiry::spawn_expiry_worker(app_state.clone(), expiry_config);
+    // Stops with shutdown, which also ends every open stream so draining is not held up.
+    live::spawn_listener(pool.clone(), live, shutdown.clone());
+    let webhook_worker = webhook_config
+        .enabled
+        .then(|| webhook::spawn_delivery_worker(app_state.clone(), webhook_config));
+    let maintenance_worker = maintenance_config
+        .enabled
+        .then(|| maintenance::spawn_maintenance_worker(app_state.clone(), maintenance_config));
+
+    let metrics_app = create_metrics_router(app_state.clone());
+    let app = create_router(app_state).layer(tracing).layer(cors);
+
+    let listener = match tokio::net::TcpListener::bind(bind).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!(error = %err, %bind, "failed to bind");
+            std::process::exit(1);
+        }
+    };
+    let metrics_listener = match tokio::net::TcpListener::bind(metrics_bind).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!(error = %err, bind = %metrics_bind, "failed to bind the metrics listener");
+            std::process::exit(1);
+        }
+    };
+    info!(bind = %metrics_bind, "serving metrics");
+    let metrics_server = axum::serve(metrics_listener, metrics_app).with_graceful_shutdown(shutdown.clone().cancelled_owned());
+    let metrics_server = tokio::spawn(async move { metrics_server.await });
+    info!(%bind, docs = openapi::DOCS_PATH, "server started");
+    // Peer addresses are needed for per-IP rate limits.
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned());
+    let mut server = tokio::spawn(async move { server.await });
+
+    // Once shutdown starts, new connections are refused and `/readyz` fails while in-flight
+    // requests, and the proofs they started, get until the deadline to finish.
+    tokio::select! {
+        biased;
+        _ = shutdown.cancelled() => {
+            info!(timeout_secs = shutdown_timeout.as_secs(), "shutting down, draining requests");
+            let deadline = tokio::time::Instant::now() + shutdown_timeout;
+            if tokio::time::timeout_at(deadline, &mut server).await.is_err() {
+                warn!("shutdown deadline passed with requests still in flight");
+                server.abort();
+            }
+            shutdown::drain_prover(deadline).await;
+        }
+        result = &mut server => {
+            if let Ok(Err(err)) = result {
+                error!(error = %err, "server stopped");
+            }
+        }
+    }
+
+    expiry_worker.abort();
+    metrics_server.abort();
+    // A delivery cut off mid-attempt is retried once its claim lapses.
+    if let Some(webhook_worker) = webhook_worker {
+        webhook_worker.abort();
+    }
+    if let Some(maintenance_worker) = maintenance_worker {
+        maintenance_worker.abort();
+    }
+    pool.close().await;
+    info!("shutdown complete");
+}
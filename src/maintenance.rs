@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use tracing::{error, info};
+
+use crate::{
+    config::MaintenanceConfig, idempotency::purge_expired_idempotency_keys,
+    ratelimit::purge_idle_rate_limit_buckets, webhook::purge_delivered_webhooks, AppState,
+};
+
+/// Periodically deletes rows that are only kept for a while: expired idempotency keys, idle
+/// rate limit buckets and delivered webhooks. Merchant record expiry runs on its own schedule
+/// in `expiry`.
+pub fn spawn_maintenance_worker(app_state: Arc<AppState>, config: MaintenanceConfig) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.interval());
+        loop {
+            interval.tick().await;
+            match purge_expired_idempotency_keys(&app_state.db).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "purged expired idempotency keys"),
+                Err(e) => error!(error = %e, "failed to purge idempotency keys"),
+            }
+            match purge_idle_rate_limit_buckets(&app_state.db).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "purged idle rate limit buckets"),
+                Err(e) => error!(error = %e, "failed to purge rate limit buckets"),
+            }
+            match purge_delivered_webhooks(&app_state.db, app_state.config.webhooks.retention_days).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "purged delivered webhooks"),
+                Err(e) => error!(error = %e, "failed to purge delivered webhooks"),
+            }
+        }
+    })
+}
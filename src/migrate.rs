@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migrator},
+    Pool, Postgres,
+};
+
+use crate::cli::MigrateCommand;
+
+/// Every file in `migrations/`, compiled into the binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file has changed since.
+    Modified,
+    /// Recorded as applied by a newer build; this binary does not have the file.
+    Unknown,
+    /// Started but did not finish.
+    Dirty,
+}
+
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// State of every migration this binary knows of, plus any applied ones it does not, by version.
+pub async fn status(db: &Pool<Postgres>) -> Result<Vec<MigrationStatus>, MigrateError> {
+    let mut conn = db.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let dirty = conn.dirty_version().await?;
+    let mut applied: HashMap<i64, Vec<u8>> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| (migration.version, migration.checksum.into_owned()))
+        .collect();
+
+    let mut statuses: Vec<MigrationStatus> = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| {
+            let state = match applied.remove(&migration.version) {
+                _ if dirty == Some(migration.version) => MigrationState::Dirty,
+                Some(checksum) if checksum != *migration.checksum => MigrationState::Modified,
+                Some(_) => MigrationState::Applied,
+                None => MigrationState::Pending,
+            };
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state,
+            }
+        })
+        .collect();
+    statuses.extend(applied.into_keys().map(|version| MigrationStatus {
+        version,
+        description: String::new(),
+        state: MigrationState::Unknown,
+    }));
+    statuses.sort_by_key(|status| status.version);
+    Ok(statuses)
+}
+
+/// Refuses a schema that does not match this binary exactly, so handlers never run
+/// against missing or unexpected columns.
+pub async fn check_up_to_date(db: &Pool<Postgres>) -> Result<(), String> {
+    let statuses = status(db).await.map_err(|e| e.to_string())?;
+    let problems: Vec<String> = statuses
+        .iter()
+        .filter_map(|status| {
+            let problem = match status.state {
+                MigrationState::Applied => return None,
+                MigrationState::Pending => "is pending",
+                MigrationState::Modified => "was changed after it was applied",
+                MigrationState::Unknown => "was applied by a newer build",
+                MigrationState::Dirty => "did not finish",
+            };
+            Some(format!("{} {}", status.version, problem))
+        })
+        .collect();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("database schema is out of date: {}", problems.join(", ")))
+    }
+}
+
+pub async fn run(db: &Pool<Postgres>, command: MigrateCommand) -> Result<(), MigrateError> {
+    match command {
+        MigrateCommand::Up => {
+            MIGRATOR.run(db).await?;
+            tracing::info!("migrations are up to date");
+        }
+        MigrateCommand::Down { to } => {
+            MIGRATOR.undo(db, to).await?;
+            tracing::info!(to, "reverted migrations");
+        }
+        MigrateCommand::Status => {
+            for status in status(db).await? {
+                let label = match status.state {
+                    MigrationState::Applied => "applied",
+                    MigrationState::Pending => "pending",
+                    MigrationState::Modified => "modified",
+                    MigrationState::Unknown => "unknown",
+                    MigrationState::Dirty => "dirty",
+                };
+                println!("{:<16}{:<10}{}", status.version, label, status.description);
+            }
+        }
+    }
+    Ok(())
+}
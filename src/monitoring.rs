@@ -0,0 +1,84 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header::CONTENT_TYPE,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use tracing::warn;
+
+use crate::AppState;
+
+pub const METRICS_PATH: &str = "/metrics";
+
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+// Proving with freshly generated parameters takes tens of seconds.
+const PROVER_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Installs the global recorder that every `counter!`, `gauge!` and `histogram!` reports to.
+pub fn install() -> Result<PrometheusHandle, BuildError> {
+    // The first matching matcher wins, so the prover's buckets come before the general ones.
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Prefix("zkp_".to_string()), PROVER_BUCKETS)?
+        .set_buckets_for_metric(Matcher::Suffix("_duration_seconds".to_string()), LATENCY_BUCKETS)?
+        .install_recorder()
+}
+
+/// Counts requests and times them per route. Added with `route_layer` so `MatchedPath` is
+/// set, which keeps ids in paths from each getting their own series.
+pub async fn track_http(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    counter!("http_requests_total", &labels).increment(1);
+    histogram!("http_request_duration_seconds", &labels).record(start.elapsed().as_secs_f64());
+    response
+}
+
+/// Prometheus text exposition of everything recorded so far. Pool gauges and chain heads are
+/// sampled here, at scrape time, rather than on every acquire or append.
+pub async fn metrics_handler(State(data): State<Arc<AppState>>) -> impl IntoResponse {
+    let size = data.db.size();
+    let idle = data.db.num_idle() as u32;
+    gauge!("db_pool_connections", "state" => "idle").set(idle as f64);
+    gauge!("db_pool_connections", "state" => "in_use").set(size.saturating_sub(idle) as f64);
+    gauge!("db_pool_max_connections").set(data.config.database.max_connections as f64);
+    record_chain_heads(&data).await;
+
+    // `install_recorder` leaves upkeep (draining histogram buffers) to us; scrapes are a fine cadence.
+    data.metrics.run_upkeep();
+    ([(CONTENT_TYPE, "text/plain; version=0.0.4")], data.metrics.render())
+}
+
+/// When each live merchant's chain head last moved, so a stalled chain can be alerted on.
+/// A failed read is logged and the scrape served without them.
+async fn record_chain_heads(data: &AppState) {
+    let heads = sqlx::query_as::<_, (uuid::Uuid, f64)>(
+        r#"SELECT id, EXTRACT(EPOCH FROM last_updated)::float8 FROM merchants WHERE deleted_at IS NULL AND last_updated IS NOT NULL"#
+    )
+    .fetch_all(&data.db)
+    .await;
+    match heads {
+        Ok(heads) => {
+            for (merchant_id, updated) in heads {
+                gauge!("merchant_record_chain_head_timestamp_seconds", "merchant_id" => merchant_id.to_string()).set(updated);
+            }
+        }
+        Err(e) => warn!(error = %e, "failed to read merchant chain heads"),
+    }
+}
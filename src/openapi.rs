@@ -0,0 +1,73 @@
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    IntoParams, Modify, OpenApi,
+};
+
+use crate::{
+    auth::API_KEY_HEADER,
+    response::{ErrorBody, Status},
+};
+
+pub const OPENAPI_PATH: &str = "/openapi.json";
+pub const DOCS_PATH: &str = "/docs";
+
+/// Document-level parts of the spec. Paths and the schemas they use are added by
+/// `route::create_router` from each handler's `#[utoipa::path]`, so the spec is built from
+/// the same list as the routes themselves.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Dog registry API",
+        description = "Dogs, merchants and their signed records, lost-and-found reports, and off-chain ZKP generation."
+    ),
+    modifiers(&SecurityAddon),
+    components(schemas(ErrorBody, Status)),
+    tags(
+        (name = "dogs", description = "Registered dogs"),
+        (name = "lost-found", description = "Lost reports and sightings"),
+        (name = "merchants", description = "Merchants and their API keys, signing keys and record types"),
+        (name = "merchant-records", description = "Signed, time-limited merchant records"),
+        (name = "webhooks", description = "Signed event notifications to merchant endpoints"),
+        (name = "events", description = "Live event stream over SSE and WebSocket"),
+        (name = "audit", description = "Change history"),
+        (name = "zkp", description = "Zero-knowledge proof sign-up and sign-in"),
+    )
+)]
+pub struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+// Headers read by extractors and middleware rather than declared in handler signatures,
+// described here so paths can list them with `params(...)`.
+
+#[derive(IntoParams)]
+#[into_params(parameter_in = Header)]
+#[allow(dead_code)]
+pub struct IfMatchHeader {
+    /// ETag from the last read of the resource, or `*` to overwrite any version.
+    #[param(rename = "If-Match")]
+    if_match: String,
+}
+
+#[derive(IntoParams)]
+#[into_params(parameter_in = Header)]
+#[allow(dead_code)]
+pub struct IdempotencyKeyHeader {
+    /// Makes retries safe: the request runs once and later ones get the stored response.
+    #[param(rename = "Idempotency-Key")]
+    idempotency_key: Option<String>,
+}
@@ -0,0 +1,46 @@
+use sqlx::PgConnection;
+
+/// One row of `outbox_events`, picked up by the webhook worker once its transaction commits.
+pub struct OutboxEvent<'a> {
+    /// One of `schema::WEBHOOK_EVENT_TYPES`.
+    pub event_type: &'a str,
+    /// Only this merchant's subscriptions receive the event; `None` sends it to every subscriber.
+    pub merchant_id: Option<uuid::Uuid>,
+    pub payload: serde_json::Value,
+}
+
+/// Writes `event` on `conn`, which should be the transaction making the change it describes,
+/// so the event is published exactly when the change is.
+#[tracing::instrument(name = "db.enqueue_event", skip_all, fields(event_type = event.event_type), err)]
+pub async fn enqueue(conn: &mut PgConnection, event: OutboxEvent<'_>) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"INSERT INTO outbox_events (event_type, merchant_id, payload) VALUES ($1, $2, $3)"#)
+        .bind(event.event_type)
+        .bind(event.merchant_id)
+        .bind(event.payload)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Like `enqueue`, but subscribers do not see the event until `release` is called with the
+/// returned id: for changes that are only complete once a step outside the database succeeds.
+#[tracing::instrument(name = "db.enqueue_held_event", skip_all, fields(event_type = event.event_type), err)]
+pub async fn enqueue_held(conn: &mut PgConnection, event: OutboxEvent<'_>) -> Result<uuid::Uuid, sqlx::Error> {
+    sqlx::query_scalar::<_, uuid::Uuid>(
+        r#"INSERT INTO outbox_events (event_type, merchant_id, payload, held) VALUES ($1, $2, $3, true) RETURNING id"#
+    )
+    .bind(event.event_type)
+    .bind(event.merchant_id)
+    .bind(event.payload)
+    .fetch_one(conn)
+    .await
+}
+
+/// Lets the webhook worker fan out an event written by `enqueue_held`.
+pub async fn release(conn: &mut PgConnection, event_id: uuid::Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"UPDATE outbox_events SET held = false WHERE id = $1"#)
+        .bind(event_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request, State},
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use metrics::counter;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    auth::{authenticate, extract_api_key, hash_api_key},
+    config::{Bucket, RateLimitBackend, RateLimitConfig, RateLimitPolicy},
+    encoding::to_hex,
+    AppState,
+};
+
+/// Buckets idle this long are full again and can be forgotten.
+const IDLE_BUCKET_TTL: Duration = Duration::from_secs(3600);
+/// In-memory buckets are capped at this many, evicting the least recently used.
+const MAX_MEMORY_BUCKETS: usize = 100_000;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Where token buckets are kept; see `RateLimitBackend`.
+pub enum RateLimiter {
+    Memory(Mutex<MemoryBuckets>),
+    Postgres(Pool<Postgres>),
+}
+
+/// Buckets by key, with their order of last use so the least recently used can be evicted
+/// without scanning the rest.
+#[derive(Default)]
+pub struct MemoryBuckets {
+    buckets: HashMap<String, MemoryBucket>,
+    by_last_use: BTreeMap<u64, String>,
+    next_use: u64,
+}
+
+struct MemoryBucket {
+    tokens: f64,
+    updated: Instant,
+    last_use: u64,
+}
+
+impl MemoryBuckets {
+    /// Refills the bucket for `key`, takes a token if there is one, and returns how many
+    /// tokens there were before taking it.
+    fn take(&mut self, key: &str, bucket: &Bucket, now: Instant) -> f64 {
+        // Idle buckets are full again, so dropping them changes nothing.
+        while let Some((&last_use, oldest)) = self.by_last_use.first_key_value() {
+            let idle = self.buckets.get(oldest).is_none_or(|state| now.duration_since(state.updated) >= IDLE_BUCKET_TTL);
+            if !idle && self.buckets.len() < MAX_MEMORY_BUCKETS {
+                break;
+            }
+            if let Some(oldest) = self.by_last_use.remove(&last_use) {
+                self.buckets.remove(&oldest);
+            }
+        }
+
+        let last_use = self.next_use;
+        self.next_use += 1;
+        let state = self.buckets.entry(key.to_string()).or_insert(MemoryBucket {
+            tokens: bucket.burst as f64,
+            updated: now,
+            last_use,
+        });
+        self.by_last_use.remove(&state.last_use);
+        self.by_last_use.insert(last_use, key.to_string());
+        state.last_use = last_use;
+
+        let elapsed = now.duration_since(state.updated).as_secs_f64();
+        let refilled = (state.tokens + elapsed * bucket.per_second()).min(bucket.burst as f64);
+        state.tokens = if refilled >= 1.0 { refilled - 1.0 } else { refilled };
+        state.updated = now;
+        refilled
+    }
+}
+
+/// Outcome of taking a token: allowed, or denied until `retry_after` has passed.
+enum Decision {
+    Allowed,
+    Denied { retry_after: Duration },
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig, db: &Pool<Postgres>) -> RateLimiter {
+        match config.backend {
+            RateLimitBackend::Memory => RateLimiter::Memory(Mutex::new(MemoryBuckets::default())),
+            RateLimitBackend::Postgres => RateLimiter::Postgres(db.clone()),
+        }
+    }
+
+    async fn take(&self, key: &str, bucket: &Bucket) -> Result<Decision, sqlx::Error> {
+        let tokens = match self {
+            RateLimiter::Memory(buckets) => buckets.lock().unwrap().take(key, bucket, Instant::now()),
+            RateLimiter::Postgres(db) => {
+                sqlx::query(r#"INSERT INTO rate_limit_buckets (key, tokens) VALUES ($1, $2) ON CONFLICT (key) DO NOTHING"#)
+                    .bind(key)
+                    .bind(bucket.burst as f64)
+                    .execute(db)
+                    .await?;
+                // The subquery locks the row, so concurrent replicas take tokens one at a time.
+                sqlx::query_scalar::<_, f64>(
+                    r#"UPDATE rate_limit_buckets b SET
+                        tokens = r.refilled - CASE WHEN r.refilled >= 1 THEN 1 ELSE 0 END,
+                        updated_at = now()
+                    FROM (
+                        SELECT LEAST($2, tokens + EXTRACT(EPOCH FROM now() - updated_at) * $3) AS refilled
+                        FROM rate_limit_buckets WHERE key = $1 FOR UPDATE
+                    ) r
+                    WHERE b.key = $1
+                    RETURNING r.refilled"#
+                )
+                .bind(key)
+                .bind(bucket.burst as f64)
+                .bind(bucket.per_second())
+                .fetch_one(db)
+                .await?
+            }
+        };
+
+        if tokens >= 1.0 {
+            Ok(Decision::Allowed)
+        } else {
+            let retry_after = Duration::from_secs_f64((1.0 - tokens) / bucket.per_second());
+            Ok(Decision::Denied { retry_after })
+        }
+    }
+}
+
+/// Route middleware applying `rate_limit.default` to every API route.
+pub async fn rate_limit_default(State(data): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    enforce(&data, "default", &data.config.rate_limit.default, request, next).await
+}
+
+/// Route middleware applying the stricter `rate_limit.zkp` to routes that prove or hit the chain.
+pub async fn rate_limit_zkp(State(data): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    enforce(&data, "zkp", &data.config.rate_limit.zkp, request, next).await
+}
+
+async fn enforce(data: &AppState, class: &'static str, policy: &RateLimitPolicy, request: Request, next: Next) -> Response {
+    if !data.config.rate_limit.enabled {
+        return next.run(request).await;
+    }
+
+    let (mut request, dog) = match policy.per_dog {
+        Some(_) => match dog_key(request).await {
+            Ok(found) => found,
+            Err(response) => return response,
+        },
+        None => (request, None),
+    };
+    let api_key = match policy.per_api_key {
+        Some(_) => authenticated_key(data, &mut request).await,
+        None => None,
+    };
+    let checks = [
+        ("ip", policy.per_ip, client_ip(&request, &data.config.rate_limit).map(|ip| ip.to_string())),
+        ("api_key", policy.per_api_key, api_key),
+        ("dog", policy.per_dog, dog),
+    ];
+
+    for (dimension, bucket, value) in checks {
+        let (Some(bucket), Some(value)) = (bucket, value) else {
+            continue;
+        };
+        let key = format!("{}:{}:{}", class, dimension, value);
+        match data.rate_limiter.take(&key, &bucket).await {
+            Ok(Decision::Allowed) => {}
+            Ok(Decision::Denied { retry_after }) => {
+                counter!("rate_limited_requests_total", "class" => class, "dimension" => dimension).increment(1);
+                return too_many_requests(retry_after);
+            }
+            // Fail open: an outage of the shared store should not take the API down with it.
+            Err(e) => tracing::warn!(error = %e, "rate limit store unavailable, allowing request"),
+        }
+    }
+    next.run(request).await
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let error_response = json!({
+        "status": "fail",
+        "message": format!("Too many requests, retry in {} seconds", seconds),
+    });
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(error_response)).into_response();
+    response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(seconds));
+    response
+}
+
+/// The hash of the request's API key if it authenticates, so made-up keys cannot each get a
+/// bucket of their own. The principal is kept on the request for the handler's extractors.
+async fn authenticated_key(data: &AppState, request: &mut Request) -> Option<String> {
+    let key = extract_api_key(request.headers()).filter(|key| !key.is_empty())?;
+    let principal = authenticate(data, &key).await.ok()?;
+    request.extensions_mut().insert(principal);
+    Some(hash_api_key(&key))
+}
+
+fn client_ip(request: &Request, config: &RateLimitConfig) -> Option<IpAddr> {
+    if config.trust_forwarded_for {
+        let forwarded = request
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| forwarded_client(value, config.trusted_proxies));
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+    request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip())
+}
+
+/// The `X-Forwarded-For` entry added by the outermost of `trusted_proxies` proxies. Each
+/// proxy appends the address it was reached from, so only the right-most entries can be
+/// trusted; a header with fewer of them did not come through every proxy.
+fn forwarded_client(header: &str, trusted_proxies: usize) -> Option<IpAddr> {
+    let hop = trusted_proxies.checked_sub(1)?;
+    header.rsplit(',').nth(hop).and_then(|ip| ip.trim().parse().ok())
+}
+
+/// The dog a ZKP request is about: `dog_id` when the body has one, else a hash of the
+/// microchip id, which is as identifying and so is never used as a key in the clear.
+/// Reads the body and puts it back for the handler.
+async fn dog_key(request: Request) -> Result<(Request, Option<String>), Response> {
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_BYTES).await.map_err(|_| {
+        let error_response = json!({
+            "status": "fail",
+            "message": "Request body is too large",
+        });
+        (StatusCode::PAYLOAD_TOO_LARGE, Json(error_response)).into_response()
+    })?;
+    let dog = serde_json::from_slice::<serde_json::Value>(&body).ok().and_then(|value| {
+        if let Some(dog_id) = value.get("dog_id").and_then(|v| v.as_str()) {
+            return Some(dog_id.to_string());
+        }
+        value
+            .get("microchip_id")
+            .map(|microchip_id| to_hex(&Sha256::digest(microchip_id.to_string().as_bytes())))
+    });
+    Ok((Request::from_parts(parts, Body::from(body)), dog))
+}
+
+/// Drops buckets nobody has used for an hour; they would be full again anyway.
+#[tracing::instrument(name = "db.purge_idle_rate_limit_buckets", skip_all, err)]
+pub async fn purge_idle_rate_limit_buckets(db: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let purged = sqlx::query(r#"DELETE FROM rate_limit_buckets WHERE updated_at < now() - make_interval(secs => $1)"#)
+        .bind(IDLE_BUCKET_TTL.as_secs() as f64)
+        .execute(db)
+        .await?
+        .rows_affected();
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwarded_client_skips_the_trusted_hops_from_the_right() {
+        let header = "203.0.113.9, 198.51.100.7 , 10.0.0.2";
+        assert_eq!(forwarded_client(header, 1), "10.0.0.2".parse().ok());
+        assert_eq!(forwarded_client(header, 2), "198.51.100.7".parse().ok());
+        assert_eq!(forwarded_client(header, 3), "203.0.113.9".parse().ok());
+        assert_eq!(forwarded_client(header, 4), None);
+        assert_eq!(forwarded_client("not-an-ip", 1), None);
+    }
+}
@@ -1,40 +1,167 @@
 use std::sync::Arc;
 
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use axum::{middleware, routing::get, Router};
+use utoipa::OpenApi;
+use utoipa_axum::{router::OpenApiRouter, routes};
+use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
-    doghandler::{create_dog_handler, delete_dog_handler, dog_list_handler, edit_dog_handler, get_dog_handler}, handlers::zkphandler::{zkp_signin, zkp_signup}, merchanthandler::{create_merchant_handler, delete_merchant_handler, edit_merchant_handler, get_merchant_handler, merchant_list_handler}, merchantrecordhandler::{create_merchant_record_handler, delete_merchant_record_handler, get_merchant_record_handler, merchant_record_list_handler}, AppState
+    audithandler, doghandler, handlers::zkphandler, livehandler,
+    health::{healthz_handler, readyz_handler, HEALTHZ_PATH, READYZ_PATH},
+    idempotency::idempotency, lostfoundhandler, merchantapikeyhandler, merchanthandler, merchantrecordhandler,
+    merchantrecordtypehandler, merchantsigningkeyhandler, webhookhandler,
+    monitoring::{metrics_handler, track_http, METRICS_PATH},
+    openapi::{ApiDoc, DOCS_PATH, OPENAPI_PATH},
+    ratelimit::{rate_limit_default, rate_limit_zkp},
+    AppState,
 };
 
+/// Every route is registered through `routes!`, which takes its method and path from the
+/// handler's `#[utoipa::path]`, so a route cannot exist without being in `/openapi.json`.
 pub fn create_router(app_state: Arc<AppState>) -> Router {
-    Router::new()
-        .route("/api/dogs", get(dog_list_handler))
-        .route("/api/dogs/", post(create_dog_handler))
-        .route(
-            "/api/dogs/:id",
-            get(get_dog_handler)
-            .patch(edit_dog_handler)
-            .delete(delete_dog_handler)
-        )
-        .route("/api/merchants", get(merchant_list_handler))
-        .route("/api/merchants/", post(create_merchant_handler))
-        .route(
-            "/api/merchants/:id",
-            get(get_merchant_handler)
-            .patch(edit_merchant_handler)
-            .delete(delete_merchant_handler)
-        )
-        .route("/api/merchantrecords/by_merchant/:merchant_id", get(merchant_record_list_handler))
-        .route(
-            "/api/merchantrecords/:merchant_record_id", 
-            get(get_merchant_record_handler)
-            .delete(delete_merchant_record_handler)
-        )
-        .route("/api/merchantrecords/", post(create_merchant_record_handler))
-        .route("/api/zkp/signup", post(zkp_signup))
-        .route("/api/zkp/signin/", post(zkp_signin))
+    // Retried creates from flaky clients must not run twice; see `idempotency`.
+    let idempotent = OpenApiRouter::new()
+        .routes(routes!(doghandler::create_dog_handler))
+        .routes(routes!(merchantrecordhandler::create_merchant_record_handler))
+        .layer(middleware::from_fn_with_state(app_state.clone(), idempotency));
+
+    // Proving is expensive and chain submissions cost fees, so these get a stricter budget,
+    // counted before an idempotent replay is served.
+    let zkp = OpenApiRouter::new()
+        .routes(routes!(zkphandler::zkp_signup))
+        .layer(middleware::from_fn_with_state(app_state.clone(), idempotency))
+        .routes(routes!(zkphandler::zkp_signin))
+        .routes(routes!(lostfoundhandler::match_sighting_handler))
+        .layer(middleware::from_fn_with_state(app_state.clone(), rate_limit_zkp));
+
+    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .routes(routes!(doghandler::dog_list_handler))
+        .routes(routes!(doghandler::nearby_dogs_handler))
+        .routes(routes!(doghandler::import_dogs_handler))
+        .routes(routes!(doghandler::export_dogs_handler))
+        .routes(routes!(doghandler::get_dog_handler, doghandler::edit_dog_handler, doghandler::delete_dog_handler))
+        .routes(routes!(doghandler::restore_dog_handler))
+        .routes(routes!(lostfoundhandler::create_lost_report_handler))
+        .routes(routes!(lostfoundhandler::lost_report_list_handler))
+        .routes(routes!(lostfoundhandler::get_lost_report_handler))
+        .routes(routes!(lostfoundhandler::cancel_lost_report_handler))
+        .routes(routes!(lostfoundhandler::reunite_lost_report_handler))
+        .routes(routes!(lostfoundhandler::create_sighting_handler))
+        .routes(routes!(merchanthandler::merchant_list_handler))
+        .routes(routes!(merchanthandler::create_merchant_handler))
+        .routes(routes!(
+            merchanthandler::get_merchant_handler,
+            merchanthandler::edit_merchant_handler,
+            merchanthandler::delete_merchant_handler
+        ))
+        .routes(routes!(merchanthandler::restore_merchant_handler))
+        .routes(routes!(
+            merchantapikeyhandler::merchant_api_key_list_handler,
+            merchantapikeyhandler::create_merchant_api_key_handler
+        ))
+        .routes(routes!(merchantapikeyhandler::revoke_merchant_api_key_handler))
+        .routes(routes!(merchantapikeyhandler::rotate_merchant_api_key_handler))
+        .routes(routes!(
+            merchantsigningkeyhandler::merchant_signing_key_list_handler,
+            merchantsigningkeyhandler::create_merchant_signing_key_handler
+        ))
+        .routes(routes!(merchantsigningkeyhandler::revoke_merchant_signing_key_handler))
+        .routes(routes!(
+            merchantrecordtypehandler::merchant_record_type_list_handler,
+            merchantrecordtypehandler::create_merchant_record_type_handler
+        ))
+        .routes(routes!(merchantrecordhandler::merchant_record_list_handler))
+        .routes(routes!(merchantrecordhandler::export_merchant_records_handler))
+        .routes(routes!(
+            merchantrecordhandler::get_merchant_record_handler,
+            merchantrecordhandler::delete_merchant_record_handler
+        ))
+        .routes(routes!(merchantrecordhandler::renew_merchant_record_handler))
+        .routes(routes!(
+            webhookhandler::webhook_subscription_list_handler,
+            webhookhandler::create_webhook_subscription_handler
+        ))
+        .routes(routes!(webhookhandler::disable_webhook_subscription_handler))
+        .routes(routes!(webhookhandler::webhook_delivery_list_handler))
+        .routes(routes!(webhookhandler::replay_webhook_delivery_handler))
+        .routes(routes!(audithandler::audit_history_handler))
+        .routes(routes!(livehandler::event_stream_handler))
+        .routes(routes!(livehandler::event_socket_handler))
+        .merge(idempotent)
+        .merge(zkp)
+        .split_for_parts();
+
+    // Only API routes are measured and limited; probes and docs would drown them out.
+    router
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), rate_limit_default))
+        .route_layer(middleware::from_fn(track_http))
+        .route(HEALTHZ_PATH, get(healthz_handler))
+        .route(READYZ_PATH, get(readyz_handler))
+        .merge(SwaggerUi::new(DOCS_PATH).url(OPENAPI_PATH, api))
         .with_state(app_state)
-}
\ No newline at end of file
+}
+
+/// `/metrics`, served on `server.metrics_bind` rather than alongside the API, so scrapes are
+/// only possible from where that address is reachable.
+pub fn create_metrics_router(app_state: Arc<AppState>) -> Router {
+    Router::new().route(METRICS_PATH, get(metrics_handler)).with_state(app_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+    };
+    use sqlx::postgres::PgPoolOptions;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::config::Config;
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+    /// State for routing alone: the pool never connects, so handlers that reach the
+    /// database fail fast with 500, which still shows the request was routed.
+    fn routing_state() -> Arc<AppState> {
+        let db = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        Arc::new(AppState::for_tests(db, Config::default()))
+    }
+
+    /// Fails when `/openapi.json` and the router disagree: every documented operation must
+    /// reach the router, and every other method on a documented path must be refused.
+    #[tokio::test]
+    async fn spec_matches_routes() {
+        // Sets apart "no such path" from the 404s handlers return for missing resources.
+        let app = create_router(routing_state()).fallback(|| async { StatusCode::IM_A_TEAPOT });
+
+        let response = app.clone().oneshot(Request::get(OPENAPI_PATH).body(Body::empty()).unwrap()).await.unwrap();
+        let spec: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        let paths = spec["paths"].as_object().unwrap();
+        assert!(!paths.is_empty());
+
+        let id = uuid::Uuid::new_v4().to_string();
+        for (path, operations) in paths {
+            let uri: Vec<&str> = path
+                .split('/')
+                .map(|segment| if segment.starts_with('{') { id.as_str() } else { segment })
+                .collect();
+            let uri = uri.join("/");
+            for method in METHODS {
+                let request = Request::builder().method(method.to_uppercase().as_str()).uri(&uri).body(Body::empty()).unwrap();
+                let status = app.clone().oneshot(request).await.unwrap().status();
+                assert_ne!(status, StatusCode::IM_A_TEAPOT, "{path} is documented but not routed");
+                if operations.get(method).is_some() {
+                    assert_ne!(status, StatusCode::METHOD_NOT_ALLOWED, "{method} {path} is documented but not routed");
+                } else {
+                    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED, "{method} {path} is routed but not documented");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::zkpgenerate;
+
+const PROVER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Resolves on SIGTERM or Ctrl-C.
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!(error = %e, "failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("received Ctrl-C"),
+        _ = terminate => info!("received SIGTERM"),
+    }
+}
+
+/// Waits until no proof is being generated, or `deadline` passes. Returns whether the
+/// prover drained in time.
+pub async fn drain_prover(deadline: Instant) -> bool {
+    while zkpgenerate::queue_depth() > 0 {
+        if Instant::now() >= deadline {
+            warn!(queued = zkpgenerate::queue_depth(), "shutdown deadline passed with proofs still running");
+            return false;
+        }
+        tokio::time::sleep(PROVER_POLL_INTERVAL).await;
+    }
+    true
+}
+
+/// Retries reaching the database until it answers, so a server started before its database
+/// waits rather than exits. Gives up only when `stop` resolves first.
+pub async fn wait_for_database(db: &sqlx::Pool<sqlx::Postgres>, stop: impl std::future::Future<Output = ()>) -> bool {
+    tokio::pin!(stop);
+    let mut delay = Duration::from_millis(500);
+    loop {
+        match db.acquire().await {
+            Ok(_) => return true,
+            Err(e) => warn!(error = %e, retry_in_ms = delay.as_millis() as u64, "database is unreachable"),
+        }
+        tokio::select! {
+            _ = &mut stop => return false,
+            _ = tokio::time::sleep(delay) => {}
+        }
+        delay = (delay * 2).min(Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,157 @@
+//! Minimal Solana client for the registry program: builds a one-instruction legacy
+//! transaction, signs it with the fee payer's keypair and sends it over JSON-RPC.
+//!
+//! The endpoint, keypair and program are read from the environment rather than the app
+//! config, so the same keypair file the Solana CLI uses can be pointed at directly:
+//! `SOLANA_RPC_URL` (default `http://127.0.0.1:8899`), `SOLANA_KEYPAIR` (default
+//! `~/.config/solana/id.json`) and `SOLANA_PROGRAM_ID`. Calls block; run them off the async
+//! workers.
+
+use std::{env, fs, path::PathBuf, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshSerialize;
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{json, Value};
+
+use crate::handlers::zkphandler::ScalarWrapper;
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+const RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Instruction data, in the order the program's entrypoint matches on.
+#[derive(BorshSerialize)]
+enum RegistryInstruction {
+    SignUp { public_input: Vec<ScalarWrapper> },
+    SignIn { proof: Vec<u8>, verifying_key: Vec<u8> },
+}
+
+/// Records a dog's commitment on-chain.
+pub fn user_sign_up(public_input: Vec<ScalarWrapper>) -> Result<(), String> {
+    send(RegistryInstruction::SignUp { public_input })
+}
+
+/// Sends a sign-in proof and the key it verifies against.
+pub fn user_sign_in(proof: Vec<u8>, verifying_key: Vec<u8>) -> Result<(), String> {
+    send(RegistryInstruction::SignIn { proof, verifying_key })
+}
+
+fn send(instruction: RegistryInstruction) -> Result<(), String> {
+    let rpc_url = env::var("SOLANA_RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string());
+    let program_id = env::var("SOLANA_PROGRAM_ID").map_err(|_| "SOLANA_PROGRAM_ID is not set".to_string())?;
+    let program_id = decode_pubkey(&program_id).ok_or("SOLANA_PROGRAM_ID is not a valid public key")?;
+    let payer = load_keypair(&keypair_path())?;
+    let data = borsh::to_vec(&instruction).map_err(|e| format!("failed to encode the instruction: {}", e))?;
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(RPC_TIMEOUT)
+        .build()
+        .map_err(|e| format!("failed to build the Solana RPC client: {}", e))?;
+    let blockhash = rpc(&client, &rpc_url, "getLatestBlockhash", json!([{"commitment": "confirmed"}]))?;
+    let blockhash = blockhash["value"]["blockhash"]
+        .as_str()
+        .and_then(decode_pubkey)
+        .ok_or("getLatestBlockhash returned no blockhash")?;
+
+    let message = message(&payer.verifying_key().to_bytes(), &program_id, &blockhash, &data);
+    let signature = payer.sign(&message);
+    let mut transaction = Vec::with_capacity(1 + 64 + message.len());
+    push_compact_u16(&mut transaction, 1);
+    transaction.extend_from_slice(&signature.to_bytes());
+    transaction.extend_from_slice(&message);
+
+    let encoded = STANDARD.encode(&transaction);
+    rpc(&client, &rpc_url, "sendTransaction", json!([encoded, {"encoding": "base64"}]))?;
+    Ok(())
+}
+
+/// Legacy message with the payer as the only signer and account, calling `program_id` once.
+fn message(payer: &[u8; 32], program_id: &[u8; 32], blockhash: &[u8; 32], data: &[u8]) -> Vec<u8> {
+    // One required signature, no read-only signers, the program is the one read-only account.
+    let mut message = vec![1, 0, 1];
+    push_compact_u16(&mut message, 2);
+    message.extend_from_slice(payer);
+    message.extend_from_slice(program_id);
+    message.extend_from_slice(blockhash);
+    push_compact_u16(&mut message, 1);
+    message.push(1);
+    push_compact_u16(&mut message, 1);
+    message.push(0);
+    push_compact_u16(&mut message, data.len());
+    message.extend_from_slice(data);
+    message
+}
+
+/// Solana's "shortvec" length prefix: 7 bits per byte, high bit set on all but the last.
+fn push_compact_u16(out: &mut Vec<u8>, len: usize) {
+    let mut rest = u16::try_from(len).expect("length does not fit a compact-u16");
+    loop {
+        let byte = (rest & 0x7f) as u8;
+        rest >>= 7;
+        if rest == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn rpc(client: &reqwest::blocking::Client, url: &str, method: &str, params: Value) -> Result<Value, String> {
+    let request = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+    let mut response: Value = client
+        .post(url)
+        .json(&request)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json())
+        .map_err(|e| format!("{} failed: {}", method, e))?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("{} failed: {}", method, error));
+    }
+    Ok(response["result"].take())
+}
+
+fn keypair_path() -> PathBuf {
+    match env::var_os("SOLANA_KEYPAIR") {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(".config/solana/id.json"),
+    }
+}
+
+/// Reads a keypair file as written by `solana-keygen`: a JSON array of the 64 keypair bytes.
+fn load_keypair(path: &PathBuf) -> Result<SigningKey, String> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let bytes: Vec<u8> = serde_json::from_str(&raw).map_err(|e| format!("{} is not a keypair file: {}", path.display(), e))?;
+    let bytes: [u8; 64] = bytes
+        .try_into()
+        .map_err(|_| format!("{} does not hold a 64-byte keypair", path.display()))?;
+    SigningKey::from_keypair_bytes(&bytes).map_err(|e| format!("{} holds an invalid keypair: {}", path.display(), e))
+}
+
+fn decode_pubkey(encoded: &str) -> Option<[u8; 32]> {
+    bs58::decode(encoded).into_vec().ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_u16_matches_the_shortvec_encoding() {
+        for (len, expected) in [(0, vec![0x00]), (0x7f, vec![0x7f]), (0x80, vec![0x80, 0x01]), (0x3fff, vec![0xff, 0x7f]), (0x4000, vec![0x80, 0x80, 0x01])] {
+            let mut out = Vec::new();
+            push_compact_u16(&mut out, len);
+            assert_eq!(out, expected, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn message_lists_the_payer_then_the_program() {
+        let message = message(&[1; 32], &[2; 32], &[3; 32], &[9, 9]);
+        assert_eq!(&message[..4], &[1, 0, 1, 2]);
+        assert_eq!(&message[4..36], &[1; 32]);
+        assert_eq!(&message[36..68], &[2; 32]);
+        assert_eq!(&message[68..100], &[3; 32]);
+        assert_eq!(&message[100..], &[1, 1, 1, 0, 2, 9, 9]);
+    }
+}
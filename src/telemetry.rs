@@ -0,0 +1,82 @@
+use axum::{body::Body, http::Request};
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+use tower_http::request_id::RequestId;
+use tracing::{info_span, Span};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::config::{LogFormat, TelemetryConfig};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Flushes exported spans when dropped; keep it alive for as long as the process runs.
+pub struct TelemetryGuard {
+    provider: Option<TracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("failed to flush spans: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Installs the global subscriber: logs in the configured format, plus OTLP span export
+/// when an endpoint is configured.
+pub fn init(config: &TelemetryConfig) -> Result<TelemetryGuard, String> {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&config.log_filter))
+        .map_err(|e| format!("invalid log filter: {}", e))?;
+    let fmt = match config.log_format {
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().with_current_span(true).with_span_list(false).boxed(),
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().boxed(),
+    };
+
+    let provider = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint)
+                .build()
+                .map_err(|e| format!("failed to create the OTLP exporter: {}", e))?;
+            Some(
+                TracerProvider::builder()
+                    .with_batch_exporter(exporter, runtime::Tokio)
+                    .with_resource(Resource::new([KeyValue::new("service.name", config.service_name.clone())]))
+                    .build(),
+            )
+        }
+        None => None,
+    };
+    let otel = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(config.service_name.clone())));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt)
+        .with(otel)
+        .try_init()
+        .map_err(|e| format!("failed to install the subscriber: {}", e))?;
+    Ok(TelemetryGuard { provider })
+}
+
+/// Span around everything a request does, tagged with the id `SetRequestIdLayer` gave it.
+/// Only the path is recorded: query strings can carry search terms and cursors.
+pub fn request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default();
+    info_span!(
+        "request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        request_id = %request_id,
+    )
+}
@@ -0,0 +1,571 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::http::header::CONTENT_TYPE;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use metrics::{counter, histogram};
+use rand::{rngs::OsRng, RngCore};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect,
+};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::{PgConnection, Pool, Postgres};
+use tokio::{task::JoinSet, time::MissedTickBehavior};
+use tracing::{error, info_span, warn, Instrument};
+
+use crate::{config::WebhookConfig, encoding::to_hex, model::WebhookDeliveryModel, AppState};
+
+/// The event's id: the same on every attempt and replay, so receivers can drop duplicates.
+pub const WEBHOOK_ID_HEADER: &str = "webhook-id";
+/// Unix seconds when the attempt was signed. Receivers should reject stale ones.
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "webhook-timestamp";
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "webhook-signature";
+pub const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+
+const STATUS_PENDING: &str = "pending";
+const STATUS_DELIVERED: &str = "delivered";
+const STATUS_DEAD: &str = "dead";
+
+/// Generates a subscription's signing secret. Unlike API keys it is stored as is, since
+/// every delivery has to be signed with it.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", WEBHOOK_SECRET_PREFIX, to_hex(&bytes))
+}
+
+/// `v1=` followed by the hex HMAC-SHA256 of `"{timestamp}.{body}"`, keyed with the secret.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("v1={}", to_hex(&mac.finalize().into_bytes()))
+}
+
+/// Whether `ip` is on the public internet, rather than loopback, private, link-local or
+/// otherwise reserved.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // 100.64.0.0/10, carrier-grade NAT
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // fc00::/7, unique local
+                    || first & 0xfe00 == 0xfc00
+                    // fe80::/10, link-local
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolves webhook hosts to their public addresses only, so a subscription cannot reach the
+/// internal network through a name that points there. Connections use the addresses checked
+/// here, so the name cannot be re-pointed between the check and the request.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Why `url` may not be delivered to, if it names a non-public address outright. Host names
+/// are checked when `PublicResolver` resolves them.
+fn blocked_target(url: &str) -> Option<String> {
+    let ip = match url::Url::parse(url).ok()?.host()? {
+        url::Host::Ipv4(ip) => IpAddr::V4(ip),
+        url::Host::Ipv6(ip) => IpAddr::V6(ip),
+        url::Host::Domain(_) => return None,
+    };
+    (!is_public(ip)).then(|| format!("{} is not a public address", ip))
+}
+
+/// The client deliveries are sent with. Redirects are not followed, since they could lead
+/// anywhere, and proxies are bypassed so every connection goes through `PublicResolver`.
+fn delivery_client(config: &WebhookConfig) -> reqwest::Result<reqwest::Client> {
+    let builder = reqwest::Client::builder().redirect(redirect::Policy::none()).no_proxy();
+    if config.allow_private_targets {
+        builder.build()
+    } else {
+        builder.dns_resolver(Arc::new(PublicResolver)).build()
+    }
+}
+
+/// A delivery claimed for one attempt, with its subscription and event.
+#[derive(Debug, sqlx::FromRow)]
+struct DueDelivery {
+    id: uuid::Uuid,
+    attempts: i32,
+    url: String,
+    secret: String,
+    event_id: uuid::Uuid,
+    event_type: String,
+    payload: serde_json::Value,
+    event_created_at: DateTime<Utc>,
+}
+
+/// Fans new outbox events out to the subscriptions that want them, then attempts every
+/// delivery that is due. Several replicas can run this at once.
+pub fn spawn_delivery_worker(app_state: Arc<AppState>, config: WebhookConfig) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let http = match delivery_client(&config) {
+            Ok(http) => http,
+            Err(e) => {
+                error!(error = %e, "failed to build the webhook client; webhooks will not be delivered");
+                return;
+            }
+        };
+        let mut interval = tokio::time::interval(config.poll_interval());
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = fan_out(&app_state.db, config.batch_size).await {
+                error!(error = %e, "failed to fan out outbox events");
+            }
+            if let Err(e) = deliver_due(&app_state.db, &http, &config).await {
+                error!(error = %e, "failed to claim webhook deliveries");
+            }
+        }
+    })
+}
+
+/// Creates one pending delivery per matching subscription for each undispatched event.
+#[tracing::instrument(name = "db.fan_out_events", skip(db), err)]
+async fn fan_out(db: &Pool<Postgres>, batch_size: i64) -> Result<u64, sqlx::Error> {
+    let mut total = 0;
+    loop {
+        let dispatched = sqlx::query(
+            r#"WITH events AS (
+                SELECT id, event_type, merchant_id FROM outbox_events
+                WHERE dispatched_at IS NULL AND NOT held
+                ORDER BY created_at
+                LIMIT $1 FOR UPDATE SKIP LOCKED
+            ),
+            deliveries AS (
+                INSERT INTO webhook_deliveries (subscription_id, event_id, event_type)
+                SELECT s.id, e.id, e.event_type
+                FROM events e
+                JOIN webhook_subscriptions s ON s.disabled_at IS NULL
+                    AND (e.merchant_id IS NULL OR s.merchant_id = e.merchant_id)
+                    AND (cardinality(s.event_types) = 0 OR e.event_type = ANY(s.event_types))
+                ON CONFLICT (subscription_id, event_id) DO NOTHING
+            )
+            UPDATE outbox_events SET dispatched_at = now() WHERE id IN (SELECT id FROM events)"#
+        )
+        .bind(batch_size)
+        .execute(db)
+        .await?
+        .rows_affected();
+
+        total += dispatched;
+        if dispatched < batch_size as u64 {
+            return Ok(total);
+        }
+    }
+}
+
+async fn deliver_due(db: &Pool<Postgres>, http: &reqwest::Client, config: &WebhookConfig) -> Result<(), sqlx::Error> {
+    // Claiming moves `next_attempt_at` past the request timeout, so another replica only
+    // retries the delivery if this one dies mid-attempt.
+    let lease = config.timeout() * 2;
+    let due = sqlx::query_as::<_, DueDelivery>(
+        r#"UPDATE webhook_deliveries d SET attempts = d.attempts + 1, next_attempt_at = now() + make_interval(secs => $2)
+        FROM webhook_subscriptions s, outbox_events e
+        WHERE d.id IN (
+            SELECT id FROM webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= now()
+            ORDER BY next_attempt_at
+            LIMIT $1 FOR UPDATE SKIP LOCKED
+        )
+        AND s.id = d.subscription_id AND e.id = d.event_id
+        RETURNING d.id, d.attempts, s.url, s.secret, e.id AS event_id, e.event_type, e.payload, e.created_at AS event_created_at"#
+    )
+    .bind(config.batch_size)
+    .bind(lease.as_secs_f64())
+    .fetch_all(db)
+    .await?;
+
+    let mut attempts = JoinSet::new();
+    for delivery in due {
+        let (db, http, config) = (db.clone(), http.clone(), config.clone());
+        attempts.spawn(async move { attempt(&db, &http, &config, delivery).await });
+    }
+    while attempts.join_next().await.is_some() {}
+    Ok(())
+}
+
+/// POSTs the event once and records how it went: delivered on any 2xx, otherwise retried
+/// after a backoff, or dead-lettered once `max_attempts` is reached. A target that is not a
+/// public address counts as a failure without being contacted.
+async fn attempt(db: &Pool<Postgres>, http: &reqwest::Client, config: &WebhookConfig, delivery: DueDelivery) {
+    let span = info_span!(
+        "webhook.deliver",
+        delivery_id = %delivery.id,
+        event_type = %delivery.event_type,
+        attempt = delivery.attempts
+    );
+    async move {
+        let body = json!({
+            "id": delivery.event_id,
+            "type": delivery.event_type,
+            "created_at": delivery.event_created_at,
+            "data": delivery.payload,
+        });
+        let body = serde_json::to_vec(&body).unwrap_or_default();
+        let timestamp = Utc::now().timestamp();
+
+        let blocked = if config.allow_private_targets { None } else { blocked_target(&delivery.url) };
+        let (status_code, failure) = match blocked {
+            Some(reason) => (None, Some(reason)),
+            None => {
+                let start = Instant::now();
+                let result = http
+                    .post(&delivery.url)
+                    .timeout(config.timeout())
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(WEBHOOK_ID_HEADER, delivery.event_id.to_string())
+                    .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+                    .header(WEBHOOK_SIGNATURE_HEADER, sign(&delivery.secret, timestamp, &body))
+                    .body(body)
+                    .send()
+                    .await;
+                histogram!("webhook_delivery_duration_seconds").record(start.elapsed().as_secs_f64());
+
+                match result {
+                    Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+                    Ok(response) => (Some(response.status().as_u16()), Some(format!("endpoint answered {}", response.status()))),
+                    // The URL is left out: receivers often put a token in it.
+                    Err(e) => (None, Some(e.without_url().to_string())),
+                }
+            }
+        };
+        let status = match failure {
+            None => STATUS_DELIVERED,
+            Some(_) if delivery.attempts >= config.max_attempts => STATUS_DEAD,
+            Some(_) => STATUS_PENDING,
+        };
+        let outcome = if status == STATUS_PENDING { "retrying" } else { status };
+        counter!("webhook_deliveries_total", "event_type" => delivery.event_type.clone(), "outcome" => outcome).increment(1);
+        if let Some(failure) = &failure {
+            warn!(error = %failure, outcome, "webhook delivery failed");
+        }
+
+        let retry_in = config.backoff(delivery.attempts);
+        if let Err(e) = record_attempt(db, delivery.id, status, status_code, failure, retry_in).await {
+            error!(error = %e, "failed to record a webhook delivery attempt");
+        }
+    }
+    .instrument(span)
+    .await
+}
+
+#[tracing::instrument(name = "db.record_webhook_attempt", skip(db, failure), err)]
+async fn record_attempt(
+    db: &Pool<Postgres>,
+    delivery_id: uuid::Uuid,
+    status: &str,
+    status_code: Option<u16>,
+    failure: Option<String>,
+    retry_in: Duration,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE webhook_deliveries SET
+            status = $2,
+            last_status_code = $3,
+            last_error = $4,
+            next_attempt_at = now() + make_interval(secs => $5),
+            delivered_at = CASE WHEN $2 = 'delivered' THEN now() ELSE delivered_at END
+        WHERE id = $1"#
+    )
+    .bind(delivery_id)
+    .bind(status)
+    .bind(status_code.map(|code| code as i16))
+    .bind(failure)
+    .bind(retry_in.as_secs_f64())
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Puts a delivered or dead delivery back in the queue with a fresh set of attempts.
+pub async fn requeue(conn: &mut PgConnection, delivery_id: uuid::Uuid) -> Result<WebhookDeliveryModel, sqlx::Error> {
+    sqlx::query_as::<_, WebhookDeliveryModel>(
+        r#"UPDATE webhook_deliveries SET status = 'pending', attempts = 0, next_attempt_at = now(), delivered_at = NULL WHERE id = $1 RETURNING *"#
+    )
+    .bind(delivery_id)
+    .fetch_one(conn)
+    .await
+}
+
+/// Deletes deliveries that succeeded more than `retention_days` ago, then events dispatched
+/// before then that have no deliveries left. Dead deliveries are kept until replayed.
+#[tracing::instrument(name = "db.purge_delivered_webhooks", skip(db), err)]
+pub async fn purge_delivered_webhooks(db: &Pool<Postgres>, retention_days: i64) -> Result<u64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let purged = sqlx::query(
+        r#"DELETE FROM webhook_deliveries WHERE status = 'delivered' AND delivered_at < now() - make_interval(days => $1)"#
+    )
+    .bind(retention_days as i32)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    sqlx::query(
+        r#"DELETE FROM outbox_events e
+        WHERE dispatched_at < now() - make_interval(days => $1)
+        AND NOT EXISTS (SELECT 1 FROM webhook_deliveries d WHERE d.event_id = e.id)"#
+    )
+    .bind(retention_days as i32)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::{header::LOCATION, HeaderMap, StatusCode, Uri},
+        Router,
+    };
+
+    use super::*;
+    use crate::{
+        migrate::MIGRATOR,
+        outbox::{enqueue, OutboxEvent},
+        schema::EVENT_MERCHANT_RECORD_CREATED,
+    };
+
+    /// What the receiver was sent.
+    struct Received {
+        path: String,
+        headers: HeaderMap,
+        body: Bytes,
+    }
+
+    #[derive(Default)]
+    struct ReceiverState {
+        /// Status codes to answer with, in order; 200 once they run out.
+        answers: Mutex<VecDeque<u16>>,
+        received: Mutex<Vec<Received>>,
+    }
+
+    /// An HTTP server on a local port that records every request it is sent. Redirects point
+    /// at `/redirected`, which is recorded like any other path if followed.
+    async fn receiver(answers: &[u16]) -> (u16, Arc<ReceiverState>) {
+        let state = Arc::new(ReceiverState {
+            answers: Mutex::new(answers.iter().copied().collect()),
+            ..Default::default()
+        });
+        async fn receive(
+            State(state): State<Arc<ReceiverState>>,
+            uri: Uri,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> (StatusCode, [(axum::http::HeaderName, &'static str); 1]) {
+            state.received.lock().unwrap().push(Received { path: uri.path().to_string(), headers, body });
+            let status = state.answers.lock().unwrap().pop_front().unwrap_or(200);
+            (StatusCode::from_u16(status).unwrap(), [(LOCATION, "/redirected")])
+        }
+        let app = Router::new().fallback(receive).with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (port, state)
+    }
+
+    fn config(max_attempts: i32, allow_private_targets: bool) -> WebhookConfig {
+        WebhookConfig { max_attempts, allow_private_targets, ..Default::default() }
+    }
+
+    /// Subscribes a new merchant to every event at `url`, and queues one event for it.
+    async fn subscribe(db: &Pool<Postgres>, url: &str) -> (uuid::Uuid, String) {
+        let merchant_id = sqlx::query_scalar::<_, uuid::Uuid>(r#"INSERT INTO merchants (last_data_hash) VALUES ($1) RETURNING id"#)
+            .bind(uuid::Uuid::new_v4().to_string())
+            .fetch_one(db)
+            .await
+            .unwrap();
+        let secret = generate_secret();
+        sqlx::query(r#"INSERT INTO webhook_subscriptions (merchant_id, url, secret) VALUES ($1, $2, $3)"#)
+            .bind(merchant_id)
+            .bind(url)
+            .bind(&secret)
+            .execute(db)
+            .await
+            .unwrap();
+        let mut conn = db.acquire().await.unwrap();
+        enqueue(&mut conn, OutboxEvent {
+            event_type: EVENT_MERCHANT_RECORD_CREATED,
+            merchant_id: Some(merchant_id),
+            payload: json!({"merchant_record_id": uuid::Uuid::new_v4()}),
+        })
+        .await
+        .unwrap();
+        (merchant_id, secret)
+    }
+
+    async fn run(db: &Pool<Postgres>, config: &WebhookConfig) {
+        fan_out(db, config.batch_size).await.unwrap();
+        deliver_due(db, &delivery_client(config).unwrap(), config).await.unwrap();
+    }
+
+    async fn only_delivery(db: &Pool<Postgres>) -> WebhookDeliveryModel {
+        sqlx::query_as::<_, WebhookDeliveryModel>(r#"SELECT * FROM webhook_deliveries"#)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    async fn deliveries(db: &Pool<Postgres>) -> Vec<WebhookDeliveryModel> {
+        sqlx::query_as::<_, WebhookDeliveryModel>(r#"SELECT * FROM webhook_deliveries ORDER BY created_at"#)
+            .fetch_all(db)
+            .await
+            .unwrap()
+    }
+
+    /// Skips the backoff, so the next run retries everything still pending.
+    async fn make_due(db: &Pool<Postgres>) {
+        sqlx::query(r#"UPDATE webhook_deliveries SET next_attempt_at = now()"#).execute(db).await.unwrap();
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let config = WebhookConfig { backoff_base_secs: 10, backoff_max_secs: 60, ..Default::default() };
+        let secs: Vec<u64> = (1..=5).map(|attempts| config.backoff(attempts).as_secs()).collect();
+        assert_eq!(secs, [10, 20, 40, 60, 60]);
+    }
+
+    #[test]
+    fn only_public_addresses_are_allowed() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fe80::1", "fd00::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip} should be blocked");
+        }
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip} should be allowed");
+        }
+        assert!(blocked_target("http://169.254.169.254/latest/meta-data").is_some());
+        assert!(blocked_target("http://[::1]:8080/hook").is_some());
+        assert!(blocked_target("https://example.com/hook").is_none());
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn signs_deliveries_and_retries_failures_with_backoff(db: Pool<Postgres>) {
+        let (port, receiver) = receiver(&[500]).await;
+        let (_, secret) = subscribe(&db, &format!("http://127.0.0.1:{port}/hook")).await;
+        let config = config(3, true);
+
+        run(&db, &config).await;
+        let delivery = only_delivery(&db).await;
+        assert_eq!((delivery.status.as_str(), delivery.attempts, delivery.last_status_code), ("pending", 1, Some(500)));
+        let retry_in = (delivery.next_attempt_at - Utc::now()).num_seconds();
+        let backoff = config.backoff(1).as_secs() as i64;
+        assert!((backoff - 5..=backoff).contains(&retry_in), "retried in {retry_in}s, not {backoff}s");
+
+        make_due(&db).await;
+        run(&db, &config).await;
+        let delivery = only_delivery(&db).await;
+        assert_eq!((delivery.status.as_str(), delivery.attempts), ("delivered", 2));
+        assert!(delivery.delivered_at.is_some());
+
+        let received = receiver.received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        for request in received.iter() {
+            let header = |name: &str| request.headers[name].to_str().unwrap().to_string();
+            let timestamp: i64 = header(WEBHOOK_TIMESTAMP_HEADER).parse().unwrap();
+            assert_eq!(header(WEBHOOK_SIGNATURE_HEADER), sign(&secret, timestamp, &request.body));
+            assert_eq!(header(WEBHOOK_ID_HEADER), delivery.event_id.to_string());
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!(body["type"], EVENT_MERCHANT_RECORD_CREATED);
+        }
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn dead_letters_after_max_attempts_until_replayed(db: Pool<Postgres>) {
+        let (port, receiver) = receiver(&[500, 503]).await;
+        subscribe(&db, &format!("http://127.0.0.1:{port}/hook")).await;
+        let config = config(2, true);
+
+        run(&db, &config).await;
+        make_due(&db).await;
+        run(&db, &config).await;
+        let delivery = only_delivery(&db).await;
+        assert_eq!((delivery.status.as_str(), delivery.attempts, delivery.last_status_code), ("dead", 2, Some(503)));
+
+        // Dead deliveries stay put however long the worker runs.
+        make_due(&db).await;
+        run(&db, &config).await;
+        assert_eq!(receiver.received.lock().unwrap().len(), 2);
+
+        let mut conn = db.acquire().await.unwrap();
+        let replayed = requeue(&mut conn, delivery.id).await.unwrap();
+        assert_eq!((replayed.status.as_str(), replayed.attempts), ("pending", 0));
+        run(&db, &config).await;
+        let delivery = only_delivery(&db).await;
+        assert_eq!((delivery.status.as_str(), delivery.attempts), ("delivered", 1));
+        assert_eq!(receiver.received.lock().unwrap().len(), 3);
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn does_not_follow_redirects(db: Pool<Postgres>) {
+        let (port, receiver) = receiver(&[307]).await;
+        subscribe(&db, &format!("http://127.0.0.1:{port}/hook")).await;
+
+        run(&db, &config(3, true)).await;
+        let delivery = only_delivery(&db).await;
+        assert_eq!((delivery.status.as_str(), delivery.last_status_code), ("pending", Some(307)));
+        let paths: Vec<String> = receiver.received.lock().unwrap().iter().map(|request| request.path.clone()).collect();
+        assert_eq!(paths, ["/hook"]);
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn refuses_private_targets(db: Pool<Postgres>) {
+        let (port, receiver) = receiver(&[]).await;
+        subscribe(&db, &format!("http://127.0.0.1:{port}/hook")).await;
+        subscribe(&db, &format!("http://localhost:{port}/hook")).await;
+
+        run(&db, &config(3, false)).await;
+        let deliveries = deliveries(&db).await;
+        assert_eq!(deliveries.len(), 2);
+        for delivery in &deliveries {
+            assert_eq!((delivery.status.as_str(), delivery.last_status_code), ("pending", None));
+            assert!(delivery.last_error.is_some());
+        }
+        assert!(receiver.received.lock().unwrap().is_empty());
+    }
+}
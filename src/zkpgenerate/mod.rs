@@ -1,45 +1,139 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    time::Instant,
+};
+
 use bellman::{
     gadgets::
         multipack::{
             bytes_to_bits_le, compute_multipacking
         },
     groth16::{
-        create_random_proof, generate_random_parameters, VerifyingKey, Proof
+        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof, Parameters, VerifyingKey, Proof
     }
 };
 use rand::rngs::OsRng;
 use bls12_381::{Bls12, Scalar};
+use metrics::{gauge, histogram};
 use sha2::{Digest, Sha256};
+use tracing::info_span;
+
+use crate::{config::ProverConfig, encoding::{from_hex, to_hex}};
 mod problem;
 
+static PARAMS: OnceLock<Parameters<Bls12>> = OnceLock::new();
+static QUEUE_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a proof in `zkp_prover_queue_depth` from the moment it is requested until it is done,
+/// so proofs waiting for a CPU show up as well as running ones.
+struct Queued;
+
+impl Queued {
+    fn enter() -> Self {
+        QUEUE_DEPTH.fetch_add(1, Ordering::SeqCst);
+        gauge!("zkp_prover_queue_depth").increment(1.0);
+        Queued
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        QUEUE_DEPTH.fetch_sub(1, Ordering::SeqCst);
+        gauge!("zkp_prover_queue_depth").decrement(1.0);
+    }
+}
+
+/// Proofs requested and not yet finished.
+pub fn queue_depth() -> usize {
+    QUEUE_DEPTH.load(Ordering::SeqCst)
+}
+
+/// Whether proofs use parameters loaded from `prover.params_path`, rather than fresh ones.
+pub fn params_loaded() -> bool {
+    PARAMS.get().is_some()
+}
+
+/// Runs one proving phase, recording its duration under `phase`.
+fn timed<T>(phase: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = info_span!("zkp.phase", phase).in_scope(f);
+    histogram!("zkp_phase_duration_seconds", "phase" => phase).record(start.elapsed().as_secs_f64());
+    result
+}
 
+/// Applies the prover settings. Call once at startup, before the first proof.
+pub fn configure(config: &ProverConfig) -> std::io::Result<()> {
+    if let Some(threads) = config.threads {
+        // bellman sizes its worker pool from this the first time it is used.
+        std::env::set_var("BELLMAN_NUM_CPUS", threads.to_string());
+    }
+    if let Some(path) = &config.params_path {
+        let params = Parameters::<Bls12>::read(BufReader::new(File::open(path)?), true)?;
+        let _ = PARAMS.set(params);
+    }
+    Ok(())
+}
+
+// Spans never carry the inputs: the embedding hash is biometric and the microchip id identifies the dog.
 
 pub fn zkpproof_sign_up(embeddinghash : [u8; 64], microchipid : [u8; 16]) -> Vec<Scalar> {
-    println!("Prepare input...");
+    let _span = info_span!("zkp.commit").entered();
     let input_arr : [u8; 80] = [&embeddinghash[..], &microchipid[..]].concat().try_into().unwrap();
     let hidden_value = input_arr;
-    let hash_bit = bytes_to_bits_le(&Sha256::digest(&hidden_value));
-    let public_input = compute_multipacking::<Scalar>(&hash_bit);
-
-    return public_input
+    timed("commit", || {
+        let hash_bit = bytes_to_bits_le(&Sha256::digest(hidden_value));
+        compute_multipacking::<Scalar>(&hash_bit)
+    })
 }
 
 pub fn zkpproof_sign_in(embeddinghash : [u8; 64], microchipid : [u8; 16]) -> (Proof<Bls12>, VerifyingKey<Bls12>) {
-    println!("Learn zk-SNARKs with Terry");
-    let params = {
-        let c = problem::OurProblem { value: Some([100; 80]) };
-
-        generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+    let _span = info_span!("zkp.prove").entered();
+    let _queued = Queued::enter();
+    let start = Instant::now();
+    let generated;
+    let params = match PARAMS.get() {
+        Some(params) => params,
+        None => {
+            let c = problem::OurProblem { value: Some([100; 80]) };
+            generated = timed("generate_parameters", || {
+                generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+            });
+            &generated
+        }
     };
     let vk = params.vk.clone();
-    println!("Prepare input...");
     let input_arr : [u8; 80] = [&embeddinghash[..], &microchipid[..]].concat().try_into().unwrap();
     let hidden_value = input_arr;
 
     let c = problem::OurProblem {
         value: Some(hidden_value),
     };
-    println!("Create proof...");
-    let proof = create_random_proof(c, &params, &mut OsRng).unwrap();
-    return (proof, vk);
+    let proof = timed("create_proof", || create_random_proof(c, params, &mut OsRng).unwrap());
+    histogram!("zkp_proof_duration_seconds").record(start.elapsed().as_secs_f64());
+    (proof, vk)
+}
+
+/// Checks a sign-in proof against the public input stored for a dog at sign-up.
+pub fn zkpproof_verify(proof: &Proof<Bls12>, vk: &VerifyingKey<Bls12>, public_input: &[Scalar]) -> bool {
+    let _span = info_span!("zkp.verify").entered();
+    let start = Instant::now();
+    let pvk = prepare_verifying_key(vk);
+    let valid = verify_proof(&pvk, proof, public_input).is_ok();
+    histogram!("zkp_verify_duration_seconds", "valid" => valid.to_string()).record(start.elapsed().as_secs_f64());
+    valid
+}
+
+/// Hex of the scalar's canonical little-endian bytes, as stored in `zkptable.public_input`.
+pub fn scalar_to_hex(scalar: &Scalar) -> String {
+    to_hex(&scalar.to_bytes())
+}
+
+pub fn scalar_from_hex(hex: &str) -> Option<Scalar> {
+    let bytes: [u8; 32] = from_hex(hex).ok()?.try_into().ok()?;
+    Option::from(Scalar::from_bytes(&bytes))
 }
@@ -17,11 +17,11 @@ fn impl_sha256<Scalar: PrimeField, CS: ConstraintSystem<Scalar>> (
     mut cs: CS,
     data: &[Boolean]
 ) -> Result<Vec<Boolean>, SynthesisError> {
-    let input: Vec<_> = data.chunks(8).map(|c| c.iter().rev()).flatten().cloned().collect();
+    let input: Vec<_> = data.chunks(8).flat_map(|c| c.iter().rev()).cloned().collect();
 
     let res = sha256(cs.namespace(|| "SHA-256(input)"), &input)?;
 
-    Ok(res.chunks(8).map(|c| c.iter().rev()).flatten().cloned().collect())
+    Ok(res.chunks(8).flat_map(|c| c.iter().rev()).cloned().collect())
 }
 
 pub struct OurProblem {
@@ -31,7 +31,7 @@ pub struct OurProblem {
 impl<Scalar: PrimeField> Circuit<Scalar> for OurProblem {
     fn synthesize<CS: ConstraintSystem<Scalar>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
         let bit_values = if let Some(value) = self.value {
-            value.into_iter().map(|byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8)).flatten().map(|b| Some(b)).collect()
+            value.into_iter().flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8)).map(Some).collect()
         } else {
             vec![None; 80 * 8]
         };
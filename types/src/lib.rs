@@ -0,0 +1,12 @@
+//! Request, response and row types shared by the dog registry server and its client.
+//!
+//! The `server` feature adds what only the server needs: `sqlx` row mapping for the models,
+//! keyset pagination, and the `axum` extractors and error responses for validated bodies,
+//! queries and patches. Without it the crate pulls in neither axum nor sqlx.
+
+pub mod model;
+pub mod pagination;
+pub mod patch;
+pub mod response;
+pub mod schema;
+pub mod validation;
@@ -0,0 +1,223 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct DogModel {
+    pub id: Uuid,
+    pub name: String,
+    pub breed: Option<String>,
+    pub color: String,
+    pub location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub prooflevel: Option<i32>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub version: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+pub struct NearbyDogModel {
+    #[cfg_attr(feature = "server", sqlx(flatten))]
+    #[serde(flatten)]
+    pub dog: DogModel,
+    pub distance_km: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+pub struct FacetCount {
+    pub value: Option<String>,
+    pub count: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct MerchantModel {
+    pub id: Uuid,
+    pub last_data_hash: String,
+    pub last_updated: Option<NaiveDateTime>,
+    pub created_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub version: i64,
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct MerchantRecordModel {
+    pub id: Uuid,
+    pub merchant_id: Uuid,
+    pub data_issued: Option<DateTime<Utc>>,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+    pub prev_data_hash: String,
+    pub data_record: Option<serde_json::Value>,
+    pub record_type: Option<String>,
+    pub data_hash: Option<String>,
+    pub signing_key_id: Option<Uuid>,
+    pub signature: Option<String>,
+    pub status: String,
+    pub expired_at: Option<DateTime<Utc>>,
+    pub supersedes: Option<Uuid>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Version of the signed byte layout the signature covers.
+    pub format_version: i16,
+}
+impl MerchantRecordModel {
+    /// A record is expired once `valid_until` has passed, even before the sweeper has flagged it.
+    pub fn is_expired(&self) -> bool {
+        self.valid_until <= Utc::now()
+    }
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct ZkpModel {
+    pub id: Uuid,
+    pub dog_id: Uuid,
+    pub public_input : Vec<String>,
+    /// `pending` until the commitment is on-chain, then `submitted`.
+    pub submission_status: String,
+    pub enrolled_event_id: Option<Uuid>,
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct MerchantApiKeyModel {
+    pub id: Uuid,
+    pub merchant_id: Uuid,
+    pub key_prefix: String,
+    #[serde(skip_serializing, default)]
+    #[schema(ignore)]
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub rotated_from: Option<Uuid>,
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct MerchantSigningKeyModel {
+    pub id: Uuid,
+    pub merchant_id: Uuid,
+    pub public_key: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct MerchantRecordTypeModel {
+    pub id: Uuid,
+    pub merchant_id: Uuid,
+    pub record_type: String,
+    pub json_schema: serde_json::Value,
+    pub created_at: Option<NaiveDateTime>,
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct LostReportModel {
+    pub id: Uuid,
+    pub dog_id: Uuid,
+    pub owner_merchant_id: Option<Uuid>,
+    pub status: String,
+    pub last_seen_location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub description: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub resolved_at: Option<NaiveDateTime>,
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct SightingModel {
+    pub id: Uuid,
+    pub lost_report_id: Uuid,
+    pub status: String,
+    pub location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub note: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub verified_at: Option<NaiveDateTime>,
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct AuditLogModel {
+    pub id: Uuid,
+    pub actor: String,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub action: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+#[derive(Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct WebhookSubscriptionModel {
+    pub id: Uuid,
+    pub merchant_id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing, default)]
+    #[schema(ignore)]
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+impl std::fmt::Debug for WebhookSubscriptionModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookSubscriptionModel")
+            .field("id", &self.id)
+            .field("merchant_id", &self.merchant_id)
+            .field("url", &self.url)
+            .field("secret", &"[redacted]")
+            .field("event_types", &self.event_types)
+            .field("created_at", &self.created_at)
+            .field("disabled_at", &self.disabled_at)
+            .finish()
+    }
+}
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct WebhookDeliveryModel {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i16>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+#[allow(non_snake_case)]
+pub struct IdempotencyKeyModel {
+    pub caller: String,
+    pub idempotency_key: String,
+    pub request_hash: String,
+    pub response_status: Option<i16>,
+    pub response_headers: Option<serde_json::Value>,
+    pub response_body: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
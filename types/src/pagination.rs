@@ -0,0 +1,359 @@
+#[cfg(feature = "server")]
+use axum::{http::StatusCode, Json};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::schema::SortOrder;
+
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    #[serde(rename = "n")]
+    Next,
+    #[serde(rename = "p")]
+    Prev,
+}
+
+/// Position of a row in a keyset-ordered list. Clients only ever see it as an opaque token.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cursor {
+    /// Sort column the cursor was issued for, so a stale token cannot be reused with another sort.
+    #[serde(rename = "s")]
+    pub sort: String,
+    #[serde(rename = "o")]
+    pub order: SortOrder,
+    /// The row's sort value as Postgres text, or `None` when it was NULL.
+    #[serde(rename = "k")]
+    pub key: Option<String>,
+    #[serde(rename = "i")]
+    pub id: uuid::Uuid,
+    #[serde(rename = "d")]
+    pub direction: Direction,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(token: &str) -> Option<Cursor> {
+        let bytes = URL_SAFE_NO_PAD.decode(token).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// A row fetched together with the values needed to build a cursor pointing at it.
+#[cfg(feature = "server")]
+#[derive(Debug, sqlx::FromRow)]
+pub struct KeysetRow<T> {
+    #[sqlx(flatten)]
+    pub row: T,
+    pub sort_key: Option<String>,
+    pub sort_id: uuid::Uuid,
+}
+
+/// Keyset pagination over `(column, id)` with NULL sort values last.
+///
+/// Handlers select `keyset.select_columns()` next to their row columns, add
+/// `keyset.predicate(k)` to the WHERE clause, order by `keyset.order_by()`, bind
+/// `keyset.cursor_key()`, `keyset.cursor_id()` and `keyset.fetch_limit()`, then turn the
+/// rows into a `Page`.
+#[cfg(feature = "server")]
+pub struct Keyset {
+    column: String,
+    sql_type: &'static str,
+    sort_name: String,
+    order: SortOrder,
+    cursor: Option<Cursor>,
+    limit: usize,
+}
+
+#[cfg(feature = "server")]
+impl Keyset {
+    /// `column` is an SQL expression, `sql_type` the type its text form is cast back to, and
+    /// `sort_name` the public name of the sort recorded in cursors.
+    pub fn new(
+        column: &str,
+        sql_type: &'static str,
+        sort_name: &str,
+        order: SortOrder,
+        cursor: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Self, (StatusCode, Json<serde_json::Value>)> {
+        let cursor = match cursor {
+            None => None,
+            Some(token) => match Cursor::decode(token) {
+                Some(cursor) if cursor.sort == sort_name && cursor.order == order => Some(cursor),
+                Some(_) => {
+                    let error_response = json!({
+                        "status": "fail",
+                        "message": "cursor was issued for a different sort order",
+                    });
+                    return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+                }
+                None => {
+                    let error_response = json!({
+                        "status": "fail",
+                        "message": "Invalid cursor",
+                    });
+                    return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+                }
+            },
+        };
+
+        Ok(Keyset {
+            column: column.to_string(),
+            sql_type,
+            sort_name: sort_name.to_string(),
+            order,
+            cursor,
+            limit: limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        })
+    }
+
+    /// Keyset over a timestamp `column` and `id`, oldest first, with the column as the sort name.
+    pub fn timestamp(
+        column: &str,
+        sql_type: &'static str,
+        cursor: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Self, (StatusCode, Json<serde_json::Value>)> {
+        Keyset::new(column, sql_type, column, SortOrder::Asc, cursor, limit)
+    }
+
+    fn direction(&self) -> Direction {
+        self.cursor.as_ref().map(|c| c.direction).unwrap_or(Direction::Next)
+    }
+
+    /// Whether rows are scanned in ascending order for this request.
+    fn scans_ascending(&self) -> bool {
+        (self.order == SortOrder::Asc) == (self.direction() == Direction::Next)
+    }
+
+    pub fn select_columns(&self) -> String {
+        format!("({})::text AS sort_key, id AS sort_id", self.column)
+    }
+
+    /// WHERE fragment selecting rows after the cursor in scan order. `$key` and `$key + 1`
+    /// must be bound to `cursor_key()` and `cursor_id()`, even when there is no cursor.
+    pub fn predicate(&self, key: usize) -> String {
+        let id = key + 1;
+        let Some(cursor) = &self.cursor else {
+            return format!("(${key}::text IS NULL AND ${id}::uuid IS NULL)");
+        };
+        let col = &self.column;
+        let ty = self.sql_type;
+        let cmp = if self.scans_ascending() { ">" } else { "<" };
+        match (cursor.direction, cursor.key.is_some()) {
+            // NULLs sort last, so walking forward from a value still reaches them.
+            (Direction::Next, true) => format!(
+                "({col} {cmp} ${key}::{ty} OR ({col} = ${key}::{ty} AND id {cmp} ${id}::uuid) OR {col} IS NULL)"
+            ),
+            (Direction::Next, false) => format!(
+                "(${key}::text IS NULL AND {col} IS NULL AND id {cmp} ${id}::uuid)"
+            ),
+            (Direction::Prev, true) => format!(
+                "({col} {cmp} ${key}::{ty} OR ({col} = ${key}::{ty} AND id {cmp} ${id}::uuid))"
+            ),
+            (Direction::Prev, false) => format!(
+                "(${key}::text IS NULL AND ({col} IS NOT NULL OR id {cmp} ${id}::uuid))"
+            ),
+        }
+    }
+
+    pub fn order_by(&self) -> String {
+        let (order, nulls) = if self.direction() == Direction::Next {
+            (self.order.as_sql(), "NULLS LAST")
+        } else {
+            (self.order.reverse().as_sql(), "NULLS FIRST")
+        };
+        format!("{} {} {}, id {}", self.column, order, nulls, order)
+    }
+
+    pub fn cursor_key(&self) -> Option<String> {
+        self.cursor.as_ref().and_then(|c| c.key.clone())
+    }
+
+    pub fn cursor_id(&self) -> Option<uuid::Uuid> {
+        self.cursor.as_ref().map(|c| c.id)
+    }
+
+    /// One extra row tells us whether another page exists.
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit as i64 + 1
+    }
+
+    pub fn page<T>(&self, mut rows: Vec<KeysetRow<T>>) -> Page<T> {
+        let has_more = rows.len() > self.limit;
+        rows.truncate(self.limit);
+        let direction = self.direction();
+        if direction == Direction::Prev {
+            rows.reverse();
+        }
+
+        let cursor_for = |row: &KeysetRow<T>, direction: Direction| {
+            Cursor {
+                sort: self.sort_name.clone(),
+                order: self.order,
+                key: row.sort_key.clone(),
+                id: row.sort_id,
+                direction,
+            }
+            .encode()
+        };
+
+        let next = match direction {
+            Direction::Next if !has_more => None,
+            _ => rows.last().map(|row| cursor_for(row, Direction::Next)),
+        };
+        let prev = match direction {
+            Direction::Next if self.cursor.is_none() => None,
+            Direction::Prev if !has_more => None,
+            _ => rows.first().map(|row| cursor_for(row, Direction::Prev)),
+        };
+
+        Page {
+            items: rows.into_iter().map(|row| row.row).collect(),
+            next,
+            prev,
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+/// `next`/`prev` pair used for both the cursor tokens and the ready-made links of a page.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Default)]
+pub struct Links {
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+#[cfg(feature = "server")]
+impl<T> Page<T> {
+    pub fn cursors(&self) -> Links {
+        Links {
+            next: self.next.clone(),
+            prev: self.prev.clone(),
+        }
+    }
+
+    /// `next`/`prev` links for the response, keeping every query parameter except `cursor`.
+    pub fn links(&self, uri: &axum::http::Uri) -> Links {
+        let link = |cursor: &Option<String>| {
+            cursor.as_ref().map(|cursor| {
+                let mut params: Vec<&str> = uri
+                    .query()
+                    .unwrap_or_default()
+                    .split('&')
+                    .filter(|param| !param.is_empty() && !param.starts_with("cursor="))
+                    .collect();
+                let cursor_param = format!("cursor={}", cursor);
+                params.push(&cursor_param);
+                format!("{}?{}", uri.path(), params.join("&"))
+            })
+        };
+        Links {
+            next: link(&self.next),
+            prev: link(&self.prev),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+
+    fn row(key: Option<&str>, id: u128) -> KeysetRow<u128> {
+        KeysetRow { row: id, sort_key: key.map(str::to_string), sort_id: uuid::Uuid::from_u128(id) }
+    }
+
+    fn cursor(key: Option<&str>, order: SortOrder, direction: Direction) -> String {
+        Cursor { sort: "name".to_string(), order, key: key.map(str::to_string), id: uuid::Uuid::from_u128(7), direction }.encode()
+    }
+
+    fn keyset(order: SortOrder, cursor: Option<&str>) -> Keyset {
+        Keyset::new("name", "text", "name", order, cursor, Some(2)).unwrap()
+    }
+
+    #[test]
+    fn first_page_has_no_cursor_predicate_and_puts_nulls_last() {
+        let keyset = keyset(SortOrder::Asc, None);
+        assert_eq!(keyset.predicate(3), "($3::text IS NULL AND $4::uuid IS NULL)");
+        assert_eq!(keyset.order_by(), "name ASC NULLS LAST, id ASC");
+        assert_eq!(keyset.fetch_limit(), 3);
+    }
+
+    #[test]
+    fn next_pages_reach_the_null_keys() {
+        let from_value = keyset(SortOrder::Asc, Some(&cursor(Some("b"), SortOrder::Asc, Direction::Next)));
+        assert_eq!(
+            from_value.predicate(1),
+            "(name > $1::text OR (name = $1::text AND id > $2::uuid) OR name IS NULL)"
+        );
+        let from_null = keyset(SortOrder::Asc, Some(&cursor(None, SortOrder::Asc, Direction::Next)));
+        assert_eq!(from_null.predicate(1), "($1::text IS NULL AND name IS NULL AND id > $2::uuid)");
+        assert_eq!(from_null.cursor_key(), None);
+        assert_eq!(from_null.cursor_id(), Some(uuid::Uuid::from_u128(7)));
+    }
+
+    #[test]
+    fn descending_order_compares_the_other_way() {
+        let keyset = keyset(SortOrder::Desc, Some(&cursor(Some("b"), SortOrder::Desc, Direction::Next)));
+        assert_eq!(keyset.predicate(1), "(name < $1::text OR (name = $1::text AND id < $2::uuid) OR name IS NULL)");
+        assert_eq!(keyset.order_by(), "name DESC NULLS LAST, id DESC");
+    }
+
+    #[test]
+    fn prev_pages_scan_backwards_and_come_out_in_order() {
+        let keyset = keyset(SortOrder::Asc, Some(&cursor(None, SortOrder::Asc, Direction::Prev)));
+        assert_eq!(keyset.predicate(1), "($1::text IS NULL AND (name IS NOT NULL OR id < $2::uuid))");
+        assert_eq!(keyset.order_by(), "name DESC NULLS FIRST, id DESC");
+
+        // Scanned backwards, with one row more than the limit: there is an earlier page too.
+        let page = keyset.page(vec![row(None, 5), row(Some("c"), 4), row(Some("a"), 3)]);
+        assert_eq!(page.items, vec![4, 5]);
+        assert!(page.prev.is_some());
+        assert!(page.next.is_some());
+    }
+
+    #[test]
+    fn issued_cursors_round_trip() {
+        let first = keyset(SortOrder::Asc, None);
+        let page = first.page(vec![row(Some("a"), 1), row(Some("b"), 2), row(None, 3)]);
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.prev, None);
+
+        let next = keyset(SortOrder::Asc, page.next.as_deref());
+        assert_eq!(next.cursor_key().as_deref(), Some("b"));
+        assert_eq!(next.cursor_id(), Some(uuid::Uuid::from_u128(2)));
+        let last = next.page(vec![row(None, 3)]);
+        assert_eq!(last.items, vec![3]);
+        assert_eq!(last.next, None);
+        assert!(last.prev.is_some());
+    }
+
+    #[test]
+    fn tampered_or_mismatched_cursors_are_rejected() {
+        let mut tampered = cursor(Some("b"), SortOrder::Asc, Direction::Next);
+        tampered.insert(3, '!');
+        let rejected = Keyset::new("name", "text", "name", SortOrder::Asc, Some(&tampered), None).err().unwrap();
+        assert_eq!(rejected.0, StatusCode::BAD_REQUEST);
+
+        let other_order = cursor(Some("b"), SortOrder::Desc, Direction::Next);
+        let rejected = Keyset::new("name", "text", "name", SortOrder::Asc, Some(&other_order), None).err().unwrap();
+        assert_eq!(rejected.0, StatusCode::BAD_REQUEST);
+        let rejected = Keyset::timestamp("created_at", "timestamptz", Some(&other_order), None).err().unwrap();
+        assert_eq!(rejected.0, StatusCode::BAD_REQUEST);
+    }
+}
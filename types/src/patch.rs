@@ -0,0 +1,232 @@
+#[cfg(feature = "server")]
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+#[cfg(feature = "server")]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "server")]
+use serde_json::json;
+#[cfg(feature = "server")]
+use validator::Validate;
+
+#[cfg(feature = "server")]
+use crate::validation::validate;
+
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+pub const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+
+/// One field of an RFC 7396 merge patch: left out, explicitly `null`, or a new value.
+/// Fields using it need `#[serde(default)]` so that a missing key becomes `Absent`, and
+/// `#[serde(skip_serializing_if = "Patch::is_absent")]` so that `Absent` is left out again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Patch<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Patch::Absent)
+    }
+
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// `(set, value)` for a column update of the form `CASE WHEN set THEN value ELSE column END`.
+    pub fn into_update(self) -> (bool, Option<T>) {
+        match self {
+            Patch::Absent => (false, None),
+            Patch::Null => (true, None),
+            Patch::Value(value) => (true, Some(value)),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl<T> Patch<T> {
+    /// For NOT NULL columns: the new value if one was given, refusing an explicit `null`.
+    pub fn required(self, field: &str) -> Result<Option<T>, (StatusCode, Json<serde_json::Value>)> {
+        match self {
+            Patch::Absent => Ok(None),
+            Patch::Value(value) => Ok(Some(value)),
+            Patch::Null => {
+                let error_response = json!({
+                    "status": "fail",
+                    "message": format!("{} cannot be null", field),
+                });
+                Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)))
+            }
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<T>::deserialize(deserializer).map(|value| value.map_or(Patch::Null, Patch::Value))
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value().serialize(serializer)
+    }
+}
+
+/// A PATCH body, chosen by `Content-Type`: an RFC 7396 merge patch
+/// (`application/merge-patch+json`, or plain `application/json`) already shaped as `T`, or an
+/// RFC 6902 JSON Patch (`application/json-patch+json`) still to be applied to the resource.
+#[cfg(feature = "server")]
+pub enum PatchBody<T> {
+    Merge(T),
+    JsonPatch(json_patch::Patch),
+}
+
+/// Applies a JSON Patch to `document`, the resource's editable fields, and reads the result
+/// back as a merge patch that sets every field. A field the patch removed reads back as
+/// `null`, so removing an optional field clears it and removing a required one is refused.
+#[cfg(feature = "server")]
+pub fn apply_json_patch<T: DeserializeOwned + Validate>(
+    document: serde_json::Value,
+    operations: &json_patch::Patch,
+) -> Result<T, (StatusCode, Json<serde_json::Value>)> {
+    let mut patched = document.clone();
+    if let Err(e) = json_patch::patch(&mut patched, operations) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("JSON Patch could not be applied: {}", e),
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+    if let (serde_json::Value::Object(before), serde_json::Value::Object(after)) = (&document, &mut patched) {
+        for field in before.keys() {
+            after.entry(field.clone()).or_insert(serde_json::Value::Null);
+        }
+    }
+    let document = patched;
+    let merge: T = serde_json::from_value(document).map_err(unprocessable)?;
+    validate(&merge)?;
+    Ok(merge)
+}
+
+#[cfg(feature = "server")]
+#[async_trait]
+impl<S, T> FromRequest<S> for PatchBody<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        let bytes = Bytes::from_request(req, state).await.map_err(|e| {
+            let response = e.into_response();
+            let error_response = json!({
+                "status": "fail",
+                "message": "Could not read the request body",
+            });
+            (response.status(), Json(error_response))
+        })?;
+
+        match content_type.as_str() {
+            JSON_PATCH_CONTENT_TYPE => serde_json::from_slice(&bytes)
+                .map(PatchBody::JsonPatch)
+                .map_err(|e| {
+                    let error_response = json!({
+                        "status": "fail",
+                        "message": format!("Invalid JSON Patch document: {}", e),
+                    });
+                    (StatusCode::BAD_REQUEST, Json(error_response))
+                }),
+            MERGE_PATCH_CONTENT_TYPE | "application/json" => {
+                let merge: T = serde_json::from_slice(&bytes).map_err(unprocessable)?;
+                validate(&merge)?;
+                Ok(PatchBody::Merge(merge))
+            }
+            _ => {
+                let error_response = json!({
+                    "status": "fail",
+                    "message": format!("Content-Type must be {} or {}", MERGE_PATCH_CONTENT_TYPE, JSON_PATCH_CONTENT_TYPE),
+                });
+                Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, Json(error_response)))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+fn unprocessable(e: serde_json::Error) -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = json!({
+        "status": "fail",
+        "message": format!("Invalid patch: {}", e),
+    });
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(error_response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize, Debug)]
+    #[cfg_attr(feature = "server", derive(Validate))]
+    struct DogEdit {
+        #[serde(default)]
+        name: Patch<String>,
+        #[serde(default)]
+        note: Patch<String>,
+    }
+
+    #[test]
+    fn missing_keys_are_absent_and_null_is_null() {
+        let edit: DogEdit = serde_json::from_str(r#"{"note": null}"#).unwrap();
+        assert_eq!(edit.name, Patch::Absent);
+        assert_eq!(edit.note, Patch::Null);
+        assert_eq!(edit.note.into_update(), (true, None));
+        assert_eq!(edit.name.into_update(), (false, None));
+
+        let edit: DogEdit = serde_json::from_str(r#"{"name": "Rex"}"#).unwrap();
+        assert_eq!(edit.name, Patch::Value("Rex".to_string()));
+    }
+
+    #[cfg(feature = "server")]
+    fn remove(field: &str) -> DogEdit {
+        let document = json!({"name": "Rex", "note": "Shy with strangers"});
+        let operations: json_patch::Patch = serde_json::from_value(json!([{"op": "remove", "path": format!("/{}", field)}])).unwrap();
+        apply_json_patch(document, &operations).unwrap()
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn json_patch_remove_clears_an_optional_field() {
+        let edit = remove("note");
+        assert_eq!(edit.note, Patch::Null);
+        assert_eq!(edit.name.required("name").unwrap(), Some("Rex".to_string()));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn json_patch_remove_of_a_required_field_is_refused() {
+        let edit = remove("name");
+        let refused = edit.name.required("name").unwrap_err();
+        assert_eq!(refused.0, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(refused.1["message"], "name cannot be null");
+    }
+}
@@ -0,0 +1,266 @@
+#[cfg(feature = "server")]
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    model::{
+        AuditLogModel, DogModel, FacetCount, LostReportModel, MerchantApiKeyModel, MerchantModel,
+        MerchantRecordModel, MerchantRecordTypeModel, MerchantSigningKeyModel, NearbyDogModel, SightingModel,
+        WebhookDeliveryModel, WebhookSubscriptionModel,
+    },
+    pagination::Links,
+    schema::RecordStatus,
+};
+
+/// `status` of every JSON response: `fail` for client errors, `error` for server errors.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Success,
+    Fail,
+    Error,
+}
+
+/// `{"status": "success", "data": {...}}`, the envelope around single resources.
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct Envelope<T> {
+    pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub data: T,
+}
+
+#[cfg(feature = "server")]
+impl<T> Envelope<T> {
+    pub fn success(data: T) -> Json<Envelope<T>> {
+        Json(Envelope { status: Status::Success, message: None, data })
+    }
+}
+
+/// Body of every 4xx and 5xx response. `errors` lists validation failures per field.
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct ErrorBody {
+    pub status: Status,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errors: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct DogData {
+    pub dog: DogModel,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantData {
+    pub merchant: MerchantModel,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantRecordData {
+    pub merchant_record: MerchantRecordModel,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantRecordStatusData {
+    pub merchant_record: MerchantRecordModel,
+    pub record_status: RecordStatus,
+    pub superseded_by: Option<uuid::Uuid>,
+    /// Only set for expired records: whether the record was read from the archive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantRecordTypeData {
+    pub record_type: MerchantRecordTypeModel,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantSigningKeyData {
+    pub signing_key: MerchantSigningKeyModel,
+}
+
+/// `key` is the plaintext API key. It is only ever returned here, when the key is issued.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MerchantApiKeyData {
+    pub api_key: MerchantApiKeyModel,
+    pub key: String,
+}
+
+impl std::fmt::Debug for MerchantApiKeyData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MerchantApiKeyData")
+            .field("api_key", &self.api_key)
+            .field("key", &"[redacted]")
+            .finish()
+    }
+}
+
+/// `secret` signs every delivery to this subscription. It is only ever returned here, when
+/// the subscription is created.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct WebhookSubscriptionData {
+    pub subscription: WebhookSubscriptionModel,
+    pub secret: String,
+}
+
+impl std::fmt::Debug for WebhookSubscriptionData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookSubscriptionData")
+            .field("subscription", &self.subscription)
+            .field("secret", &"[redacted]")
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct WebhookDeliveryData {
+    pub delivery: WebhookDeliveryModel,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct LostReportData {
+    pub lost_report: LostReportModel,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct LostReportDetailData {
+    pub lost_report: LostReportModel,
+    pub sightings: Vec<SightingModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct SightingData {
+    pub sighting: SightingModel,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct SightingMatchData {
+    pub verified: bool,
+    pub lost_report: LostReportModel,
+    pub sighting: SightingModel,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct ZkpData {
+    pub zkp: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct DogFacets {
+    pub breed: Vec<FacetCount>,
+    pub color: Vec<FacetCount>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct DogList {
+    pub status: Status,
+    pub results: usize,
+    /// Dogs matching the filters across all pages.
+    pub total: i64,
+    pub links: Links,
+    pub cursors: Links,
+    pub dogs: Vec<DogModel>,
+    pub facets: DogFacets,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct NearbyDogList {
+    pub status: Status,
+    pub results: usize,
+    pub radius_km: f64,
+    pub dogs: Vec<NearbyDogModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantList {
+    pub status: Status,
+    pub results: usize,
+    pub links: Links,
+    pub cursors: Links,
+    pub merchants: Vec<MerchantModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantRecordList {
+    pub status: Status,
+    pub results: usize,
+    pub links: Links,
+    pub cursors: Links,
+    #[serde(rename = "merchant records")]
+    pub merchant_records: Vec<MerchantRecordModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantApiKeyList {
+    pub status: Status,
+    pub results: usize,
+    pub api_keys: Vec<MerchantApiKeyModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantSigningKeyList {
+    pub status: Status,
+    pub results: usize,
+    pub signing_keys: Vec<MerchantSigningKeyModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct MerchantRecordTypeList {
+    pub status: Status,
+    pub results: usize,
+    pub record_types: Vec<MerchantRecordTypeModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct WebhookSubscriptionList {
+    pub status: Status,
+    pub results: usize,
+    pub subscriptions: Vec<WebhookSubscriptionModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct WebhookDeliveryList {
+    pub status: Status,
+    pub results: usize,
+    pub links: Links,
+    pub cursors: Links,
+    pub deliveries: Vec<WebhookDeliveryModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct LostReportList {
+    pub status: Status,
+    pub results: usize,
+    pub links: Links,
+    pub cursors: Links,
+    pub lost_reports: Vec<LostReportModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct AuditLogList {
+    pub status: Status,
+    pub results: usize,
+    pub links: Links,
+    pub cursors: Links,
+    pub audit_log: Vec<AuditLogModel>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub message: String,
+}
+
+/// Outcome of a bulk import. `status` is `fail` when the body stopped being readable part
+/// way through; rows read before that are still imported.
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct ImportReport {
+    pub status: Status,
+    pub message: Option<String>,
+    pub imported: u64,
+    pub failed: u64,
+    pub errors: Vec<ImportRowError>,
+}
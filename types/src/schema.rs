@@ -0,0 +1,469 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::{
+    patch::Patch,
+    validation::{hex, known_event_types, known_scopes, known_topics, lost_report_status, patch_hex, webhook_url},
+};
+
+pub const LOST_REPORT_STATUSES: [&str; 4] = ["lost", "found", "reunited", "cancelled"];
+
+pub const SCOPE_RECORDS_READ: &str = "records:read";
+pub const SCOPE_RECORDS_WRITE: &str = "records:write";
+pub const SCOPE_WEBHOOKS: &str = "webhooks";
+/// Manage the merchant's API keys, signing keys and record types.
+pub const SCOPE_ADMIN: &str = "admin";
+pub const KNOWN_SCOPES: [&str; 4] = [SCOPE_RECORDS_READ, SCOPE_RECORDS_WRITE, SCOPE_WEBHOOKS, SCOPE_ADMIN];
+/// Scopes of a new API key that does not ask for any; `admin` has to be asked for.
+pub const DEFAULT_SCOPES: [&str; 3] = [SCOPE_RECORDS_READ, SCOPE_RECORDS_WRITE, SCOPE_WEBHOOKS];
+
+pub const EVENT_DOG_ENROLLED: &str = "dog.enrolled";
+pub const EVENT_ZKP_SIGNIN_SUCCEEDED: &str = "zkp.signin.succeeded";
+pub const EVENT_ZKP_SIGNIN_FAILED: &str = "zkp.signin.failed";
+pub const EVENT_MERCHANT_RECORD_CREATED: &str = "merchant_record.created";
+pub const EVENT_MERCHANT_RECORD_EXPIRED: &str = "merchant_record.expired";
+/// Events a webhook can subscribe to. There is no ownership-transfer event yet: the service
+/// has no transfer flow to emit one from.
+pub const WEBHOOK_EVENT_TYPES: [&str; 5] = [
+    EVENT_DOG_ENROLLED,
+    EVENT_ZKP_SIGNIN_SUCCEEDED,
+    EVENT_ZKP_SIGNIN_FAILED,
+    EVENT_MERCHANT_RECORD_CREATED,
+    EVENT_MERCHANT_RECORD_EXPIRED,
+];
+
+const REDACTED: &str = "[redacted]";
+
+#[derive(Serialize, Deserialize, Debug, Default, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FilterOptions {
+    #[validate(length(max = 1024))]
+    pub cursor: Option<String>,        // Opaque token from a previous page's next/prev link
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportOptions {
+    pub format: Option<ExportFormat>,   // csv (default), ndjson or parquet
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DogFilterOptions {
+    #[validate(length(max = 1024))]
+    pub cursor: Option<String>,        // Opaque token from a previous page's next/prev link
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+    #[validate(length(max = 200))]
+    pub q: Option<String>,              // Full-text search over name and breed
+    #[validate(length(max = 255))]
+    pub breed: Option<String>,
+    #[validate(length(max = 255))]
+    pub color: Option<String>,
+    #[validate(length(max = 255))]
+    pub location: Option<String>,
+    #[validate(range(min = 0))]
+    pub prooflevel_min: Option<i32>,
+    #[validate(range(min = 0))]
+    pub prooflevel_max: Option<i32>,
+    pub sort: Option<DogSortField>,
+    pub order: Option<SortOrder>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DogSortField {
+    Name,
+    Breed,
+    Color,
+    Location,
+    Prooflevel,
+    CreatedAt,
+    UpdatedAt,
+    Relevance,                          // Only meaningful together with `q`
+}
+
+impl DogSortField {
+    pub fn column(&self) -> &'static str {
+        match self {
+            DogSortField::Name => "name",
+            DogSortField::Breed => "breed",
+            DogSortField::Color => "color",
+            DogSortField::Location => "location",
+            DogSortField::Prooflevel => "prooflevel",
+            DogSortField::CreatedAt => "created_at",
+            DogSortField::UpdatedAt => "updated_at",
+            DogSortField::Relevance => "ts_rank(search_vector, websearch_to_tsquery('simple', $1))",
+        }
+    }
+
+    /// Postgres type a cursor's text sort key is cast back to.
+    pub fn sql_type(&self) -> &'static str {
+        match self {
+            DogSortField::Prooflevel => "int",
+            DogSortField::CreatedAt | DogSortField::UpdatedAt => "timestamp",
+            DogSortField::Relevance => "real",
+            _ => "text",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DogSortField::Name => "name",
+            DogSortField::Breed => "breed",
+            DogSortField::Color => "color",
+            DogSortField::Location => "location",
+            DogSortField::Prooflevel => "prooflevel",
+            DogSortField::CreatedAt => "created_at",
+            DogSortField::UpdatedAt => "updated_at",
+            DogSortField::Relevance => "relevance",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+
+    pub fn reverse(&self) -> SortOrder {
+        match self {
+            SortOrder::Asc => SortOrder::Desc,
+            SortOrder::Desc => SortOrder::Asc,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NearbyOptions {
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub lat: f64,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub lon: f64,
+    #[validate(range(exclusive_min = 0.0, max = 500.0))]
+    pub radius_km: Option<f64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct CreateDogSchema {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(length(max = 255))]
+    pub breed: String,
+    #[validate(length(min = 1, max = 255))]
+    pub color: String,
+    #[validate(length(max = 255))]
+    pub location: String,               // Human-readable label, e.g. a geocoded address
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: Option<f64>,
+    #[validate(range(min = 0))]
+    pub prooflevel: i32,
+}
+
+/// Merge patch for a dog: absent fields are kept, `null` clears a nullable field.
+#[derive(Serialize, Deserialize, Debug, Default, Validate, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateDogSchema {
+    #[validate(length(min = 1, max = 255))]
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "Patch::is_absent")]
+    pub name: Patch<String>,
+    #[validate(length(max = 255))]
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "Patch::is_absent")]
+    pub breed: Patch<String>,
+    #[validate(length(min = 1, max = 255))]
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "Patch::is_absent")]
+    pub color: Patch<String>,
+    #[validate(length(max = 255))]
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "Patch::is_absent")]
+    pub location: Patch<String>,
+    #[validate(range(min = -90.0, max = 90.0))]
+    #[schema(value_type = Option<f64>)]
+    #[serde(skip_serializing_if = "Patch::is_absent")]
+    pub latitude: Patch<f64>,           // Set or cleared together with longitude
+    #[validate(range(min = -180.0, max = 180.0))]
+    #[schema(value_type = Option<f64>)]
+    #[serde(skip_serializing_if = "Patch::is_absent")]
+    pub longitude: Patch<f64>,
+    #[validate(range(min = 0))]
+    #[schema(value_type = Option<i32>)]
+    #[serde(skip_serializing_if = "Patch::is_absent")]
+    pub prooflevel: Patch<i32>,
+}
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct CreateMerchantSchema {
+    #[validate(length(min = 1, max = 255), custom(function = "hex"))]
+    pub last_data_hash: String
+}
+#[derive(Serialize, Deserialize, Debug, Default, Validate, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateMerchantSchema {
+    #[validate(length(min = 1, max = 255), custom(function = "patch_hex"))]
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "Patch::is_absent")]
+    pub last_data_hash: Patch<String>
+}
+#[derive(Serialize, Deserialize, Debug, Default, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MerchantRecordFilterOptions {
+    #[validate(length(max = 1024))]
+    pub cursor: Option<String>,        // Opaque token from a previous page's next/prev link
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+    #[validate(length(max = 100))]
+    pub record_type: Option<String>,
+    #[validate(length(max = 4096))]
+    pub contains: Option<String>,       // JSON object matched against data_record with @>
+    pub status: Option<RecordStatus>,
+}
+#[derive(Serialize, Deserialize, Debug, Default, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecordStatusFilter {
+    pub status: Option<RecordStatus>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordStatus {
+    Active,
+    Expired,
+}
+impl RecordStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordStatus::Active => "active",
+            RecordStatus::Expired => "expired",
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct CreateMerchantRecordSchema {
+    pub id: uuid::Uuid,
+    pub merchant_id: uuid::Uuid,                 // Foreign key to merchants(id)
+    pub valid_from: chrono::DateTime<chrono::Utc>,          // RFC 3339, signed; may be in the past for imports
+    pub valid_until: chrono::DateTime<chrono::Utc>,         // RFC 3339, any offset is normalised to UTC
+    #[validate(length(min = 1, max = 255), custom(function = "hex"))]
+    pub prev_data_hash: String,         // Must equal the merchant's current last_data_hash
+    #[validate(length(min = 1, max = 100))]
+    pub record_type: String,            // Registered in merchant_record_types for this merchant
+    pub data_record: serde_json::Value,
+    pub signing_key_id: uuid::Uuid,
+    #[validate(length(equal = 128), custom(function = "hex"))]
+    pub signature: String,              // Hex-encoded Ed25519 signature over the record's canonical bytes
+}
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct RenewMerchantRecordSchema {
+    pub id: uuid::Uuid,
+    pub valid_from: chrono::DateTime<chrono::Utc>,
+    pub valid_until: chrono::DateTime<chrono::Utc>,
+    #[validate(length(min = 1, max = 255), custom(function = "hex"))]
+    pub prev_data_hash: String,
+    pub signing_key_id: uuid::Uuid,
+    #[validate(length(equal = 128), custom(function = "hex"))]
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct ZkpSignUpSchema {
+    pub dog_id : uuid::Uuid,
+    #[validate(length(equal = 64), custom(function = "hex"))]
+    pub embedding_hash : String,
+    pub microchip_id : u128
+}
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct ZkpSignInSchema {
+    #[validate(length(equal = 64), custom(function = "hex"))]
+    pub embedding_hash : String,
+    pub microchip_id : u128
+}
+
+// The embedding hash is biometric and the microchip id identifies the dog, so neither may
+// reach a log through `{:?}`.
+
+impl std::fmt::Debug for ZkpSignUpSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZkpSignUpSchema")
+            .field("dog_id", &self.dog_id)
+            .field("embedding_hash", &REDACTED)
+            .field("microchip_id", &REDACTED)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for ZkpSignInSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZkpSignInSchema")
+            .field("embedding_hash", &REDACTED)
+            .field("microchip_id", &REDACTED)
+            .finish()
+    }
+}
+#[derive(Serialize, Deserialize, Debug, Default, Validate, ToSchema)]
+pub struct CreateMerchantApiKeySchema {
+    #[validate(length(min = 1), custom(function = "known_scopes"))]
+    pub scopes: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct CreateWebhookSubscriptionSchema {
+    #[validate(length(min = 1, max = 2048), custom(function = "webhook_url"))]
+    pub url: String,                    // http(s) endpoint receiving signed POSTs
+    #[validate(custom(function = "known_event_types"))]
+    pub event_types: Option<Vec<String>>,   // Omitted or empty subscribes to every event
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WebhookDeliveryFilterOptions {
+    #[validate(length(max = 1024))]
+    pub cursor: Option<String>,        // Opaque token from a previous page's next/prev link
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+    pub status: Option<DeliveryStatus>, // `dead` lists the dead-letter queue
+}
+
+pub const TOPIC_MERCHANT_RECORDS: &str = "merchant_records";
+pub const TOPIC_CHAIN_HEAD: &str = "chain_head";
+pub const TOPIC_PROOFS: &str = "proofs";
+pub const TOPIC_DOGS: &str = "dogs";
+/// Topics of the live event stream.
+pub const LIVE_TOPICS: [&str; 4] = [TOPIC_MERCHANT_RECORDS, TOPIC_CHAIN_HEAD, TOPIC_PROOFS, TOPIC_DOGS];
+
+#[derive(Serialize, Deserialize, Debug, Default, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventStreamOptions {
+    #[validate(length(min = 1, max = 255), custom(function = "known_topics"))]
+    pub topics: Option<String>,         // Comma-separated; defaults to every topic the API key may see
+    pub dog_id: Option<uuid::Uuid>,     // Only this dog's updates and proofs
+    #[validate(length(max = 100))]
+    pub record_type: Option<String>,    // Only merchant records of this type
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Dead,
+}
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Dead => "dead",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct CreateMerchantSigningKeySchema {
+    #[validate(length(equal = 64), custom(function = "hex"))]
+    pub public_key: String,             // Hex-encoded 32-byte Ed25519 public key
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct CreateMerchantRecordTypeSchema {
+    #[validate(length(min = 1, max = 100))]
+    pub record_type: String,
+    pub json_schema: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Validate, ToSchema)]
+pub struct CreateLostReportSchema {
+    #[validate(length(max = 255))]
+    pub last_seen_location: Option<String>,   // Defaults to the dog's current location label
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: Option<f64>,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LostReportFilterOptions {
+    #[validate(length(max = 1024))]
+    pub cursor: Option<String>,        // Opaque token from a previous page's next/prev link
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+    #[validate(custom(function = "lost_report_status"))]
+    pub status: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct CreateSightingSchema {
+    #[validate(length(max = 255))]
+    pub location: Option<String>,
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: Option<f64>,
+    #[validate(length(max = 2000))]
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct SightingMatchSchema {
+    #[validate(length(equal = 64), custom(function = "hex"))]
+    pub embedding_hash : String,                // From the finder's photo scan
+    pub microchip_id : u128                     // From the finder's chip scan
+}
+
+impl std::fmt::Debug for SightingMatchSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SightingMatchSchema")
+            .field("embedding_hash", &REDACTED)
+            .field("microchip_id", &REDACTED)
+            .finish()
+    }
+}
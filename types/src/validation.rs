@@ -0,0 +1,206 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "server")]
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequest, FromRequestParts, Query, Request},
+    http::{request::Parts, StatusCode},
+    Json,
+};
+#[cfg(feature = "server")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "server")]
+use serde_json::json;
+#[cfg(feature = "server")]
+use validator::{Validate, ValidationErrors, ValidationErrorsKind};
+use validator::{ValidateLength, ValidateRange, ValidationError};
+
+use crate::{
+    patch::Patch,
+    schema::{KNOWN_SCOPES, LIVE_TOPICS, LOST_REPORT_STATUSES, WEBHOOK_EVENT_TYPES},
+};
+
+/// A JSON body that has been deserialized and passed its `Validate` rules. Malformed JSON and
+/// rule violations are both answered with 422 before the handler runs; a body that is not
+/// JSON or could not be read keeps its own status, such as 415 or 413.
+#[cfg(feature = "server")]
+pub struct ValidatedJson<T>(pub T);
+
+/// Query parameters that have passed their `Validate` rules. Unparseable parameters are a
+/// 400, rule violations a 422.
+#[cfg(feature = "server")]
+pub struct ValidatedQuery<T>(pub T);
+
+#[cfg(feature = "server")]
+#[async_trait]
+impl<S, T> FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await.map_err(|rejection| {
+            let status = match &rejection {
+                JsonRejection::JsonDataError(_) | JsonRejection::JsonSyntaxError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => rejection.status(),
+            };
+            let error_response = json!({
+                "status": "fail",
+                "message": rejection.body_text(),
+            });
+            (status, Json(error_response))
+        })?;
+        validate(&value)?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+#[cfg(feature = "server")]
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ValidatedQuery<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state).await.map_err(|rejection| {
+            let error_response = json!({
+                "status": "fail",
+                "message": rejection.body_text(),
+            });
+            (StatusCode::BAD_REQUEST, Json(error_response))
+        })?;
+        validate(&value)?;
+        Ok(ValidatedQuery(value))
+    }
+}
+
+/// Runs `value`'s rules, turning violations into a 422 listing the errors per field.
+#[cfg(feature = "server")]
+pub fn validate<T: Validate>(value: &T) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    value.validate().map_err(|errors| {
+        let error_response = json!({
+            "status": "fail",
+            "message": "Validation failed",
+            "errors": field_errors(&errors),
+        });
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(error_response))
+    })
+}
+
+/// `{"field": [{"code", "message", "params"}], "nested": {...}, "list": {"0": {...}}}`.
+#[cfg(feature = "server")]
+fn field_errors(errors: &ValidationErrors) -> serde_json::Value {
+    let fields: serde_json::Map<String, serde_json::Value> = errors
+        .errors()
+        .iter()
+        .map(|(field, kind)| {
+            let details = match kind {
+                ValidationErrorsKind::Field(errors) => errors
+                    .iter()
+                    .map(|error| {
+                        json!({
+                            "code": error.code,
+                            "message": error.message.clone().unwrap_or_else(|| Cow::Owned(format!("failed the '{}' rule", error.code))),
+                            "params": error.params,
+                        })
+                    })
+                    .collect(),
+                ValidationErrorsKind::Struct(errors) => field_errors(errors),
+                ValidationErrorsKind::List(errors) => errors
+                    .iter()
+                    .map(|(index, errors)| (index.to_string(), field_errors(errors)))
+                    .collect::<serde_json::Map<_, _>>()
+                    .into(),
+            };
+            (field.to_string(), details)
+        })
+        .collect();
+    fields.into()
+}
+
+fn error(code: &'static str, message: &'static str) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(Cow::Borrowed(message));
+    error
+}
+
+pub fn hex(value: &str) -> Result<(), ValidationError> {
+    if value.len().is_multiple_of(2) && value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(error("hex", "must be an even number of hexadecimal digits"))
+    }
+}
+
+pub fn patch_hex(value: &Patch<String>) -> Result<(), ValidationError> {
+    value.value().map_or(Ok(()), |value| hex(value))
+}
+
+pub fn known_scopes(scopes: &[String]) -> Result<(), ValidationError> {
+    if scopes.iter().all(|scope| KNOWN_SCOPES.contains(&scope.as_str())) {
+        Ok(())
+    } else {
+        Err(error("scope", "contains an unknown scope"))
+    }
+}
+
+pub fn known_event_types(event_types: &[String]) -> Result<(), ValidationError> {
+    if event_types.iter().all(|event_type| WEBHOOK_EVENT_TYPES.contains(&event_type.as_str())) {
+        Ok(())
+    } else {
+        Err(error("event_type", "contains an unknown event type"))
+    }
+}
+
+pub fn known_topics(topics: &str) -> Result<(), ValidationError> {
+    if topics.split(',').all(|topic| LIVE_TOPICS.contains(&topic.trim())) {
+        Ok(())
+    } else {
+        Err(error("topic", "must be a comma-separated list of merchant_records, chain_head, proofs, dogs"))
+    }
+}
+
+pub fn webhook_url(value: &str) -> Result<(), ValidationError> {
+    match url::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => Ok(()),
+        _ => Err(error("url", "must be an absolute http or https URL")),
+    }
+}
+
+pub fn lost_report_status(status: &str) -> Result<(), ValidationError> {
+    if LOST_REPORT_STATUSES.contains(&status) {
+        Ok(())
+    } else {
+        Err(error("enum", "must be one of lost, found, reunited, cancelled"))
+    }
+}
+
+// Merge-patch fields are checked like `Option`s: only a given value has to satisfy the rule.
+
+impl<T, L> ValidateLength<L> for Patch<T>
+where
+    T: ValidateLength<L>,
+    L: PartialEq + PartialOrd,
+{
+    fn length(&self) -> Option<L> {
+        self.value().and_then(|value| value.length())
+    }
+}
+
+impl<T, R> ValidateRange<R> for Patch<T>
+where
+    T: ValidateRange<R>,
+{
+    fn greater_than(&self, max: R) -> Option<bool> {
+        self.value().and_then(|value| value.greater_than(max))
+    }
+
+    fn less_than(&self, min: R) -> Option<bool> {
+        self.value().and_then(|value| value.less_than(min))
+    }
+}